@@ -0,0 +1,59 @@
+use porkbun::resolve::{PropagationReport, Resolver};
+use porkbun::{challenge_name, Client, RecordType};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Creates the TXT record answering a DNS-01 challenge, then waits up to `wait` for
+/// `resolvers` to return it, since the certificate authority checks as soon as this returns.
+pub fn auth(
+    client: &Client,
+    identifier: &str,
+    validation: &str,
+    wait: Duration,
+    resolvers: &[Resolver],
+) -> Result<(), String> {
+    let zone = zone(client, identifier)?;
+    client
+        .create_acme_challenge(&zone, identifier, validation)
+        .map_err(|msg| format!("failed to create challenge for {identifier}: {msg}"))?;
+    let name = challenge_name(identifier);
+    info!("created {name}");
+    if wait.is_zero() {
+        return Ok(());
+    }
+
+    let report = PropagationReport::wait(
+        resolvers,
+        &name,
+        RecordType::Txt,
+        validation,
+        wait,
+        |report| {
+            info!(
+                "{}/{} resolvers return the challenge",
+                report.matched(),
+                report.resolvers.len()
+            )
+        },
+    );
+    if !report.is_complete() {
+        warn!("{name} hasn't reached every resolver; validation may fail");
+    }
+    Ok(())
+}
+
+/// Deletes the TXT record created by [auth].
+pub fn cleanup(client: &Client, identifier: &str, validation: &str) -> Result<(), String> {
+    let zone = zone(client, identifier)?;
+    let deleted = client
+        .delete_acme_challenge(&zone, identifier, validation)
+        .map_err(|msg| format!("failed to delete challenge for {identifier}: {msg}"))?;
+    info!("deleted {deleted} challenge records of {identifier}");
+    Ok(())
+}
+
+fn zone(client: &Client, identifier: &str) -> Result<String, String> {
+    client
+        .find_zone(identifier.trim_start_matches("*."))
+        .map_err(|msg| format!("failed to find the domain of {identifier}: {msg}"))
+}
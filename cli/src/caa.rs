@@ -0,0 +1,114 @@
+use crate::dns::{self, Output};
+use porkbun::{CaaPolicy, CaaPosture, ChangeSet, Client};
+use tracing::{error, info};
+
+/// What to do about the CAA records of every domain in the account.
+#[derive(Clone, Debug)]
+pub struct Audit {
+    /// Authorities every domain should allow.
+    pub expect: Vec<String>,
+    /// Replace each domain's CAA records with ones allowing only `expect`.
+    pub apply: bool,
+    pub iodef: Option<String>,
+    pub jobs: usize,
+}
+
+/// Lists the CAA posture of every domain, flagging those without CAA records or which don't
+/// allow an expected authority, then optionally applies a standard policy to all of them.
+pub fn audit(client: &Client, audit: Audit, output: Output) -> Result<(), String> {
+    let domains: Vec<String> = client
+        .list_domains()
+        .map_err(|msg| format!("failed to list domains: {}", dns::explain(&msg)))?
+        .into_iter()
+        .map(|x| x.domain)
+        .collect();
+
+    let mut postures = Vec::new();
+    let mut fetched = Vec::new();
+    for (domain, records) in client.fetch_many(&domains, audit.jobs) {
+        match records {
+            Ok(records) => {
+                postures.push(CaaPosture::from_records(&domain, &records));
+                fetched.push((domain, records));
+            }
+            Err(msg) => error!("failed to fetch records of {domain}: {msg}"),
+        }
+    }
+
+    if output.json {
+        let json = serde_json::to_string_pretty(&postures)
+            .map_err(|msg| format!("failed to encode postures: {msg}"))?;
+        println!("{json}");
+    } else {
+        print_postures(&postures, &audit.expect);
+    }
+    if !audit.apply {
+        return Ok(());
+    }
+
+    let mut policy = CaaPolicy::new(audit.expect.iter().cloned());
+    if let Some(iodef) = audit.iodef {
+        policy = policy.with_iodef(iodef);
+    }
+    let plans: Vec<ChangeSet> = fetched
+        .iter()
+        .map(|(domain, records)| policy.plan(domain, records))
+        .filter(|x| !x.is_empty())
+        .collect();
+    if plans.is_empty() {
+        info!("every domain already has the policy");
+        return Ok(());
+    }
+    output.print_plans(&plans);
+    if !output.confirm(&format!("Apply the policy to {} domains?", plans.len()))? {
+        return Err("cancelled".to_string());
+    }
+
+    let mut failed = 0;
+    for changes in plans.iter().cloned() {
+        if let Err(msg) = dns::apply(client, changes) {
+            error!("{msg}");
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(format!(
+            "{failed} of {} domains failed to update",
+            plans.len()
+        ));
+    }
+    Ok(())
+}
+
+fn print_postures(postures: &[CaaPosture], expect: &[String]) {
+    let width = postures.iter().map(|x| x.domain.len()).max().unwrap_or(0);
+    let mut flagged = 0;
+    for posture in postures {
+        let missing = posture.missing(expect);
+        let (issuers, status) = if !posture.has_caa() {
+            (
+                "(no CAA)".to_string(),
+                "any authority may issue".to_string(),
+            )
+        } else if posture.issuers.is_empty() {
+            // Only issue properties restrict issuance, so without one any authority may issue
+            // (RFC 8659, section 4.2).
+            (
+                "(no issue records)".to_string(),
+                "any authority may issue".to_string(),
+            )
+        } else if missing.is_empty() {
+            (posture.issuers.join(", "), "ok".to_string())
+        } else {
+            (
+                posture.issuers.join(", "),
+                format!("doesn't allow {}", missing.join(", ")),
+            )
+        };
+        if status != "ok" {
+            flagged += 1;
+        }
+        println!("{:<width$}  {issuers}  [{status}]", posture.domain);
+    }
+    println!("\n{flagged}/{} domains flagged", postures.len());
+}
@@ -0,0 +1,88 @@
+use crate::dns;
+use porkbun::compat::{CompatReport, Golden};
+use porkbun::endpoints::Endpoint;
+use porkbun::{Client, DomainName};
+use std::path::{Path, PathBuf};
+
+/// Saves redacted responses of `ping`, `domain/listAll`, and `dns/retrieve` for each domain
+/// into `out`, then checks them as [check] does.
+pub fn capture(client: &Client, domains: &[DomainName], out: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(out)
+        .map_err(|msg| format!("failed to create {}: {msg}", out.display()))?;
+    let mut endpoints = vec![
+        ("ping".to_string(), Endpoint::Ping),
+        ("domain-listAll".to_string(), Endpoint::ListDomains),
+    ];
+    // Files are numbered rather than named after the domains, which are redacted.
+    for (i, domain) in domains.iter().enumerate() {
+        let endpoint = Endpoint::DnsRetrieve {
+            domain: domain.to_ascii(),
+        };
+        endpoints.push((format!("dns-retrieve-{}", i + 1), endpoint));
+    }
+
+    let mut reports = Vec::new();
+    for (name, endpoint) in endpoints {
+        let golden = client.capture(endpoint).map_err(|msg| {
+            format!(
+                "failed to capture {}: {}",
+                endpoint.operation(),
+                dns::explain(&msg)
+            )
+        })?;
+        let path = out.join(format!("{name}.json"));
+        golden
+            .save(&path)
+            .map_err(|msg| format!("failed to write {}: {msg}", path.display()))?;
+        reports.push((path, golden.check()));
+    }
+    print_reports(&reports);
+    tracing::info!(
+        "saved {} responses to {}; check them for anything private before attaching them to a bug report",
+        reports.len(),
+        out.display()
+    );
+    Ok(())
+}
+
+/// Replays the golden files at `paths`, or in them if they are directories, and reports any
+/// the client can't read.
+pub fn check(paths: &[PathBuf]) -> Result<(), String> {
+    let mut reports = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let checked = porkbun::compat::check_dir(path)
+                .map_err(|msg| format!("failed to check {}: {msg}", path.display()))?;
+            reports.extend(checked);
+        } else {
+            let golden = Golden::open(path)
+                .map_err(|msg| format!("failed to read {}: {msg}", path.display()))?;
+            reports.push((path.clone(), golden.check()));
+        }
+    }
+    print_reports(&reports);
+    let failed = reports.iter().filter(|(_, x)| !x.is_compatible()).count();
+    if failed > 0 {
+        return Err(format!(
+            "{failed} of {} responses can't be read",
+            reports.len()
+        ));
+    }
+    Ok(())
+}
+
+fn print_reports(reports: &[(PathBuf, CompatReport)]) {
+    for (path, report) in reports {
+        let path = path.display();
+        let operation = &report.operation;
+        match &report.error {
+            Some(error) if !report.is_compatible() => {
+                println!("fail {path} ({operation}): {error}")
+            }
+            _ => println!("ok   {path} ({operation})"),
+        }
+        for field in &report.unknown_fields {
+            println!("       ignores {field}");
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::dns::{self, Output};
+use porkbun::{ChangeSet, Client, DmarcPolicy, DnsRecord, RecordType};
+use tracing::{error, info};
+
+/// Publishes `policy` at every domain, along with the records which let the domains of its
+/// report addresses accept reports about them.
+///
+/// Authorizations are only published for report domains in this account.
+pub fn provision(
+    client: &Client,
+    domains: &[String],
+    policy: &DmarcPolicy,
+    jobs: usize,
+    output: Output,
+) -> Result<(), String> {
+    let account: Vec<String> = client
+        .list_domains()
+        .map_err(|msg| format!("failed to list domains: {}", dns::explain(&msg)))?
+        .into_iter()
+        .map(|x| x.domain)
+        .collect();
+
+    // Each report domain gets one plan, which authorizes every domain.
+    let mut authorizations: Vec<(String, Vec<porkbun::NewRecord>)> = Vec::new();
+    for domain in domains {
+        for (receiver, mut record) in policy.authorizations(domain) {
+            let Some(zone) = zone_of(&account, &receiver) else {
+                info!("{receiver} isn't in this account, so it must authorize reports for {domain} itself");
+                continue;
+            };
+            if let Some(subdomain) = receiver
+                .strip_suffix(zone)
+                .and_then(|x| x.strip_suffix('.'))
+            {
+                record.name = record.name.map(|x| format!("{x}.{subdomain}"));
+            }
+            match authorizations.iter_mut().find(|x| x.0 == zone) {
+                Some((_, records)) => records.push(record),
+                None => authorizations.push((zone.to_string(), vec![record])),
+            }
+        }
+    }
+
+    let zones: Vec<&str> = domains
+        .iter()
+        .map(String::as_str)
+        .chain(authorizations.iter().map(|x| x.0.as_str()))
+        .collect();
+    let mut fetched: Vec<(String, Vec<DnsRecord>)> = Vec::new();
+    for (domain, records) in client.fetch_many(&zones, jobs) {
+        let records = records.map_err(|msg| {
+            format!(
+                "failed to fetch records of {domain}: {}",
+                dns::explain(&msg)
+            )
+        })?;
+        fetched.push((domain, records));
+    }
+    let records_of = |zone: &str| {
+        fetched
+            .iter()
+            .find(|x| x.0 == zone)
+            .map(|x| x.1.as_slice())
+            .unwrap_or_default()
+    };
+
+    let mut plans: Vec<ChangeSet> = domains
+        .iter()
+        .map(|domain| policy.plan(domain, records_of(domain)))
+        .collect();
+    for (zone, desired) in &authorizations {
+        // Only the authorization records themselves are compared, so nothing else is touched.
+        let existing: Vec<DnsRecord> = records_of(zone)
+            .iter()
+            .filter(|x| x.ty == RecordType::Txt)
+            .filter(|x| {
+                desired
+                    .iter()
+                    .filter_map(|y| y.name.as_deref())
+                    .any(|y| x.name.eq_ignore_ascii_case(&format!("{y}.{zone}")))
+            })
+            .cloned()
+            .collect();
+        match plans.iter_mut().find(|x| x.domain == *zone) {
+            Some(plan) => plan
+                .create
+                .extend(ChangeSet::diff(zone, &existing, desired).create),
+            None => plans.push(ChangeSet::diff(zone, &existing, desired)),
+        }
+    }
+    plans.retain(|x| !x.is_empty());
+    if plans.is_empty() {
+        info!("every domain already has the policy");
+        return Ok(());
+    }
+
+    output.print_plans(&plans);
+    if !output.confirm(&format!("Apply the changes to {} domains?", plans.len()))? {
+        return Err("cancelled".to_string());
+    }
+    let mut failed = 0;
+    for changes in plans.iter().cloned() {
+        if let Err(msg) = dns::apply(client, changes) {
+            error!("{msg}");
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(format!(
+            "{failed} of {} domains failed to update",
+            plans.len()
+        ));
+    }
+    Ok(())
+}
+
+/// The longest domain of the account which `name` is, or is beneath.
+fn zone_of<'a>(account: &'a [String], name: &str) -> Option<&'a str> {
+    account
+        .iter()
+        .filter(|x| name.eq_ignore_ascii_case(x) || name.ends_with(&format!(".{x}")))
+        .max_by_key(|x| x.len())
+        .map(String::as_str)
+}
@@ -0,0 +1,985 @@
+use crate::format::{parse_specs, Format};
+use crate::signing;
+use crate::vars::Vars;
+use porkbun::preset::SiteHost;
+use porkbun::resolve::{PropagationReport, Resolver};
+use porkbun::spf::SpfAnalysis;
+use porkbun::{
+    canonicalize, AuditEntry, ChangeOutcome, ChangeSet, Client, DnsRecord, JsonlSink, NewRecord,
+    PlanRenderer, RecordEdit, RecordId, RecordSpec, SyncOptions, TransactionReport, ZoneLock,
+    ZoneReport, ZoneSnapshot, ZoneSpec,
+};
+use porkbun::{RecordType, Ttl};
+use std::io::{BufRead, IsTerminal, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+pub fn export(
+    client: &Client,
+    domain: &str,
+    format: Format,
+    output: Option<&Path>,
+) -> Result<(), String> {
+    let records: Vec<NewRecord> = fetch(client, domain)?
+        .iter()
+        .map(|x| NewRecord::from_existing(domain, x))
+        .collect();
+    write_output(output, &format.export(domain, &records))
+}
+
+/// Writes a domain's records as a zone spec, with their notes as comments.
+///
+/// With a `sign_key`, the spec's signature is written next to the `output` file.
+pub fn adopt(
+    client: &Client,
+    domain: &str,
+    output: Option<&Path>,
+    sign_key: Option<&Path>,
+    json: bool,
+) -> Result<(), String> {
+    let spec = ZoneSpec::from_records(domain, &fetch(client, domain)?);
+    if let Some(older) = output.and_then(|x| previous_snapshot(x, domain)) {
+        let summary = ZoneSnapshot::from_spec(&spec).diff(&older);
+        if json {
+            println!("{}", summary.json());
+        } else {
+            print!("{summary}");
+        }
+    }
+    let spec = spec.to_toml();
+    write_output(output, &spec)?;
+    match (output, sign_key) {
+        (Some(output), Some(key)) => signing::sign(output, &spec, key),
+        _ => Ok(()),
+    }
+}
+
+/// Reads the snapshot of `domain` which a previous [adopt] left at `path`, if there is one.
+fn previous_snapshot(path: &Path, domain: &str) -> Option<ZoneSnapshot> {
+    let text = std::fs::read_to_string(path).ok()?;
+    let specs = match parse_specs(&text, spec_dir(path), None) {
+        Ok(specs) => specs,
+        Err(msg) => {
+            warn!("can't compare with {}: {msg}", path.display());
+            return None;
+        }
+    };
+    specs
+        .iter()
+        .find(|x| x.domain.trim_end_matches('.').eq_ignore_ascii_case(domain))
+        .map(ZoneSnapshot::from_spec)
+}
+
+pub fn import(client: &Client, domain: &str, source: Source, dry_run: bool) -> Result<(), String> {
+    let records = source.records(domain)?;
+    let existing = fetch(client, domain)?;
+    let exists = |record: &NewRecord| {
+        existing.iter().any(|x| {
+            let x = NewRecord::from_existing(domain, x);
+            x.name == record.name
+                && x.ty == record.ty
+                && canonicalize(x.ty, &x.content) == canonicalize(record.ty, &record.content)
+        })
+    };
+
+    let mut created = 0;
+    for record in records.iter().filter(|x| !exists(x)) {
+        let name = record.name.as_deref().unwrap_or("@");
+        if dry_run {
+            info!(
+                "would create {} record {name} -> {}",
+                record.ty, record.content
+            );
+            continue;
+        }
+        client
+            .create_record(
+                domain,
+                record.name.as_deref(),
+                record.ty,
+                &record.content,
+                record.ttl,
+                record.prio.as_deref(),
+            )
+            .map_err(|msg| format!("failed to create {} record {name}: {msg}", record.ty))?;
+        info!("created {} record {name} -> {}", record.ty, record.content);
+        created += 1;
+    }
+    if !dry_run {
+        info!(
+            "imported {created} records ({} already existed)",
+            records.len() - created
+        );
+    }
+    Ok(())
+}
+
+/// Prints the changes which would make a domain's records match a file.
+pub fn diff(client: &Client, domain: &str, source: Source, output: Output) -> Result<(), String> {
+    let changes = plan(client, domain, source, &SyncOptions::new().prune(true))?;
+    output.print_plan(&changes);
+    Ok(())
+}
+
+/// Makes a domain's records match a file.
+///
+/// Records which aren't in the file are only deleted if `options` prune, and live records
+/// are reconciled with each type's conflict strategy. With `lock`, the zone is
+/// [locked](porkbun::ZoneLock) for up to that long until the changes are applied.
+pub fn sync(
+    client: &Client,
+    domain: &str,
+    source: Source,
+    options: &SyncOptions,
+    output: Output,
+    dry_run: bool,
+    lock: Option<Duration>,
+) -> Result<(), String> {
+    let _lock = lock.map(|x| self::lock(client, domain, x)).transpose()?;
+    let changes = plan(client, domain, source, options)?;
+    output.print_plan(&changes);
+    if dry_run || changes.is_empty() {
+        return Ok(());
+    }
+    output.confirm_deletions(std::slice::from_ref(&changes))?;
+
+    apply(client, changes)
+}
+
+/// Applies a plan, reverting it if any change fails.
+pub fn apply(client: &Client, changes: ChangeSet) -> Result<(), String> {
+    let domain = changes.domain.clone();
+    let report = client
+        .apply_transaction(&domain, changes.into_changes())
+        .map_err(|msg| format!("failed to apply changes to {domain}: {}", explain(&msg)))?;
+    check_report(&domain, &report)
+}
+
+/// Logs the changes of a transaction which failed, and fails with whether it left the domain
+/// partially changed.
+fn check_report(domain: &str, report: &TransactionReport) -> Result<(), String> {
+    if report.is_success() {
+        info!("applied {} changes to {domain}", report.changes.len());
+        return Ok(());
+    }
+    for (change, outcome) in &report.changes {
+        match outcome {
+            ChangeOutcome::Failed(msg) => error!("failed to apply {change:?}: {msg}"),
+            ChangeOutcome::RevertFailed(msg) => error!("failed to revert {change:?}: {msg}"),
+            _ => {}
+        }
+    }
+    if report.is_consistent() {
+        Err(format!("no changes were made to {domain}"))
+    } else {
+        Err(format!("{domain} was left partially changed"))
+    }
+}
+
+/// Prints statistics and likely mistakes in a domain's records.
+pub fn report(client: &Client, domain: &str, json: bool) -> Result<(), String> {
+    let report = ZoneReport::analyze(&fetch(client, domain)?);
+    if json {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|msg| format!("failed to encode report: {msg}"))?;
+        println!("{json}");
+    } else {
+        print!("{report}");
+    }
+    Ok(())
+}
+
+/// Prints what each resolver returns for a record, redrawing the table until they all return
+/// `expected` if there's time to `wait`.
+pub fn propagation(
+    name: &str,
+    ty: RecordType,
+    expected: &str,
+    resolvers: &[Resolver],
+    wait: Option<Duration>,
+    json: bool,
+) -> Result<(), String> {
+    let live = !json && std::io::stdout().is_terminal();
+    let mut lines = 0;
+    let show = |report: &PropagationReport| {
+        if json {
+            return;
+        }
+        let table = report.to_string();
+        if live && lines > 0 {
+            // Move back up over the previous table and clear it.
+            print!("\x1b[{lines}A\x1b[J");
+        }
+        lines = table.lines().count();
+        print!("{table}");
+    };
+    let report = match wait {
+        Some(timeout) => PropagationReport::wait(resolvers, name, ty, expected, timeout, show),
+        None => {
+            let report = PropagationReport::check(resolvers, name, ty, expected);
+            let mut show = show;
+            show(&report);
+            report
+        }
+    };
+    if json {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|msg| format!("failed to encode report: {msg}"))?;
+        println!("{json}");
+    }
+    if report.is_complete() {
+        Ok(())
+    } else {
+        Err(format!(
+            "only {}/{} resolvers return {expected} for {name}",
+            report.matched(),
+            report.resolvers.len()
+        ))
+    }
+}
+
+/// Prints an analysis of a domain's SPF policy. With `flatten`, also plans replacing it with
+/// a flattened policy, which is applied if `flatten` is `Some(true)`.
+pub fn spf(
+    client: &Client,
+    domain: &str,
+    resolver: &Resolver,
+    flatten: Option<bool>,
+    output: Output,
+) -> Result<(), String> {
+    let analysis = SpfAnalysis::analyze(resolver, domain)
+        .map_err(|msg| format!("failed to analyze the SPF policy of {domain}: {msg}"))?;
+    if output.json {
+        let json = serde_json::to_string_pretty(&analysis)
+            .map_err(|msg| format!("failed to encode analysis: {msg}"))?;
+        println!("{json}");
+    } else {
+        print!("{analysis}");
+    }
+    let Some(apply) = flatten else {
+        return Ok(());
+    };
+
+    let Some(flattened) = analysis.flatten() else {
+        return Err(format!("the SPF policy of {domain} can't be flattened"));
+    };
+    if !output.json {
+        println!("\nFlattened: {flattened}\n");
+    }
+    let changes = flattened.plan(domain, &fetch(client, domain)?);
+    output.print_plan(&changes);
+    if apply && !changes.is_empty() {
+        self::apply(client, changes)?;
+    }
+    Ok(())
+}
+
+/// A bundle of records for a self-hosted service or static site host.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum Preset {
+    /// Matrix homeserver federation.
+    Matrix,
+    /// XMPP client and server connections.
+    Xmpp,
+    /// SIP over UDP, TCP, and TLS.
+    Sip,
+    /// A Minecraft Java Edition server.
+    Minecraft,
+    /// A GitHub Pages site at the domain and www.
+    GithubPages,
+    /// A Netlify site at the domain and www.
+    Netlify,
+    /// A Vercel project at the domain and www.
+    Vercel,
+}
+
+/// Where a preset's records point.
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct PresetOptions {
+    /// The server's hostname, such as matrix.example.com.
+    #[clap(long)]
+    host: Option<String>,
+    /// The service's port, if it isn't the default.
+    #[clap(long)]
+    port: Option<u16>,
+    /// The second port of services with two: XMPP's server-to-server port, or SIP's TLS port.
+    #[clap(long)]
+    second_port: Option<u16>,
+    /// Where Minecraft players connect, such as mc for mc.example.com.
+    #[clap(long)]
+    subdomain: Option<String>,
+    /// The GitHub user or organization, or the Netlify site name.
+    #[clap(long)]
+    site: Option<String>,
+}
+
+/// Gives a domain a preset's records, replacing those in the way. For static site hosts,
+/// also checks whether the change has propagated and prints what's left to do.
+pub fn preset(
+    client: &Client,
+    domain: &str,
+    preset: Preset,
+    options: &PresetOptions,
+    output: Output,
+    dry_run: bool,
+) -> Result<(), String> {
+    use porkbun::preset;
+
+    let records = fetch(client, domain)?;
+    let host = || {
+        options
+            .host
+            .as_deref()
+            .ok_or_else(|| "this preset needs --host".to_string())
+    };
+    let site = || {
+        options
+            .site
+            .as_deref()
+            .ok_or_else(|| "this preset needs --site".to_string())
+    };
+    let service = |desired: Vec<NewRecord>| (preset::plan(domain, &records, &desired), None);
+    let (changes, site_host): (_, Option<SiteHost>) = match preset {
+        Preset::Matrix => service(preset::matrix(host()?, options.port)),
+        Preset::Xmpp => service(preset::xmpp(host()?, options.port, options.second_port)),
+        Preset::Sip => service(preset::sip(host()?, options.port, options.second_port)),
+        Preset::Minecraft => service(preset::minecraft(
+            host()?,
+            options.port,
+            options.subdomain.as_deref(),
+        )),
+        Preset::GithubPages | Preset::Netlify | Preset::Vercel => {
+            let site_host = match preset {
+                Preset::GithubPages => preset::github_pages(domain, site()?),
+                Preset::Netlify => preset::netlify(domain, site()?),
+                _ => preset::vercel(domain),
+            };
+            (site_host.plan(domain, &records), Some(site_host))
+        }
+    };
+    output.print_plan(&changes);
+    if dry_run {
+        return Ok(());
+    }
+    if !changes.is_empty() {
+        output.confirm_deletions(std::slice::from_ref(&changes))?;
+    }
+    let Some(site_host) = site_host else {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        return apply(client, changes);
+    };
+
+    let setup = client
+        .apply_site_plan(site_host, changes)
+        .map_err(|msg| format!("failed to apply changes to {domain}: {}", explain(&msg)))?;
+    if !setup.report.changes.is_empty() {
+        check_report(domain, &setup.report)?;
+    }
+    if let Some(propagation) = setup.propagation {
+        println!();
+        print!("{propagation}");
+    }
+    println!("\nRemaining steps:");
+    for step in &setup.steps {
+        println!("  - {step}");
+    }
+    Ok(())
+}
+
+/// Gives the records matching `filter` a new ttl.
+pub fn set_ttl(
+    client: &Client,
+    domain: &str,
+    ttl: Ttl,
+    filter: impl Fn(&DnsRecord) -> bool,
+    output: Output,
+    dry_run: bool,
+) -> Result<(), String> {
+    let changes = client
+        .plan_zone_ttl(domain, ttl, filter)
+        .map_err(|msg| format!("failed to plan ttl changes for {domain}: {msg}"))?;
+    output.print_plan(&changes);
+    if dry_run || changes.is_empty() {
+        return Ok(());
+    }
+    apply(client, changes)
+}
+
+/// A record to create, described with flags rather than in porkbun's content format.
+#[derive(clap::Args, Clone, Debug)]
+pub struct RecordArgs {
+    #[clap(value_name = "TYPE")]
+    ty: RecordType,
+    /// The record's subdomain, or `@` for the domain itself.
+    name: String,
+    /// The record's content, such as an address or hostname. SRV records may use --port and
+    /// --target instead.
+    #[clap(required_unless_present = "target")]
+    content: Option<String>,
+    #[clap(long)]
+    ttl: Option<Ttl>,
+    /// The priority of an MX, SRV, HTTPS, or SVCB record.
+    #[clap(long)]
+    prio: Option<u16>,
+    /// The weight of an SRV record among those with the same priority.
+    #[clap(long, requires = "target")]
+    weight: Option<u16>,
+    /// The port of an SRV record's service.
+    #[clap(long, requires = "target")]
+    port: Option<u16>,
+    /// The host of an SRV record's service.
+    #[clap(long, requires = "port", conflicts_with = "content")]
+    target: Option<String>,
+}
+
+impl RecordArgs {
+    /// Builds the record, failing if a flag doesn't apply to its type or the record is one
+    /// porkbun would reject or resolvers would ignore.
+    fn to_record(&self, domain: &str) -> Result<NewRecord, String> {
+        let ty = self.ty;
+        let has_prio = matches!(
+            ty,
+            RecordType::Mx | RecordType::Srv | RecordType::Https | RecordType::Svcb
+        );
+        if self.prio.is_some() && !has_prio {
+            return Err(format!(
+                "--prio only applies to MX, SRV, HTTPS, and SVCB records, not {ty}"
+            ));
+        }
+        if self.target.is_some() && ty != RecordType::Srv {
+            return Err(format!(
+                "--weight, --port, and --target only apply to SRV records, not {ty}"
+            ));
+        }
+        let content = match (&self.content, &self.target) {
+            (Some(content), _) => content.clone(),
+            (None, Some(target)) => format!(
+                "{} {} {}",
+                self.weight.unwrap_or(0),
+                self.port.unwrap_or_default(),
+                target.trim_end_matches('.')
+            ),
+            (None, None) => return Err("the record's content is missing".into()),
+        };
+        let name = Some(self.name.as_str()).filter(|x| !matches!(*x, "" | "@"));
+
+        let spec = ZoneSpec {
+            domain: domain.to_string(),
+            records: vec![RecordSpec {
+                name: name.map(str::to_string),
+                ty,
+                content,
+                ttl: self.ttl,
+                prio: self.prio,
+                notes: None,
+            }],
+            ..ZoneSpec::default()
+        };
+        if let Some(problem) = spec.validate().into_iter().next() {
+            return Err(problem.error.to_string());
+        }
+        Ok(spec.records[0].to_record())
+    }
+}
+
+/// Creates a record, failing if it would conflict with existing records.
+pub fn create(
+    client: &Client,
+    domain: &str,
+    record: &RecordArgs,
+    output: Output,
+    dry_run: bool,
+) -> Result<(), String> {
+    let record = record.to_record(domain)?;
+    output.print_plan(&ChangeSet {
+        domain: domain.to_string(),
+        create: vec![record.clone()],
+        ..ChangeSet::default()
+    });
+    if dry_run {
+        return Ok(());
+    }
+    let id = client
+        .create_record_checked(
+            domain,
+            record.name.as_deref(),
+            record.ty,
+            &record.content,
+            record.ttl,
+            record.prio.as_deref(),
+        )
+        .map_err(|msg| format!("failed to create {} record: {}", record.ty, explain(&msg)))?;
+    match id {
+        Some(id) => info!("created {} record {id}", record.ty),
+        None => info!("created {} record", record.ty),
+    }
+    Ok(())
+}
+
+/// The fields of a record to change; the rest are kept as they are.
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct RecordChanges {
+    /// Move the record to this subdomain, or `@` for the domain itself.
+    #[clap(long)]
+    name: Option<String>,
+    #[clap(long)]
+    content: Option<String>,
+    #[clap(long)]
+    ttl: Option<Ttl>,
+    #[clap(long)]
+    prio: Option<String>,
+    #[clap(long)]
+    notes: Option<String>,
+}
+
+/// Changes some fields of a record, or replaces it with one read from `from_json`, such as
+/// a record printed by `grep --json` and then edited.
+pub fn edit(
+    client: &Client,
+    domain: &str,
+    id: Option<RecordId>,
+    changes: &RecordChanges,
+    from_json: Option<&Path>,
+    output: Output,
+    dry_run: bool,
+) -> Result<(), String> {
+    let edited = from_json.map(read_record).transpose()?;
+    let id = match (id, &edited) {
+        (Some(id), Some(edited)) if id != edited.id => {
+            return Err(format!(
+                "the record in the file has id {}, not {id}",
+                edited.id
+            ));
+        }
+        (_, Some(edited)) => edited.id,
+        (Some(id), None) => id,
+        (None, None) => return Err("the record's id is missing".into()),
+    };
+    let current = client
+        .fetch_record(domain, id)
+        .map_err(|msg| format!("failed to fetch record {id}: {}", explain(&msg)))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("{domain} has no record with id {id}"))?;
+
+    let (record, notes) = match edited {
+        Some(edited) => (NewRecord::from_existing(domain, &edited), edited.notes),
+        None => {
+            let mut record = NewRecord::from_existing(domain, &current);
+            if let Some(name) = &changes.name {
+                record.name = Some(name.clone()).filter(|x| !matches!(x.as_str(), "" | "@"));
+            }
+            if let Some(content) = &changes.content {
+                record.content = content.clone();
+            }
+            record.ttl = changes.ttl.or(record.ttl);
+            record.prio = changes.prio.clone().or(record.prio);
+            let notes = changes.notes.clone().or(current.notes.clone());
+            (record, notes)
+        }
+    };
+    let notes = notes.filter(|x| !x.is_empty());
+    if record == NewRecord::from_existing(domain, &current)
+        && notes == current.notes.clone().filter(|x| !x.is_empty())
+    {
+        info!("record {id} already matches");
+        return Ok(());
+    }
+
+    output.print_plan(&ChangeSet {
+        domain: domain.to_string(),
+        edit: vec![RecordEdit {
+            before: current,
+            after: record.clone(),
+        }],
+        ..ChangeSet::default()
+    });
+    if dry_run {
+        return Ok(());
+    }
+    client
+        .edit_record_with_notes(domain, id, &record, notes.as_deref())
+        .map_err(|msg| format!("failed to edit record {id}: {}", explain(&msg)))?;
+    info!("edited record {id}");
+    Ok(())
+}
+
+/// Reads a record in porkbun's JSON form, or a list holding just one.
+fn read_record(path: &Path) -> Result<DnsRecord, String> {
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum Records {
+        One(DnsRecord),
+        Many(Vec<DnsRecord>),
+    }
+
+    let invalid = |msg: String| format!("failed to parse {}: {msg}", path.display());
+    match serde_json::from_str(&read_input(path)?).map_err(|msg| invalid(msg.to_string()))? {
+        Records::One(record) => Ok(record),
+        Records::Many(mut records) if records.len() == 1 => Ok(records.remove(0)),
+        Records::Many(records) => Err(invalid(format!(
+            "expected one record, but found {}",
+            records.len()
+        ))),
+    }
+}
+
+/// Deletes records by id, along with any picked from a numbered list with `select`.
+///
+/// The list only shows records whose type, name, and content contain every word of `select`.
+pub fn delete(
+    client: &Client,
+    domain: &str,
+    ids: &[RecordId],
+    select: Option<&str>,
+    output: Output,
+    dry_run: bool,
+) -> Result<(), String> {
+    let records = fetch(client, domain)?;
+    let mut changes = ChangeSet {
+        domain: domain.to_string(),
+        ..ChangeSet::default()
+    };
+    for id in ids {
+        let record = records
+            .iter()
+            .find(|x| x.id == *id)
+            .ok_or_else(|| format!("{domain} has no record with id {id}"))?;
+        changes.delete.push(record.clone());
+    }
+    if let Some(query) = select {
+        for record in pick(&records, query, output)? {
+            if !changes.delete.iter().any(|x| x.id == record.id) {
+                changes.delete.push(record);
+            }
+        }
+    }
+    if changes.is_empty() {
+        info!("nothing to delete");
+        return Ok(());
+    }
+    output.print_plan(&changes);
+    if dry_run {
+        return Ok(());
+    }
+    output.confirm_deletions(std::slice::from_ref(&changes))?;
+    apply(client, changes)
+}
+
+/// Lists the records matching `query` with numbers, and asks which to pick.
+fn pick(records: &[DnsRecord], query: &str, output: Output) -> Result<Vec<DnsRecord>, String> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let matches: Vec<&DnsRecord> = records
+        .iter()
+        .filter(|x| {
+            let line = format!("{} {} {}", x.ty, x.name, x.content).to_lowercase();
+            words.iter().all(|word| line.contains(word))
+        })
+        .collect();
+    if matches.is_empty() {
+        return Err(format!("no records match \"{query}\""));
+    }
+
+    let ty_width = matches
+        .iter()
+        .map(|x| x.ty.as_str().len())
+        .max()
+        .unwrap_or(0);
+    let name_width = matches.iter().map(|x| x.name.len()).max().unwrap_or(0);
+    for (i, record) in matches.iter().enumerate() {
+        println!(
+            "{:>3}  {:ty_width$}  {:name_width$}  {}",
+            i + 1,
+            record.ty.as_str(),
+            record.name,
+            record.content
+        );
+    }
+    let answer = output.ask("Records to delete, such as 1,3-5:")?;
+    Ok(parse_selection(&answer, matches.len())?
+        .into_iter()
+        .map(|i| matches[i].clone())
+        .collect())
+}
+
+/// Parses numbers and ranges from 1 to `len`, such as `1,3-5`, into indices.
+fn parse_selection(text: &str, len: usize) -> Result<Vec<usize>, String> {
+    let mut picked = Vec::new();
+    for part in text
+        .split([',', ' '])
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+    {
+        let parse = |x: &str| {
+            x.trim()
+                .parse()
+                .ok()
+                .filter(|x| (1..=len).contains(x))
+                .ok_or_else(|| format!("\"{part}\" isn't a number from 1 to {len}"))
+        };
+        let (start, end) = part.split_once('-').unwrap_or((part, part));
+        for i in parse(start)?..=parse(end)? {
+            if !picked.contains(&(i - 1)) {
+                picked.push(i - 1);
+            }
+        }
+    }
+    Ok(picked)
+}
+
+/// Copies the records of `source` to `domain`, after letting `adapt` drop or rewrite them.
+pub fn clone(
+    client: &Client,
+    source: &str,
+    domain: &str,
+    adapt: impl FnOnce(&mut ZoneSpec),
+    prune: bool,
+    output: Output,
+    dry_run: bool,
+) -> Result<(), String> {
+    let mut spec = ZoneSpec::from_records(source, &fetch(client, source)?);
+    adapt(&mut spec);
+    let mut changes = ChangeSet::diff(domain, &fetch(client, domain)?, &spec.to_records());
+    if !prune {
+        changes.delete.clear();
+    }
+    output.print_plan(&changes);
+    if dry_run || changes.is_empty() {
+        return Ok(());
+    }
+    output.confirm_deletions(std::slice::from_ref(&changes))?;
+    apply(client, changes)
+}
+
+/// Reverses the `last` changes recorded in an audit log, most recent first.
+///
+/// Each change is undone as its own transaction, stopping at the first which fails.
+pub fn undo(
+    client: &Client,
+    log: &Path,
+    last: usize,
+    output: Output,
+    dry_run: bool,
+) -> Result<(), String> {
+    let entries =
+        JsonlSink::read(log).map_err(|msg| format!("failed to read {}: {msg}", log.display()))?;
+    let plans: Vec<ChangeSet> = entries
+        .iter()
+        .rev()
+        .take(last)
+        .map(AuditEntry::undo)
+        .collect();
+    if plans.len() < last {
+        info!("the audit log only has {} changes", plans.len());
+    }
+    output.print_plans(&plans);
+    if dry_run || plans.iter().all(ChangeSet::is_empty) {
+        return Ok(());
+    }
+    if !output.confirm(&format!("Undo {} changes?", plans.len()))? {
+        return Err("cancelled".to_string());
+    }
+    for changes in plans.into_iter().filter(|x| !x.is_empty()) {
+        apply(client, changes)?;
+    }
+    Ok(())
+}
+
+fn plan(
+    client: &Client,
+    domain: &str,
+    source: Source,
+    options: &SyncOptions,
+) -> Result<ChangeSet, String> {
+    let desired = source.records(domain)?;
+    ChangeSet::diff_with(domain, &fetch(client, domain)?, &desired, options)
+        .map_err(|msg| format!("can't sync {domain}: {msg}"))
+}
+
+/// A file of records in another tool's format.
+#[derive(Clone, Copy, Debug)]
+pub struct Source<'a> {
+    pub format: Format,
+    /// The file to read, or `-` for stdin.
+    pub path: &'a Path,
+    pub vars: &'a Vars,
+    /// A public key the file must have been signed with, such as for restoring a backup.
+    /// Files it includes and the vars file must be signed with it too.
+    pub verify_key: Option<&'a Path>,
+}
+
+impl Source<'_> {
+    fn records(self, domain: &str) -> Result<Vec<NewRecord>, String> {
+        self.format.import(
+            domain,
+            &self.read()?,
+            spec_dir(self.path),
+            self.vars,
+            self.verify_key,
+        )
+    }
+
+    /// Reads the file, checking its signature if there is a `verify_key`.
+    pub fn read(self) -> Result<String, String> {
+        let text = read_input(self.path)?;
+        if let Some(key) = self.verify_key {
+            signing::verify(self.path, &text, key)?;
+        }
+        Ok(text)
+    }
+}
+
+/// How plans are printed, and whether they are confirmed before being applied.
+#[derive(Clone, Copy, Debug)]
+pub struct Output {
+    pub json: bool,
+    /// Show internationalized names in their Unicode form.
+    pub unicode: bool,
+    /// Apply without asking first.
+    pub yes: bool,
+    /// Fail rather than ask, even on a terminal.
+    pub no_input: bool,
+}
+
+impl Output {
+    /// Asks a yes or no question on the terminal, unless `yes` is set. Fails with `no_input`
+    /// or if stdin isn't a terminal, since nobody could answer.
+    pub fn confirm(self, question: &str) -> Result<bool, String> {
+        if self.yes {
+            return Ok(true);
+        }
+        if self.no_input {
+            return Err(
+                "refusing to apply changes without --yes, since --no-input was given".into(),
+            );
+        }
+        if !std::io::stdin().is_terminal() {
+            return Err(
+                "refusing to apply changes without --yes, since stdin isn't a terminal".into(),
+            );
+        }
+        let answer = self.ask(&format!("{question} [y/N]"))?;
+        Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+    }
+
+    /// Asks for a line of input on the terminal. Fails with `no_input` or if stdin isn't a
+    /// terminal.
+    fn ask(self, question: &str) -> Result<String, String> {
+        if self.no_input {
+            return Err("refusing to ask for input, since --no-input was given".into());
+        }
+        if !std::io::stdin().is_terminal() {
+            return Err("refusing to ask for input, since stdin isn't a terminal".into());
+        }
+        print!("{question} ");
+        std::io::stdout().flush().ok();
+        let mut answer = String::new();
+        std::io::stdin()
+            .lock()
+            .read_line(&mut answer)
+            .map_err(|msg| format!("failed to read answer: {msg}"))?;
+        Ok(answer)
+    }
+
+    /// Asks before applying printed plans which delete records, failing if the answer is no.
+    pub fn confirm_deletions(self, plans: &[ChangeSet]) -> Result<(), String> {
+        let deletions: usize = plans.iter().map(|x| x.delete.len()).sum();
+        if deletions == 0 || self.confirm(&format!("Delete {deletions} records?"))? {
+            Ok(())
+        } else {
+            Err("cancelled".to_string())
+        }
+    }
+
+    fn print_plan(self, changes: &ChangeSet) {
+        if self.json {
+            println!("{}", PlanRenderer::new().json(changes));
+        } else {
+            let renderer = PlanRenderer::for_stdout().unicode(self.unicode);
+            print!("{}", renderer.render(changes));
+        }
+    }
+
+    /// Prints the plans of several domains, followed by their totals.
+    pub fn print_plans(self, plans: &[ChangeSet]) {
+        if self.json {
+            // Records are plain strings and numbers, so this can't fail.
+            println!(
+                "{}",
+                serde_json::to_string_pretty(plans).unwrap_or_default()
+            );
+            return;
+        }
+        let renderer = PlanRenderer::for_stdout().unicode(self.unicode);
+        for changes in plans {
+            println!("{}:", changes.domain);
+            print!("{}", renderer.render(changes));
+            println!();
+        }
+        let count = |f: fn(&ChangeSet) -> usize| plans.iter().map(f).sum::<usize>();
+        println!(
+            "Total: {} zones, {} to create, {} to edit, {} to delete.",
+            plans.len(),
+            count(|x| x.create.len()),
+            count(|x| x.edit.len()),
+            count(|x| x.delete.len())
+        );
+    }
+}
+
+/// Takes the lock on a zone, naming this host and process as its holder.
+pub fn lock(client: &Client, domain: &str, duration: Duration) -> Result<ZoneLock, String> {
+    let host = std::env::var("HOSTNAME")
+        .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+        .map_or_else(|_| "unknown host".to_string(), |x| x.trim().to_string());
+    let holder = format!("porkbun on {host}, pid {}", std::process::id());
+    client
+        .lock_zone(domain, &holder, duration)
+        .map_err(|msg| format!("failed to lock {domain}: {}", explain(&msg)))
+}
+
+pub fn fetch(client: &Client, domain: &str) -> Result<Vec<DnsRecord>, String> {
+    client
+        .fetch_records(domain)
+        .map_err(|msg| format!("failed to fetch records of {domain}: {}", explain(&msg)))
+}
+
+/// Formats an error along with its [explanation](porkbun::Error::explanation), if any.
+pub fn explain(error: &porkbun::Error) -> String {
+    match error.explanation() {
+        Some(hint) => format!("{error}\n  hint: {hint}"),
+        None => error.to_string(),
+    }
+}
+
+/// The directory paths in a spec file are relative to; the working directory for stdin.
+pub fn spec_dir(path: &Path) -> &Path {
+    path.parent().unwrap_or(Path::new(""))
+}
+
+/// Reads a file, or stdin if `path` is `-`.
+pub fn read_input(path: &Path) -> Result<String, String> {
+    let mut text = String::new();
+    if path == Path::new("-") {
+        std::io::stdin().read_to_string(&mut text)
+    } else {
+        std::fs::File::open(path).and_then(|mut file| file.read_to_string(&mut text))
+    }
+    .map_err(|msg| format!("failed to read {}: {msg}", path.display()))?;
+    Ok(text)
+}
+
+/// Writes to a file, or stdout if there is no `path`.
+fn write_output(path: Option<&Path>, text: &str) -> Result<(), String> {
+    match path {
+        Some(path) => std::fs::write(path, text)
+            .map_err(|msg| format!("failed to write {}: {msg}", path.display())),
+        None => {
+            print!("{text}");
+            Ok(())
+        }
+    }
+}
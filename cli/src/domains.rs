@@ -0,0 +1,68 @@
+use crate::dns;
+use chrono::{DateTime, Local, Utc};
+use porkbun::{Client, DomainInfo};
+
+/// Lists the domains in the account with their registration and expiry dates, in the local
+/// time zone unless `utc` is set.
+pub fn list(client: &Client, json: bool, utc: bool) -> Result<(), String> {
+    #[derive(serde::Serialize)]
+    struct Row<'a> {
+        domain: &'a str,
+        status: Option<&'a str>,
+        created: Option<String>,
+        expires: Option<String>,
+        days_remaining: Option<i64>,
+    }
+
+    let domains = client
+        .list_domains()
+        .map_err(|msg| format!("failed to list domains: {}", dns::explain(&msg)))?;
+    let now = Utc::now();
+    let date = |date: Option<DateTime<Utc>>, format: &str| {
+        date.map(|x| {
+            if utc {
+                x.format(format).to_string()
+            } else {
+                x.with_timezone(&Local).format(format).to_string()
+            }
+        })
+    };
+
+    if json {
+        let rows: Vec<Row> = domains
+            .iter()
+            .map(|x| Row {
+                domain: &x.domain,
+                status: x.status.as_deref(),
+                created: date(x.created_at(), "%+"),
+                expires: date(x.expires_at(), "%+"),
+                days_remaining: x.days_remaining(now),
+            })
+            .collect();
+        let json = serde_json::to_string_pretty(&rows)
+            .map_err(|msg| format!("failed to encode domains: {msg}"))?;
+        println!("{json}");
+        return Ok(());
+    }
+
+    let width = domains.iter().map(|x| x.domain.len()).max().unwrap_or(0);
+    for domain in &domains {
+        println!(
+            "{:width$}  {:<8}  created {}  expires {}{}",
+            domain.domain,
+            domain.status.as_deref().unwrap_or("-"),
+            date(domain.created_at(), "%Y-%m-%d").unwrap_or_else(|| "-".into()),
+            date(domain.expires_at(), "%Y-%m-%d %H:%M %:z").unwrap_or_else(|| "-".into()),
+            remaining(domain, now),
+        );
+    }
+    Ok(())
+}
+
+fn remaining(domain: &DomainInfo, now: DateTime<Utc>) -> String {
+    match domain.days_remaining(now) {
+        Some(days) if days < 0 => format!(" (expired {} days ago)", -days),
+        Some(days) => format!(" ({days} days left)"),
+        None => String::new(),
+    }
+}
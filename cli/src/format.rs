@@ -0,0 +1,282 @@
+use crate::signing;
+use crate::vars::Vars;
+use porkbun::{NewRecord, RecordSpec, RecordType, SpecSet, Ttl, ZoneSpec};
+use std::collections::HashMap;
+use std::path::Path;
+
+mod dnscontrol;
+mod octodns;
+
+/// A zone file format used by another DNS tool.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum Format {
+    /// An octoDNS YAML zone config.
+    Octodns,
+    /// A dnscontrol `dnsconfig.js`.
+    Dnscontrol,
+    /// This tool's own zone spec, as written by `dns adopt`.
+    Toml,
+}
+
+impl Format {
+    pub fn export(self, domain: &str, records: &[NewRecord]) -> String {
+        match self {
+            Format::Octodns => octodns::export(records),
+            Format::Dnscontrol => dnscontrol::export(domain, records),
+            Format::Toml => ZoneSpec {
+                domain: domain.to_string(),
+                records: records.iter().map(RecordSpec::from).collect(),
+                ..ZoneSpec::default()
+            }
+            .to_toml(),
+        }
+    }
+
+    /// Reads the records in `text`, filling in `${name}` placeholders of a toml zone spec
+    /// from `vars`.
+    ///
+    /// A toml file of several zones is searched for the one describing `domain`. Files it
+    /// includes are found relative to `dir`.
+    pub fn import(
+        self,
+        domain: &str,
+        text: &str,
+        dir: &Path,
+        vars: &Vars,
+        verify_key: Option<&Path>,
+    ) -> Result<Vec<NewRecord>, String> {
+        match self {
+            Format::Octodns => octodns::import(text),
+            Format::Dnscontrol => dnscontrol::import(domain, text),
+            Format::Toml => {
+                let mut domains = Vec::new();
+                for mut spec in parse_specs(text, dir, verify_key)? {
+                    spec.interpolate(vars.lookup(domain, verify_key)?)
+                        .map_err(|msg| msg.to_string())?;
+                    if spec.domain.trim_end_matches('.') == domain {
+                        return Ok(spec.to_records());
+                    }
+                    domains.push(spec.domain);
+                }
+                Err(format!("spec is for {}, not {domain}", domains.join(", ")))
+            }
+        }
+    }
+}
+
+/// A file of records which zone specs can include, such as a mail provider's records.
+/// ```toml
+/// [[records]]
+/// type = "MX"
+/// content = "mx.example.net"
+/// prio = 10
+/// ```
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Snippet {
+    records: Vec<RecordSpec>,
+}
+
+/// Parses a toml file of either a single zone or a [SpecSet] of several, with any blocks
+/// they use and files they include filled in. Included paths are relative to `dir`.
+///
+/// With a `verify_key`, each included file must be signed by it, like the spec itself.
+pub fn parse_specs(
+    text: &str,
+    dir: &Path,
+    verify_key: Option<&Path>,
+) -> Result<Vec<ZoneSpec>, String> {
+    let table: toml::Table = toml::from_str(text).map_err(|msg| msg.to_string())?;
+    // Parsing the text again, rather than converting the table, keeps the line and column
+    // of mistakes in the error.
+    let set = if table.contains_key("zones") {
+        toml::from_str(text).map_err(|msg| msg.to_string())?
+    } else {
+        SpecSet {
+            zones: vec![toml::from_str(text).map_err(|msg| msg.to_string())?],
+            ..SpecSet::default()
+        }
+    };
+    let mut specs = set.resolve().map_err(|msg| msg.to_string())?;
+
+    // Zones usually share their includes, so each file is only read once.
+    let mut snippets: HashMap<String, Vec<RecordSpec>> = HashMap::new();
+    for spec in &mut specs {
+        spec.resolve_includes(|include| {
+            if let Some(records) = snippets.get(include) {
+                return Ok(records.clone());
+            }
+            let path = dir.join(include);
+            let text = std::fs::read_to_string(&path)
+                .map_err(|msg| format!("failed to read {}: {msg}", path.display()))?;
+            if let Some(key) = verify_key {
+                signing::verify(&path, &text, key)?;
+            }
+            let snippet: Snippet = toml::from_str(&text)
+                .map_err(|msg| format!("failed to parse {}: {msg}", path.display()))?;
+            snippets.insert(include.to_string(), snippet.records.clone());
+            Ok::<_, String>(snippet.records)
+        })?;
+    }
+    Ok(specs)
+}
+
+/// A record's content, split into the fields other tools keep separately.
+///
+/// Hostnames are fully qualified, with a trailing dot.
+/// Fields are named and serialized as octoDNS names them.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    /// A, AAAA, and TXT records.
+    Text(String),
+    /// CNAME, ALIAS, and NS records.
+    Host(String),
+    Mx {
+        preference: u16,
+        exchange: String,
+    },
+    Srv {
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: String,
+    },
+    Caa {
+        #[serde(default)]
+        flags: u8,
+        tag: String,
+        value: String,
+    },
+    Tlsa {
+        #[serde(rename = "certificate_usage")]
+        usage: u8,
+        selector: u8,
+        matching_type: u8,
+        #[serde(rename = "certificate_association_data")]
+        data: String,
+    },
+}
+
+impl Value {
+    /// Splits up the content of a porkbun record, or returns `None` if its type isn't supported.
+    pub fn from_record(record: &NewRecord) -> Option<Self> {
+        let prio = || {
+            record
+                .prio
+                .as_deref()
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(0)
+        };
+        let content = &record.content;
+        let mut fields = content.split_whitespace();
+        let value = match record.ty {
+            RecordType::A | RecordType::Aaaa | RecordType::Txt => Value::Text(content.clone()),
+            RecordType::Cname | RecordType::Alias | RecordType::Ns => Value::Host(fqdn(content)),
+            RecordType::Mx => Value::Mx {
+                preference: prio(),
+                exchange: fqdn(content),
+            },
+            RecordType::Srv => Value::Srv {
+                priority: prio(),
+                weight: fields.next()?.parse().ok()?,
+                port: fields.next()?.parse().ok()?,
+                target: fqdn(fields.next()?),
+            },
+            RecordType::Caa => Value::Caa {
+                flags: fields.next()?.parse().ok()?,
+                tag: fields.next()?.to_string(),
+                value: content
+                    .splitn(3, ' ')
+                    .nth(2)?
+                    .trim()
+                    .trim_matches('"')
+                    .to_string(),
+            },
+            RecordType::Tlsa => Value::Tlsa {
+                usage: fields.next()?.parse().ok()?,
+                selector: fields.next()?.parse().ok()?,
+                matching_type: fields.next()?.parse().ok()?,
+                data: fields.next()?.to_string(),
+            },
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Checks that a value suits records of type `ty`.
+    ///
+    /// Hostnames parse as [Value::Text], so they're converted here.
+    pub fn for_type(self, ty: RecordType) -> Option<Self> {
+        match (ty, self) {
+            (RecordType::A | RecordType::Aaaa | RecordType::Txt, value @ Value::Text(_))
+            | (RecordType::Mx, value @ Value::Mx { .. })
+            | (RecordType::Srv, value @ Value::Srv { .. })
+            | (RecordType::Caa, value @ Value::Caa { .. })
+            | (RecordType::Tlsa, value @ Value::Tlsa { .. }) => Some(value),
+            (
+                RecordType::Cname | RecordType::Alias | RecordType::Ns,
+                Value::Text(host) | Value::Host(host),
+            ) => Some(Value::Host(host)),
+            _ => None,
+        }
+    }
+
+    /// Joins the fields back into a porkbun record.
+    pub fn into_record(self, name: Option<String>, ty: RecordType, ttl: Option<Ttl>) -> NewRecord {
+        let (content, prio) = match self {
+            Value::Text(text) => (text, None),
+            Value::Host(host) => (relative(&host), None),
+            Value::Mx {
+                preference,
+                exchange,
+            } => (relative(&exchange), Some(preference)),
+            Value::Srv {
+                priority,
+                weight,
+                port,
+                target,
+            } => (
+                format!("{weight} {port} {}", relative(&target)),
+                Some(priority),
+            ),
+            Value::Caa { flags, tag, value } => (format!("{flags} {tag} \"{value}\""), None),
+            Value::Tlsa {
+                usage,
+                selector,
+                matching_type,
+                data,
+            } => (format!("{usage} {selector} {matching_type} {data}"), None),
+        };
+        NewRecord {
+            name,
+            ty,
+            content,
+            ttl,
+            prio: prio.map(|x| x.to_string()),
+        }
+    }
+}
+
+/// Warns about a record which can't be represented in another format.
+pub fn unsupported(record: &NewRecord) {
+    tracing::warn!(
+        "skipping {} record {} which can't be exported",
+        record.ty,
+        record.name.as_deref().unwrap_or("@")
+    );
+}
+
+/// Adds a trailing dot to a hostname.
+pub fn fqdn(host: &str) -> String {
+    if host.ends_with('.') {
+        host.to_string()
+    } else {
+        format!("{host}.")
+    }
+}
+
+/// Removes the trailing dot from a hostname, as porkbun stores them.
+pub fn relative(host: &str) -> String {
+    host.strip_suffix('.').unwrap_or(host).to_string()
+}
@@ -0,0 +1,329 @@
+//! dnscontrol `dnsconfig.js` files.
+//!
+//! ```js
+//! D("example.com", REG_NONE, DnsProvider(DSP_PORKBUN),
+//!     A("@", "203.0.113.7", TTL(600)),
+//!     MX("@", 10, "mail.example.com."),
+//!     CNAME("www", "example.com.")
+//! );
+//! ```
+//!
+//! Importing only understands the subset of javascript dnscontrol itself writes:
+//! function calls with string and number arguments, within a `D` call for the domain.
+
+use super::{unsupported, Value};
+use porkbun::{NewRecord, RecordType, Ttl};
+
+pub fn export(domain: &str, records: &[NewRecord]) -> String {
+    let mut lines = Vec::with_capacity(records.len());
+    for record in records {
+        let Some(value) = Value::from_record(record) else {
+            unsupported(record);
+            continue;
+        };
+        let mut args = vec![string(record.name.as_deref().unwrap_or("@"))];
+        match value {
+            Value::Text(text) | Value::Host(text) => args.push(string(&text)),
+            Value::Mx {
+                preference,
+                exchange,
+            } => args.extend([preference.to_string(), string(&exchange)]),
+            Value::Srv {
+                priority,
+                weight,
+                port,
+                target,
+            } => args.extend([
+                priority.to_string(),
+                weight.to_string(),
+                port.to_string(),
+                string(&target),
+            ]),
+            Value::Caa { flags, tag, value } => {
+                args.extend([string(&tag), string(&value)]);
+                if flags & 128 != 0 {
+                    args.push("CAA_CRITICAL".to_string());
+                }
+            }
+            Value::Tlsa {
+                usage,
+                selector,
+                matching_type,
+                data,
+            } => args.extend([
+                usage.to_string(),
+                selector.to_string(),
+                matching_type.to_string(),
+                string(&data),
+            ]),
+        }
+        if let Some(ttl) = record.ttl {
+            args.push(format!("TTL({ttl})"));
+        }
+        lines.push(format!("    {}({})", record.ty, args.join(", ")));
+    }
+
+    format!(
+        "var REG_NONE = NewRegistrar(\"none\");\n\
+         var DSP_PORKBUN = NewDnsProvider(\"porkbun\");\n\
+         \n\
+         D({}, REG_NONE, DnsProvider(DSP_PORKBUN),\n{}\n);\n",
+        string(domain),
+        lines.join(",\n")
+    )
+}
+
+/// Quotes a javascript string.
+fn string(text: &str) -> String {
+    serde_json::Value::from(text).to_string()
+}
+
+pub fn import(domain: &str, text: &str) -> Result<Vec<NewRecord>, String> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+    };
+    let mut calls = Vec::new();
+    while parser.position < tokens.len() {
+        match parser.expr()? {
+            Some(expr) => calls.push(expr),
+            // Anything else, such as `var` and `=`, doesn't affect the records.
+            None => parser.position += 1,
+        }
+    }
+
+    let is_domain =
+        |x: Option<&Expr>| matches!(x, Some(Expr::Str(x)) if x.trim_end_matches('.') == domain);
+    let Some(args) = calls.into_iter().find_map(|x| match x {
+        Expr::Call(name, args) if name == "D" && is_domain(args.first()) => Some(args),
+        _ => None,
+    }) else {
+        return Err(format!("no D(\"{domain}\", ...) found"));
+    };
+
+    // Records may also be grouped into arrays, which dnscontrol flattens.
+    let args = args.into_iter().flat_map(|x| match x {
+        Expr::Array(items) => items,
+        x => vec![x],
+    });
+    let mut records = Vec::new();
+    for arg in args {
+        let Expr::Call(function, args) = arg else {
+            continue;
+        };
+        let Ok(ty) = serde_json::from_value::<RecordType>(function.clone().into()) else {
+            continue;
+        };
+        let record = record(domain, ty, &args).ok_or_else(|| format!("invalid {function}(...)"))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+fn record(domain: &str, ty: RecordType, args: &[Expr]) -> Option<NewRecord> {
+    let mut ttl = None;
+    let mut critical = false;
+    let mut positional = Vec::new();
+    for arg in args {
+        match arg {
+            Expr::Call(name, args) if name == "TTL" => {
+                ttl = Some(Ttl::from_secs(args.first()?.number()?.try_into().ok()?));
+            }
+            Expr::Ident(name) if name == "CAA_CRITICAL" => critical = true,
+            Expr::Str(_) | Expr::Number(_) => positional.push(arg),
+            _ => {}
+        }
+    }
+
+    let number = |i: usize| -> Option<u64> { positional.get(i)?.number() };
+    let text = |i: usize| -> Option<String> { positional.get(i)?.string() };
+    let value = match ty {
+        RecordType::Mx => Value::Mx {
+            preference: number(1)?.try_into().ok()?,
+            exchange: text(2)?,
+        },
+        RecordType::Srv => Value::Srv {
+            priority: number(1)?.try_into().ok()?,
+            weight: number(2)?.try_into().ok()?,
+            port: number(3)?.try_into().ok()?,
+            target: text(4)?,
+        },
+        RecordType::Caa => Value::Caa {
+            flags: if critical { 128 } else { 0 },
+            tag: text(1)?,
+            value: text(2)?,
+        },
+        RecordType::Tlsa => Value::Tlsa {
+            usage: number(1)?.try_into().ok()?,
+            selector: number(2)?.try_into().ok()?,
+            matching_type: number(3)?.try_into().ok()?,
+            data: text(4)?,
+        },
+        _ => Value::Text(text(1)?),
+    };
+
+    let name = text(0)?;
+    let name = match name.strip_suffix('.') {
+        // A fully-qualified name.
+        Some(name) if name == domain => None,
+        Some(name) => Some(name.strip_suffix(&format!(".{domain}"))?.to_string()),
+        None if name == "@" => None,
+        None => Some(name),
+    };
+    Some(value.for_type(ty)?.into_record(name, ty, ttl))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Str(String),
+    Number(u64),
+    Ident(String),
+    Punct(char),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '"' | '\'' | '`' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        None => return Err("unterminated string".to_string()),
+                        Some(x) if x == c => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => string.push('\n'),
+                            Some('t') => string.push('\t'),
+                            Some(x) => string.push(x),
+                            None => return Err("unterminated string".to_string()),
+                        },
+                        Some(x) => string.push(x),
+                    }
+                }
+                tokens.push(Token::Str(string));
+            }
+            c if c.is_ascii_digit() => {
+                let mut number = String::from(c);
+                while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                    number.push(c);
+                }
+                let number = number
+                    .parse()
+                    .map_err(|_| format!("number out of range: {number}"))?;
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphabetic() || c == '_' || c == '$' => {
+                let mut ident = String::from(c);
+                while let Some(c) =
+                    chars.next_if(|x| x.is_alphanumeric() || matches!(x, '_' | '$' | '.'))
+                {
+                    ident.push(c);
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            c => tokens.push(Token::Punct(c)),
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Str(String),
+    Number(u64),
+    Ident(String),
+    Call(String, Vec<Expr>),
+    Array(Vec<Expr>),
+}
+
+impl Expr {
+    fn number(&self) -> Option<u64> {
+        match self {
+            Expr::Number(x) => Some(*x),
+            Expr::Str(x) => x.parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn string(&self) -> Option<String> {
+        match self {
+            Expr::Str(x) => Some(x.clone()),
+            Expr::Number(x) => Some(x.to_string()),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// Parses an expression, or returns `None` without consuming anything if there isn't one.
+    fn expr(&mut self) -> Result<Option<Expr>, String> {
+        let expr = match self.peek() {
+            Some(Token::Str(x)) => Expr::Str(x.clone()),
+            Some(Token::Number(x)) => Expr::Number(*x),
+            Some(Token::Ident(name)) => {
+                let name = name.clone();
+                self.position += 1;
+                if self.peek() != Some(&Token::Punct('(')) {
+                    return Ok(Some(Expr::Ident(name)));
+                }
+                self.position += 1;
+                return Ok(Some(Expr::Call(name, self.list(')')?)));
+            }
+            Some(Token::Punct('[')) => {
+                self.position += 1;
+                return Ok(Some(Expr::Array(self.list(']')?)));
+            }
+            _ => return Ok(None),
+        };
+        self.position += 1;
+        Ok(Some(expr))
+    }
+
+    /// Parses comma-separated expressions up to and including `end`.
+    fn list(&mut self, end: char) -> Result<Vec<Expr>, String> {
+        let mut items = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(format!("expected '{end}'")),
+                Some(Token::Punct(c)) if *c == end => {
+                    self.position += 1;
+                    return Ok(items);
+                }
+                Some(Token::Punct(',')) => self.position += 1,
+                _ => match self.expr()? {
+                    Some(expr) => items.push(expr),
+                    None => return Err(format!("unexpected {:?}", self.peek())),
+                },
+            }
+        }
+    }
+}
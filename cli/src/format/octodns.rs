@@ -0,0 +1,142 @@
+//! octoDNS YAML zone configs, as read by its `YamlProvider`.
+//!
+//! ```yaml
+//! '':
+//!   - type: A
+//!     ttl: 600
+//!     value: 203.0.113.7
+//!   - type: MX
+//!     values:
+//!       - exchange: mail.example.com.
+//!         preference: 10
+//! www:
+//!   type: CNAME
+//!   value: example.com.
+//! ```
+
+use super::{unsupported, Value};
+use porkbun::{NewRecord, RecordType, Ttl};
+use serde_yaml::{Mapping, Value as Yaml};
+use std::collections::BTreeMap;
+
+/// Every record with one name and type.
+struct Group {
+    ty: RecordType,
+    ttl: Option<Ttl>,
+    values: Vec<Value>,
+}
+
+pub fn export(records: &[NewRecord]) -> String {
+    let mut zone: BTreeMap<String, Vec<Group>> = BTreeMap::new();
+    for record in records {
+        let Some(value) = Value::from_record(record) else {
+            unsupported(record);
+            continue;
+        };
+        let value = match value {
+            Value::Text(text) if record.ty == RecordType::Txt => Value::Text(escape_txt(&text)),
+            value => value,
+        };
+        let groups = zone
+            .entry(record.name.clone().unwrap_or_default())
+            .or_default();
+        match groups.iter_mut().find(|x| x.ty == record.ty) {
+            Some(group) => group.values.push(value),
+            None => groups.push(Group {
+                ty: record.ty,
+                ttl: record.ttl,
+                values: vec![value],
+            }),
+        }
+    }
+
+    let mut yaml = Mapping::new();
+    for (name, groups) in zone {
+        let mut entries: Vec<Yaml> = groups.into_iter().map(group_to_yaml).collect();
+        let entry = if entries.len() == 1 {
+            entries.remove(0)
+        } else {
+            Yaml::Sequence(entries)
+        };
+        yaml.insert(Yaml::String(name), entry);
+    }
+    // A mapping of strings and numbers always serializes.
+    serde_yaml::to_string(&yaml).unwrap_or_default()
+}
+
+fn group_to_yaml(mut group: Group) -> Yaml {
+    let mut entry = Mapping::new();
+    entry.insert("type".into(), group.ty.as_str().into());
+    if let Some(ttl) = group.ttl {
+        entry.insert("ttl".into(), ttl.as_secs().into());
+    }
+    let value = |x: Value| serde_yaml::to_value(x).unwrap_or_default();
+    if group.values.len() == 1 {
+        entry.insert("value".into(), value(group.values.remove(0)));
+    } else {
+        let values = group.values.into_iter().map(value).collect();
+        entry.insert("values".into(), Yaml::Sequence(values));
+    }
+    Yaml::Mapping(entry)
+}
+
+pub fn import(text: &str) -> Result<Vec<NewRecord>, String> {
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        One(T),
+        Many(Vec<T>),
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Entry {
+        #[serde(rename = "type")]
+        ty: String,
+        ttl: Option<u32>,
+        value: Option<Value>,
+        #[serde(default)]
+        values: Vec<Value>,
+    }
+
+    let zone: BTreeMap<String, OneOrMany<Entry>> =
+        serde_yaml::from_str(text).map_err(|msg| format!("invalid octoDNS config: {msg}"))?;
+
+    let mut records = Vec::new();
+    for (name, entries) in zone {
+        let entries = match entries {
+            OneOrMany::One(entry) => vec![entry],
+            OneOrMany::Many(entries) => entries,
+        };
+        let label = if name.is_empty() { "@" } else { &name };
+        for entry in entries {
+            let Ok(ty) = serde_yaml::from_value::<RecordType>(entry.ty.clone().into()) else {
+                tracing::warn!(
+                    "skipping {} record {label} which porkbun doesn't support",
+                    entry.ty
+                );
+                continue;
+            };
+            for value in entry.value.into_iter().chain(entry.values) {
+                let value = value
+                    .for_type(ty)
+                    .ok_or_else(|| format!("invalid value for {ty} record {label}"))?;
+                let value = match value {
+                    Value::Text(text) if ty == RecordType::Txt => Value::Text(unescape_txt(&text)),
+                    value => value,
+                };
+                let name = (!name.is_empty()).then(|| name.clone());
+                records.push(value.into_record(name, ty, entry.ttl.map(Ttl::from_secs)));
+            }
+        }
+    }
+    Ok(records)
+}
+
+/// octoDNS requires semicolons in TXT values to be escaped.
+fn escape_txt(text: &str) -> String {
+    text.replace(';', "\\;")
+}
+
+fn unescape_txt(text: &str) -> String {
+    text.replace("\\;", ";")
+}
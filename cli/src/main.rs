@@ -0,0 +1,1198 @@
+use clap::Parser;
+use format::Format;
+use porkbun::resolve::Resolver;
+use porkbun::{
+    Access, ConflictStrategy, Disposition, DmarcPolicy, DomainName, RecordId, RecordType,
+    SyncOptions, Ttl, ZoneSpec,
+};
+use std::path::PathBuf;
+use std::process::exit;
+use std::time::Duration;
+use tracing::{error, warn};
+
+mod acme;
+mod caa;
+mod debug;
+mod dmarc;
+mod dns;
+mod domains;
+mod format;
+mod search;
+mod signing;
+mod validate;
+mod vars;
+mod zones;
+
+#[derive(clap::Parser)]
+#[clap(author, version, about, long_about = None)]
+struct Cli {
+    /// Path to the porkbun api key file.
+    #[clap(short, long, value_parser, value_name = "PATH", global = true)]
+    key: Option<PathBuf>,
+
+    /// Show internationalized domains as punycode rather than Unicode.
+    #[clap(long, global = true)]
+    ascii: bool,
+
+    /// Append every change made to this file, so that `dns undo` can reverse it.
+    #[clap(long, value_parser, value_name = "PATH", global = true)]
+    audit_log: Option<PathBuf>,
+
+    /// Who is making the changes, as recorded in the --audit-log. Defaults to the current user.
+    #[clap(long, value_name = "NAME", global = true, env = "PORKBUN_ACTOR")]
+    actor: Option<String>,
+
+    /// Share one rate-limit budget with other porkbun tools through the coordinator
+    /// listening on this socket, as started by `porkbun coordinate`.
+    #[clap(
+        long,
+        value_parser,
+        value_name = "PATH",
+        global = true,
+        env = "PORKBUN_COORDINATOR"
+    )]
+    coordinator: Option<PathBuf>,
+
+    /// Apply changes without asking for confirmation.
+    #[clap(short, long, global = true)]
+    yes: bool,
+
+    /// Fail instead of asking for confirmation, even on a terminal.
+    #[clap(long, global = true)]
+    no_input: bool,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Manage DNS records.
+    #[clap(subcommand)]
+    Dns(DnsCommand),
+    /// List the domains in the account.
+    #[clap(subcommand)]
+    Domains(DomainsCommand),
+    /// Manage every zone in a toml spec file at once.
+    #[clap(subcommand)]
+    Zones(ZonesCommand),
+    /// Manage which certificate authorities may issue certificates for your domains.
+    #[clap(subcommand)]
+    Caa(CaaCommand),
+    /// Manage DMARC policies.
+    #[clap(subcommand)]
+    Dmarc(DmarcCommand),
+    /// Answer ACME DNS-01 challenges, such as from certbot's manual hooks.
+    #[clap(subcommand)]
+    Acme(AcmeCommand),
+    /// Manage the api keys in the --key file.
+    #[clap(subcommand)]
+    Keys(KeysCommand),
+    /// Save and check porkbun's responses, such as for a bug report about an unrecognized
+    /// response.
+    #[clap(subcommand)]
+    Debug(DebugCommand),
+    /// Serve one rate-limit budget on a unix socket, for every porkbun tool on this host given
+    /// it with --coordinator.
+    Coordinate {
+        /// The socket to listen on.
+        #[clap(long, value_parser, value_name = "PATH")]
+        socket: PathBuf,
+        /// How many requests may be sent in each --per period.
+        #[clap(long, value_name = "N", default_value_t = 60)]
+        requests: u32,
+        #[clap(long, value_parser = humantime::parse_duration, value_name = "DURATION", default_value = "1m")]
+        per: Duration,
+    },
+    /// Check for common problems: keys which don't work, domains without API access, and
+    /// domains which mix scripts like lookalikes of other domains do.
+    Doctor {
+        /// The domains to check; every domain in the account if none are given.
+        domains: Vec<DomainName>,
+    },
+    /// Check toml zone specs for mistakes without contacting porkbun.
+    ///
+    /// Exits with an error if any are found.
+    Validate {
+        /// The spec files to check, or `-` for stdin.
+        #[clap(required = true, value_parser, value_name = "PATH")]
+        paths: Vec<PathBuf>,
+        #[clap(flatten)]
+        vars: vars::Vars,
+    },
+    /// Print the JSON schema of a file format, so editors can complete and check hand-written
+    /// files.
+    Schema {
+        #[clap(value_enum)]
+        format: SchemaFormat,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum DomainsCommand {
+    /// List every domain with its registration and expiry dates, in the local time zone.
+    List {
+        /// Show dates in UTC.
+        #[clap(long)]
+        utc: bool,
+        /// Print the domains as JSON, with the days left until each expires.
+        #[clap(long)]
+        json: bool,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum KeysCommand {
+    /// Replace the keys in the --key file with a new pair, after checking that they work.
+    ///
+    /// Create the new pair in the Porkbun dashboard first, and only delete the old one once
+    /// this succeeds.
+    Rotate {
+        /// A key file holding the new pair, or `-` to read it from stdin.
+        #[clap(long, value_parser, value_name = "PATH", default_value = "-")]
+        from: PathBuf,
+    },
+    /// Check that the keys work, and that API access is enabled for each domain.
+    Check {
+        #[clap(required = true)]
+        domains: Vec<DomainName>,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum DebugCommand {
+    /// Save responses to a directory with the account's domains, addresses, and notes
+    /// redacted, and check that they can be read.
+    Capture {
+        /// Domains whose records to capture too.
+        domains: Vec<DomainName>,
+        #[clap(long, value_parser, value_name = "DIR")]
+        out: PathBuf,
+    },
+    /// Replay saved responses, or directories of them, and report those which can't be read
+    /// or which have fields this version ignores.
+    Check {
+        #[clap(required = true, value_parser, value_name = "PATH")]
+        paths: Vec<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+enum SchemaFormat {
+    /// A zone spec, as used by `dns sync --format toml`.
+    ZoneSpec,
+    /// A file of several zones, as used by `zones sync`.
+    Zones,
+}
+
+/// Both commands read the variables certbot sets for `--manual-auth-hook` and
+/// `--manual-cleanup-hook`, so they can be used as hooks directly.
+#[derive(clap::Subcommand)]
+enum AcmeCommand {
+    /// Create the TXT record answering a challenge, and wait for it to propagate.
+    Auth {
+        /// The name being validated.
+        #[clap(long, env = "CERTBOT_DOMAIN")]
+        domain: String,
+        /// The value the certificate authority expects.
+        #[clap(long, env = "CERTBOT_VALIDATION")]
+        validation: String,
+        /// How long to wait for resolvers to return the record; 0 to not wait.
+        #[clap(long, value_parser = humantime::parse_duration, value_name = "DURATION", default_value = "10m")]
+        wait: Duration,
+        /// Resolvers which must return the record: system, cloudflare, google, quad9, or
+        /// DNS-over-HTTPS urls.
+        #[clap(
+            long,
+            value_name = "RESOLVER",
+            value_delimiter = ',',
+            default_value = "cloudflare,google"
+        )]
+        resolvers: Vec<Resolver>,
+    },
+    /// Delete the TXT record created by `auth`.
+    Cleanup {
+        /// The name which was validated.
+        #[clap(long, env = "CERTBOT_DOMAIN")]
+        domain: String,
+        /// The value of the record to delete.
+        #[clap(long, env = "CERTBOT_VALIDATION")]
+        validation: String,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum DmarcCommand {
+    /// Publish one DMARC policy across many domains, sending their reports to a central
+    /// mailbox.
+    ///
+    /// If the mailbox's domain is in this account, the records authorizing it to receive
+    /// reports for the other domains are created too.
+    Provision {
+        /// The domains to publish the policy at.
+        #[clap(required_unless_present = "all")]
+        domains: Vec<DomainName>,
+        /// Publish the policy at every domain in the account.
+        #[clap(long, conflicts_with = "domains")]
+        all: bool,
+        /// What receivers should do with failing mail: none, quarantine, or reject.
+        #[clap(long, value_name = "DISPOSITION", default_value = "none")]
+        policy: Disposition,
+        /// A different policy for subdomains.
+        #[clap(long, value_name = "DISPOSITION")]
+        subdomain_policy: Option<Disposition>,
+        /// An address which receives aggregate reports.
+        #[clap(long, value_name = "ADDRESS", required = true)]
+        rua: Vec<String>,
+        /// An address which receives a report about each failing message.
+        #[clap(long, value_name = "ADDRESS")]
+        ruf: Vec<String>,
+        /// How many domains to fetch at once.
+        #[clap(short, long, default_value_t = 4)]
+        jobs: usize,
+        /// Print the plans as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum CaaCommand {
+    /// List the CAA records of every domain in the account, flagging those without any.
+    Audit {
+        /// An authority every domain should allow, such as letsencrypt.org.
+        #[clap(long, value_name = "AUTHORITY")]
+        expect: Vec<String>,
+        /// Replace each domain's CAA records with ones allowing only the --expect authorities.
+        #[clap(long, requires = "expect")]
+        apply: bool,
+        /// Where authorities should report refused requests, such as mailto:security@example.com.
+        #[clap(long, value_name = "URL", requires = "apply")]
+        iodef: Option<String>,
+        /// How many domains to fetch at once.
+        #[clap(short, long, default_value_t = 4)]
+        jobs: usize,
+        /// Print the postures and plans as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum DnsCommand {
+    /// Print every record of a domain in another tool's format.
+    Export {
+        domain: DomainName,
+        #[clap(short, long, value_enum)]
+        format: Format,
+        /// Write to this file instead of stdout.
+        #[clap(short, long, value_parser, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Write a domain's current records as a zone spec for `sync --format toml`.
+    ///
+    /// Record notes are kept as comments. When the output file already holds a snapshot of
+    /// the domain, what changed since then is printed.
+    Adopt {
+        domain: DomainName,
+        /// Write to this file instead of stdout.
+        #[clap(short, long, value_parser, value_name = "PATH")]
+        out: Option<PathBuf>,
+        /// Sign the spec with this ed25519 private key (PEM), writing the signature to the
+        /// output file with `.sig` appended.
+        #[clap(long, value_parser, value_name = "PATH", requires = "out")]
+        sign_key: Option<PathBuf>,
+        /// Print what changed since the previous snapshot in the output file as JSON.
+        #[clap(long, requires = "out")]
+        json: bool,
+    },
+    /// Create the records described by a file in another tool's format.
+    ///
+    /// Records which already exist are left alone, and nothing is deleted.
+    Import {
+        domain: DomainName,
+        #[clap(short, long, value_enum)]
+        format: Format,
+        /// The file to import, or `-` for stdin.
+        #[clap(value_parser, value_name = "PATH")]
+        input: PathBuf,
+        #[clap(flatten)]
+        vars: vars::Vars,
+        /// Print the records which would be created without creating them.
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Show how a domain's records differ from a file in another tool's format.
+    Diff {
+        domain: DomainName,
+        #[clap(short, long, value_enum)]
+        format: Format,
+        /// The file to compare with, or `-` for stdin.
+        #[clap(value_parser, value_name = "PATH")]
+        input: PathBuf,
+        #[clap(flatten)]
+        vars: vars::Vars,
+        /// Print the plan as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+    /// Make a domain's records match a file in another tool's format.
+    Sync {
+        domain: DomainName,
+        #[clap(short, long, value_enum)]
+        format: Format,
+        /// The file to apply, or `-` for stdin.
+        #[clap(value_parser, value_name = "PATH")]
+        input: PathBuf,
+        #[clap(flatten)]
+        vars: vars::Vars,
+        /// Refuse the file unless its `.sig` signature was made by this ed25519 public key (PEM),
+        /// such as when restoring a snapshot written by `adopt --sign-key`. Included files and
+        /// --vars-file must be signed too, and --var and environment variables aren't used.
+        #[clap(long, value_parser, value_name = "PATH")]
+        verify_key: Option<PathBuf>,
+        /// Also delete records which aren't in the file.
+        #[clap(long)]
+        prune: bool,
+        /// What to do when live records differ from the file: overwrite, ignore, fail, or merge,
+        /// for every type or as TYPE=STRATEGY for one. May be repeated.
+        #[clap(long, value_parser = parse_conflict, value_name = "[TYPE=]STRATEGY")]
+        on_conflict: Vec<(Option<RecordType>, ConflictStrategy)>,
+        /// Print the plan as JSON.
+        #[clap(long)]
+        json: bool,
+        /// Print the plan without applying it.
+        #[clap(long)]
+        dry_run: bool,
+        /// Hold the zone's _porkbun-lock record from planning until the changes are applied,
+        /// so that concurrent syncs can't interleave. Stale locks expire after DURATION.
+        #[clap(long, value_parser = humantime::parse_duration, value_name = "DURATION", num_args = 0..=1, default_missing_value = "10m")]
+        lock: Option<Duration>,
+    },
+    /// Check whether public resolvers return a record's new value yet.
+    Propagation {
+        domain: DomainName,
+        /// The record's subdomain, or `@` for the domain itself.
+        name: String,
+        #[clap(value_name = "TYPE")]
+        ty: RecordType,
+        /// The answer to wait for, as DNS presents it, such as `10 mail.example.com` for MX.
+        #[clap(long, value_name = "ANSWER")]
+        expect: String,
+        /// Resolvers to ask: system, cloudflare, google, quad9, or DNS-over-HTTPS urls.
+        #[clap(
+            long,
+            value_name = "RESOLVER",
+            value_delimiter = ',',
+            default_value = "cloudflare,google,quad9"
+        )]
+        resolvers: Vec<Resolver>,
+        /// Keep checking until every resolver agrees or this much time passes.
+        #[clap(long, value_parser = humantime::parse_duration, value_name = "DURATION")]
+        wait: Option<Duration>,
+        /// Print the final report as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+    /// Check a domain's SPF policy against the limit of 10 DNS lookups, and optionally
+    /// flatten it into addresses.
+    Spf {
+        domain: DomainName,
+        /// How to look up records: cloudflare, google, quad9, or a DNS-over-HTTPS url.
+        #[clap(long, value_name = "RESOLVER", default_value = "cloudflare")]
+        resolver: Resolver,
+        /// Plan replacing the policy with one listing every address it allows.
+        #[clap(long)]
+        flatten: bool,
+        /// Apply the flattened policy.
+        #[clap(long, requires = "flatten")]
+        apply: bool,
+        /// Print the analysis and plan as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+    /// Create the records a self-hosted service or static site host needs, replacing any
+    /// which are in the way.
+    Preset {
+        domain: DomainName,
+        #[clap(value_enum)]
+        preset: dns::Preset,
+        #[clap(flatten)]
+        options: dns::PresetOptions,
+        /// Print the plan without applying it.
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Create a record, such as `create example.com A www 203.0.113.7 --ttl 600`.
+    ///
+    /// Fails if the record would conflict with existing ones, such as a CNAME record.
+    Create {
+        domain: DomainName,
+        #[clap(flatten)]
+        record: dns::RecordArgs,
+        /// Print the plan as JSON.
+        #[clap(long)]
+        json: bool,
+        /// Print the plan without creating the record.
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Change some fields of a record, keeping the rest.
+    Edit {
+        domain: DomainName,
+        #[clap(value_name = "ID", required_unless_present = "from_json")]
+        id: Option<RecordId>,
+        #[clap(flatten)]
+        changes: dns::RecordChanges,
+        /// Replace the record with one in porkbun's JSON form, such as from `grep --json`, or
+        /// `-` for stdin.
+        #[clap(long, value_parser, value_name = "PATH", conflicts_with_all = ["name", "content", "ttl", "prio", "notes"])]
+        from_json: Option<PathBuf>,
+        /// Print the plan as JSON.
+        #[clap(long)]
+        json: bool,
+        /// Print the plan without editing the record.
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Delete records by id, or pick them from a numbered list with --select.
+    Delete {
+        domain: DomainName,
+        #[clap(value_name = "ID", required_unless_present = "select")]
+        ids: Vec<RecordId>,
+        /// List the domain's records with numbers to pick from, only showing those containing
+        /// every word of QUERY if given.
+        #[clap(long, value_name = "QUERY", num_args = 0..=1, default_missing_value = "")]
+        select: Option<String>,
+        /// Print the plan as JSON.
+        #[clap(long)]
+        json: bool,
+        /// Print the plan without applying it.
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Change the ttl of many records at once, such as before and after a migration.
+    SetTtl {
+        domain: DomainName,
+        /// The new ttl, in seconds.
+        ttl: Ttl,
+        /// Only change records of this type. May be given several times.
+        #[clap(long = "type", value_name = "TYPE")]
+        types: Vec<RecordType>,
+        /// Only change records whose name contains this.
+        #[clap(long, value_name = "TEXT")]
+        name_contains: Option<String>,
+        /// Print the plan as JSON.
+        #[clap(long)]
+        json: bool,
+        /// Print the plan without applying it.
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Copy a domain's records to another, such as to spin up a staging domain.
+    ///
+    /// The source's name is replaced with the destination's in record names and contents.
+    Clone {
+        source: DomainName,
+        #[clap(name = "DESTINATION")]
+        domain: DomainName,
+        /// Record types to leave out.
+        #[clap(
+            long,
+            value_delimiter = ',',
+            default_value = "NS",
+            value_name = "TYPES"
+        )]
+        exclude: Vec<RecordType>,
+        /// Also replace FROM with TO in record names and contents. May be given several times.
+        #[clap(long, value_parser = parse_rewrite, value_name = "FROM=TO")]
+        rewrite: Vec<(String, String)>,
+        /// Delete records of the destination which the source doesn't have.
+        #[clap(long)]
+        prune: bool,
+        /// Print the plan as JSON.
+        #[clap(long)]
+        json: bool,
+        /// Print the plan without applying it.
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// List records whose name or content matches a pattern, across many domains, such as to
+    /// find every reference to a host being decommissioned.
+    Grep {
+        /// The text to look for, ignoring case.
+        pattern: String,
+        /// Treat the pattern as a regular expression.
+        #[clap(short = 'E', long)]
+        regex: bool,
+        #[clap(flatten)]
+        selection: search::Selection,
+        /// Print the matching records as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+    /// Replace text in the content of records across many domains, such as to move them from
+    /// one server's address to another's.
+    Replace {
+        /// The text to replace. Only whole fields and labels match, so 203.0.113.10 doesn't
+        /// match 203.0.113.100.
+        #[clap(long, value_name = "TEXT")]
+        from: String,
+        /// What to replace it with.
+        #[clap(long, value_name = "TEXT")]
+        to: String,
+        #[clap(flatten)]
+        selection: search::Selection,
+        /// Print the plans as JSON.
+        #[clap(long)]
+        json: bool,
+        /// Print the plans without applying them.
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Reverse the most recent changes recorded in the --audit-log, re-creating deleted records
+    /// and reverting edits.
+    ///
+    /// Undoing is itself recorded, so undoing the last change twice redoes it.
+    Undo {
+        /// How many changes to reverse.
+        #[clap(long, default_value_t = 1)]
+        last: usize,
+        /// Print the plans as JSON.
+        #[clap(long)]
+        json: bool,
+        /// Print the plans without applying them.
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Summarize a domain's records and point out likely mistakes.
+    Report {
+        domain: DomainName,
+        /// Print the report as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+
+    // These commands don't need a key.
+    match &cli.command {
+        Command::Schema { format } => {
+            let schema = match format {
+                SchemaFormat::ZoneSpec => porkbun::ZoneSpec::json_schema(),
+                SchemaFormat::Zones => porkbun::SpecSet::json_schema(),
+            };
+            println!("{schema:#}");
+            return;
+        }
+        Command::Coordinate {
+            socket,
+            requests,
+            per,
+        } => {
+            #[cfg(unix)]
+            {
+                let coordinator = porkbun::coordinator::Coordinator::new(*requests, *per);
+                tracing::info!("coordinating requests on {}", socket.display());
+                if let Err(msg) = coordinator.serve(socket) {
+                    error!("failed to serve {}: {msg}", socket.display());
+                }
+            }
+            #[cfg(not(unix))]
+            {
+                let _ = (socket, requests, per);
+                error!("the coordinator needs unix sockets, which this platform doesn't have");
+            }
+            exit(1);
+        }
+        Command::Debug(DebugCommand::Check { paths }) => {
+            if let Err(msg) = debug::check(paths) {
+                error!("{msg}");
+                exit(1);
+            }
+            return;
+        }
+        Command::Validate { paths, vars } => {
+            if let Err(msg) = validate::validate(paths, vars) {
+                error!("{msg}");
+                exit(1);
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let Some(key) = cli.key else {
+        error!("no key file was provided; use --key");
+        exit(1);
+    };
+    let mut client = porkbun::Client::open_keys(&key).unwrap_or_else(|msg| {
+        error!(
+            "failed to open key file ({}): {}",
+            key.display(),
+            dns::explain(&msg)
+        );
+        exit(1);
+    });
+    #[cfg(unix)]
+    if let Some(path) = &cli.coordinator {
+        client = client.with_coordinator(path);
+    }
+    if let Some(path) = &cli.audit_log {
+        let sink = porkbun::JsonlSink::open(path).unwrap_or_else(|msg| {
+            error!("failed to open audit log ({}): {msg}", path.display());
+            exit(1);
+        });
+        client = client.with_audit(sink);
+        let user = || {
+            std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .ok()
+        };
+        if let Some(actor) = cli.actor.clone().or_else(user) {
+            client = client.with_actor(actor);
+        }
+    }
+
+    if let Some(domain) = cli.command.dns().and_then(DnsCommand::domain) {
+        warn_lookalike(domain);
+    }
+    let plan_output = |json| dns::Output {
+        json,
+        unicode: !cli.ascii,
+        yes: cli.yes,
+        no_input: cli.no_input,
+    };
+
+    let source = |format, path, vars, verify_key| dns::Source {
+        format,
+        path,
+        vars,
+        verify_key,
+    };
+
+    let result = match cli.command {
+        Command::Dns(DnsCommand::Export {
+            domain,
+            format,
+            output,
+        }) => dns::export(&client, domain.to_ascii(), format, output.as_deref()),
+        Command::Dns(DnsCommand::Import {
+            domain,
+            format,
+            input,
+            vars,
+            dry_run,
+        }) => dns::import(
+            &client,
+            domain.to_ascii(),
+            source(format, &input, &vars, None),
+            dry_run,
+        ),
+        Command::Dns(DnsCommand::Diff {
+            domain,
+            format,
+            input,
+            vars,
+            json,
+        }) => dns::diff(
+            &client,
+            domain.to_ascii(),
+            source(format, &input, &vars, None),
+            plan_output(json),
+        ),
+        Command::Dns(DnsCommand::Sync {
+            domain,
+            format,
+            input,
+            vars,
+            verify_key,
+            prune,
+            on_conflict,
+            json,
+            dry_run,
+            lock,
+        }) => dns::sync(
+            &client,
+            domain.to_ascii(),
+            source(format, &input, &vars, verify_key.as_deref()),
+            &sync_options(prune, &on_conflict),
+            plan_output(json),
+            dry_run,
+            lock,
+        ),
+        Command::Dns(DnsCommand::Adopt {
+            domain,
+            out,
+            sign_key,
+            json,
+        }) => dns::adopt(
+            &client,
+            domain.to_ascii(),
+            out.as_deref(),
+            sign_key.as_deref(),
+            json,
+        ),
+        Command::Dns(DnsCommand::Propagation {
+            domain,
+            name,
+            ty,
+            expect,
+            resolvers,
+            wait,
+            json,
+        }) => {
+            let name = match name.as_str() {
+                "@" => domain.to_ascii().to_string(),
+                name => format!("{name}.{}", domain.to_ascii()),
+            };
+            dns::propagation(&name, ty, &expect, &resolvers, wait, json)
+        }
+        Command::Dns(DnsCommand::Spf {
+            domain,
+            resolver,
+            flatten,
+            apply,
+            json,
+        }) => dns::spf(
+            &client,
+            domain.to_ascii(),
+            &resolver,
+            flatten.then_some(apply),
+            plan_output(json),
+        ),
+        Command::Dns(DnsCommand::Preset {
+            domain,
+            preset,
+            options,
+            dry_run,
+        }) => dns::preset(
+            &client,
+            domain.to_ascii(),
+            preset,
+            &options,
+            plan_output(false),
+            dry_run,
+        ),
+        Command::Dns(DnsCommand::Create {
+            domain,
+            record,
+            json,
+            dry_run,
+        }) => dns::create(
+            &client,
+            domain.to_ascii(),
+            &record,
+            plan_output(json),
+            dry_run,
+        ),
+        Command::Dns(DnsCommand::Edit {
+            domain,
+            id,
+            changes,
+            from_json,
+            json,
+            dry_run,
+        }) => dns::edit(
+            &client,
+            domain.to_ascii(),
+            id,
+            &changes,
+            from_json.as_deref(),
+            plan_output(json),
+            dry_run,
+        ),
+        Command::Dns(DnsCommand::Delete {
+            domain,
+            ids,
+            select,
+            json,
+            dry_run,
+        }) => dns::delete(
+            &client,
+            domain.to_ascii(),
+            &ids,
+            select.as_deref(),
+            plan_output(json),
+            dry_run,
+        ),
+        Command::Dns(DnsCommand::SetTtl {
+            domain,
+            ttl,
+            types,
+            name_contains,
+            json,
+            dry_run,
+        }) => {
+            let filter = |record: &porkbun::DnsRecord| {
+                (types.is_empty() || types.contains(&record.ty))
+                    && name_contains
+                        .as_deref()
+                        .is_none_or(|x| record.name.contains(x))
+            };
+            dns::set_ttl(
+                &client,
+                domain.to_ascii(),
+                ttl,
+                filter,
+                plan_output(json),
+                dry_run,
+            )
+        }
+        Command::Dns(DnsCommand::Clone {
+            source,
+            domain,
+            exclude,
+            mut rewrite,
+            prune,
+            json,
+            dry_run,
+        }) => {
+            rewrite.insert(0, (source.to_ascii().into(), domain.to_ascii().into()));
+            let adapt = |spec: &mut ZoneSpec| {
+                spec.records.retain(|x| !exclude.contains(&x.ty));
+                for (from, to) in &rewrite {
+                    spec.rewrite(from, to);
+                }
+            };
+            dns::clone(
+                &client,
+                source.to_ascii(),
+                domain.to_ascii(),
+                adapt,
+                prune,
+                plan_output(json),
+                dry_run,
+            )
+        }
+        Command::Dns(DnsCommand::Grep {
+            pattern,
+            regex,
+            selection,
+            json,
+        }) => search::Pattern::new(&pattern, regex)
+            .and_then(|pattern| search::grep(&client, &pattern, &selection, json)),
+        Command::Dns(DnsCommand::Replace {
+            from,
+            to,
+            selection,
+            json,
+            dry_run,
+        }) => search::replace(&client, &from, &to, selection, plan_output(json), dry_run),
+        Command::Dns(DnsCommand::Undo {
+            last,
+            json,
+            dry_run,
+        }) => match &cli.audit_log {
+            Some(path) => dns::undo(&client, path, last, plan_output(json), dry_run),
+            None => Err("undo needs the --audit-log changes were recorded in".to_string()),
+        },
+        Command::Dns(DnsCommand::Report { domain, json }) => {
+            dns::report(&client, domain.to_ascii(), json)
+        }
+        Command::Caa(CaaCommand::Audit {
+            expect,
+            apply,
+            iodef,
+            jobs,
+            json,
+        }) => caa::audit(
+            &client,
+            caa::Audit {
+                expect,
+                apply,
+                iodef,
+                jobs,
+            },
+            plan_output(json),
+        ),
+        Command::Dmarc(DmarcCommand::Provision {
+            domains,
+            all,
+            policy,
+            subdomain_policy,
+            rua,
+            ruf,
+            jobs,
+            json,
+        }) => {
+            let mut dmarc = DmarcPolicy::new(policy);
+            dmarc.subdomain_policy = subdomain_policy;
+            for address in &rua {
+                dmarc = dmarc.with_rua(address);
+            }
+            for address in &ruf {
+                dmarc = dmarc.with_ruf(address);
+            }
+            let domains: porkbun::Result<Vec<String>> = if all {
+                client
+                    .list_domains()
+                    .map(|x| x.into_iter().map(|x| x.domain).collect())
+            } else {
+                Ok(domains.iter().map(|x| x.to_ascii().to_string()).collect())
+            };
+            domains
+                .map_err(|msg| format!("failed to list domains: {}", dns::explain(&msg)))
+                .and_then(|domains| {
+                    dmarc::provision(&client, &domains, &dmarc, jobs, plan_output(json))
+                })
+        }
+        Command::Acme(AcmeCommand::Auth {
+            domain,
+            validation,
+            wait,
+            resolvers,
+        }) => acme::auth(&client, &domain, &validation, wait, &resolvers),
+        Command::Acme(AcmeCommand::Cleanup { domain, validation }) => {
+            acme::cleanup(&client, &domain, &validation)
+        }
+        Command::Domains(DomainsCommand::List { utc, json }) => domains::list(&client, json, utc),
+        Command::Keys(KeysCommand::Rotate { from }) => rotate_keys(&client, &key, &from),
+        Command::Keys(KeysCommand::Check { domains }) => check_keys(&client, &domains),
+        Command::Doctor { domains } => doctor(&client, domains),
+        Command::Debug(DebugCommand::Capture { domains, out }) => {
+            debug::capture(&client, &domains, &out)
+        }
+        Command::Schema { .. }
+        | Command::Coordinate { .. }
+        | Command::Validate { .. }
+        | Command::Debug(DebugCommand::Check { .. }) => Ok(()),
+        Command::Zones(ZonesCommand::Diff {
+            input,
+            vars,
+            jobs,
+            json,
+        }) => zones::diff(
+            &client,
+            source(Format::Toml, &input, &vars, None),
+            jobs,
+            plan_output(json),
+        ),
+        Command::Zones(ZonesCommand::Sync {
+            input,
+            vars,
+            jobs,
+            verify_key,
+            prune,
+            on_conflict,
+            json,
+            dry_run,
+            lock,
+        }) => zones::sync(
+            &client,
+            source(Format::Toml, &input, &vars, verify_key.as_deref()),
+            jobs,
+            &sync_options(prune, &on_conflict),
+            plan_output(json),
+            dry_run,
+            lock,
+        ),
+    };
+    if let Err(msg) = result {
+        error!("{msg}");
+        exit(1);
+    }
+}
+
+#[derive(clap::Subcommand)]
+enum ZonesCommand {
+    /// Show how each zone's records differ from the spec file.
+    Diff {
+        /// The spec file, or `-` for stdin.
+        #[clap(value_parser, value_name = "PATH")]
+        input: PathBuf,
+        #[clap(flatten)]
+        vars: vars::Vars,
+        /// How many zones to fetch at once.
+        #[clap(short, long, default_value_t = 4)]
+        jobs: usize,
+        /// Print the plans as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+    /// Make each zone's records match the spec file.
+    Sync {
+        /// The spec file, or `-` for stdin.
+        #[clap(value_parser, value_name = "PATH")]
+        input: PathBuf,
+        #[clap(flatten)]
+        vars: vars::Vars,
+        /// How many zones to fetch or change at once.
+        #[clap(short, long, default_value_t = 4)]
+        jobs: usize,
+        /// Refuse the file unless its `.sig` signature was made by this ed25519 public key (PEM),
+        /// such as when restoring a snapshot written by `adopt --sign-key`. Included files and
+        /// --vars-file must be signed too, and --var and environment variables aren't used.
+        #[clap(long, value_parser, value_name = "PATH")]
+        verify_key: Option<PathBuf>,
+        /// Also delete records which aren't in the file.
+        #[clap(long)]
+        prune: bool,
+        /// What to do when live records differ from the file: overwrite, ignore, fail, or merge,
+        /// for every type or as TYPE=STRATEGY for one. May be repeated.
+        #[clap(long, value_parser = parse_conflict, value_name = "[TYPE=]STRATEGY")]
+        on_conflict: Vec<(Option<RecordType>, ConflictStrategy)>,
+        /// Print the plans as JSON.
+        #[clap(long)]
+        json: bool,
+        /// Print the plans without applying them.
+        #[clap(long)]
+        dry_run: bool,
+        /// Hold each zone's _porkbun-lock record from planning until the changes are applied,
+        /// so that concurrent syncs can't interleave. Stale locks expire after DURATION.
+        #[clap(long, value_parser = humantime::parse_duration, value_name = "DURATION", num_args = 0..=1, default_missing_value = "10m")]
+        lock: Option<Duration>,
+    },
+}
+
+impl Command {
+    fn dns(&self) -> Option<&DnsCommand> {
+        match self {
+            Command::Dns(command) => Some(command),
+            _ => None,
+        }
+    }
+}
+
+impl DnsCommand {
+    fn domain(&self) -> Option<&DomainName> {
+        Some(match self {
+            DnsCommand::Export { domain, .. }
+            | DnsCommand::Adopt { domain, .. }
+            | DnsCommand::Import { domain, .. }
+            | DnsCommand::Diff { domain, .. }
+            | DnsCommand::Sync { domain, .. }
+            | DnsCommand::Propagation { domain, .. }
+            | DnsCommand::Spf { domain, .. }
+            | DnsCommand::Preset { domain, .. }
+            | DnsCommand::Create { domain, .. }
+            | DnsCommand::Edit { domain, .. }
+            | DnsCommand::Delete { domain, .. }
+            | DnsCommand::SetTtl { domain, .. }
+            | DnsCommand::Clone { domain, .. }
+            | DnsCommand::Report { domain, .. } => domain,
+            DnsCommand::Grep { .. } | DnsCommand::Replace { .. } | DnsCommand::Undo { .. } => {
+                return None
+            }
+        })
+    }
+}
+
+fn parse_rewrite(text: &str) -> Result<(String, String), String> {
+    let (from, to) = text
+        .split_once('=')
+        .ok_or("expected FROM=TO, such as example.com=staging.example.com")?;
+    if from.is_empty() {
+        return Err("FROM can't be empty".into());
+    }
+    Ok((from.to_string(), to.to_string()))
+}
+
+fn parse_conflict(text: &str) -> Result<(Option<RecordType>, ConflictStrategy), String> {
+    match text.split_once('=') {
+        Some((ty, strategy)) => Ok((Some(ty.parse()?), strategy.parse()?)),
+        None => Ok((None, text.parse()?)),
+    }
+}
+
+fn sync_options(prune: bool, rules: &[(Option<RecordType>, ConflictStrategy)]) -> SyncOptions {
+    let mut options = SyncOptions::new().prune(prune);
+    for &(ty, strategy) in rules {
+        options = match ty {
+            Some(ty) => options.strategy_for(ty, strategy),
+            None => options.default_strategy(strategy),
+        };
+    }
+    options
+}
+
+/// Warns if a domain could be a lookalike of another, such as one using a Cyrillic `а`.
+fn warn_lookalike(domain: &DomainName) {
+    for label in domain.mixed_script_labels() {
+        warn!(
+            "\"{label}\" in {} mixes characters from different scripts, as lookalike domains do",
+            domain.to_unicode()
+        );
+    }
+}
+
+/// Checks the keys and each of `domains`, or every domain in the account, warning about those
+/// which could be lookalikes.
+fn doctor(client: &porkbun::Client, mut domains: Vec<DomainName>) -> Result<(), String> {
+    if domains.is_empty() {
+        let listed = client
+            .list_domains()
+            .map_err(|msg| format!("failed to list domains: {}", dns::explain(&msg)))?;
+        domains = listed
+            .iter()
+            .map(|x| DomainName::new(&x.domain))
+            .collect::<porkbun::Result<_>>()
+            .map_err(|msg| msg.to_string())?;
+    }
+    for domain in &domains {
+        warn_lookalike(domain);
+    }
+    check_keys(client, &domains)
+}
+
+/// Checks that the keys can manage each of `domains`, logging the result for each one.
+fn check_keys(client: &porkbun::Client, domains: &[DomainName]) -> Result<(), String> {
+    let mut failed = 0;
+    for domain in domains {
+        let domain = domain.to_ascii();
+        match client.verify_access(domain) {
+            Ok(Access::Granted) => tracing::info!("{domain}: ok"),
+            Ok(Access::BadCredentials { message }) => {
+                return Err(format!("the keys were rejected: {message}"));
+            }
+            Ok(Access::NotAuthorized { message }) => {
+                error!(
+                    "{domain}: {message}\n  hint: the domain may belong to another account, or API access may not be enabled for it in the Porkbun dashboard"
+                );
+                failed += 1;
+            }
+            Err(msg) => {
+                error!("{domain}: {}", dns::explain(&msg));
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!(
+            "{failed} of {} domains can't be managed",
+            domains.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Swaps the new pair of keys in `from` into the key file at `key`.
+fn rotate_keys(
+    client: &porkbun::Client,
+    key: &std::path::Path,
+    from: &std::path::Path,
+) -> Result<(), String> {
+    #[derive(serde::Deserialize)]
+    struct Keys {
+        #[serde(rename = "apikey")]
+        api: String,
+        #[serde(rename = "secretapikey")]
+        secret_api: String,
+    }
+
+    let keys: Keys = serde_json::from_str(&dns::read_input(from)?)
+        .map_err(|msg| format!("failed to parse {}: {msg}", from.display()))?;
+    client
+        .rotate_keys(key, &keys.api, &keys.secret_api)
+        .map_err(|msg| format!("failed to rotate keys: {}", dns::explain(&msg)))?;
+    tracing::info!(
+        "replaced the keys in {}; the old pair can now be deleted in the Porkbun dashboard",
+        key.display()
+    );
+    Ok(())
+}
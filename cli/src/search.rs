@@ -0,0 +1,237 @@
+use crate::dns::{self, Output};
+use porkbun::{ChangeSet, Client, DnsRecord, DomainName};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, error, info};
+
+/// Which zones to search, and how to fetch them.
+#[derive(clap::Args, Clone, Debug)]
+pub struct Selection {
+    /// The domains to search.
+    #[clap(required_unless_present = "all_domains")]
+    domains: Vec<DomainName>,
+    /// Search every domain in the account.
+    #[clap(long, conflicts_with = "domains")]
+    all_domains: bool,
+    /// How many domains to fetch at once.
+    #[clap(short, long, default_value_t = 4)]
+    jobs: usize,
+    /// Keep fetched records in this file, and reuse them on later runs.
+    #[clap(long, value_parser, value_name = "PATH")]
+    cache: Option<PathBuf>,
+    /// How long records in the cache stay fresh.
+    #[clap(long, value_parser = humantime::parse_duration, value_name = "DURATION", default_value = "10m")]
+    max_age: Duration,
+}
+
+/// The records of a domain, and when they were fetched in seconds since the Unix epoch.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+struct Cached {
+    fetched: u64,
+    records: Vec<DnsRecord>,
+}
+
+impl Selection {
+    /// Fetches the records of every selected domain, skipping those which fail.
+    pub fn fetch(&self, client: &Client) -> Result<Vec<(String, Vec<DnsRecord>)>, String> {
+        let domains: Vec<String> = if self.all_domains {
+            client
+                .list_domains()
+                .map_err(|msg| format!("failed to list domains: {}", dns::explain(&msg)))?
+                .into_iter()
+                .map(|x| x.domain)
+                .collect()
+        } else {
+            self.domains
+                .iter()
+                .map(|x| x.to_ascii().to_string())
+                .collect()
+        };
+
+        let mut cache = self.read_cache();
+        let now = now();
+        let fresh = |cached: &Cached| now.saturating_sub(cached.fetched) < self.max_age.as_secs();
+        let stale: Vec<&String> = domains
+            .iter()
+            .filter(|x| !cache.get(*x).is_some_and(fresh))
+            .collect();
+        debug!(
+            "{} of {} domains are cached",
+            domains.len() - stale.len(),
+            domains.len()
+        );
+        for (domain, records) in client.fetch_many(&stale, self.jobs) {
+            match records {
+                Ok(records) => {
+                    cache.insert(
+                        domain,
+                        Cached {
+                            fetched: now,
+                            records,
+                        },
+                    );
+                }
+                Err(msg) => error!("failed to fetch records of {domain}: {msg}"),
+            }
+        }
+        self.write_cache(&cache)?;
+
+        Ok(domains
+            .into_iter()
+            .filter_map(|domain| {
+                let records = cache.remove(&domain)?.records;
+                Some((domain, records))
+            })
+            .collect())
+    }
+
+    /// Fetches every domain anew, such as before changing records, while still updating the
+    /// cache.
+    pub fn refreshed(self) -> Self {
+        Self {
+            max_age: Duration::ZERO,
+            ..self
+        }
+    }
+
+    /// A missing or unreadable cache is treated as empty, since it can always be refetched.
+    fn read_cache(&self) -> BTreeMap<String, Cached> {
+        self.cache
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    fn write_cache(&self, cache: &BTreeMap<String, Cached>) -> Result<(), String> {
+        let Some(path) = &self.cache else {
+            return Ok(());
+        };
+        let json =
+            serde_json::to_string(cache).map_err(|msg| format!("failed to encode cache: {msg}"))?;
+        std::fs::write(path, json)
+            .map_err(|msg| format!("failed to write {}: {msg}", path.display()))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs())
+}
+
+/// Text to look for in records.
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    /// Matches records containing the text, ignoring case.
+    Text(String),
+    Regex(regex::Regex),
+}
+
+impl Pattern {
+    pub fn new(pattern: &str, regex: bool) -> Result<Self, String> {
+        if regex {
+            regex::Regex::new(pattern)
+                .map(Pattern::Regex)
+                .map_err(|msg| format!("invalid pattern: {msg}"))
+        } else {
+            Ok(Pattern::Text(pattern.to_lowercase()))
+        }
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        match self {
+            Pattern::Text(pattern) => text.to_lowercase().contains(pattern),
+            Pattern::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+/// Lists the records whose name or content matches `pattern`, across every selected domain.
+pub fn grep(
+    client: &Client,
+    pattern: &Pattern,
+    selection: &Selection,
+    json: bool,
+) -> Result<(), String> {
+    #[derive(serde::Serialize)]
+    struct Match<'a> {
+        domain: &'a str,
+        #[serde(flatten)]
+        record: &'a DnsRecord,
+    }
+
+    let zones = selection.fetch(client)?;
+    let matches: Vec<Match> = zones
+        .iter()
+        .flat_map(|(domain, records)| {
+            records
+                .iter()
+                .filter(|x| pattern.is_match(&x.name) || pattern.is_match(&x.content))
+                .map(move |record| Match { domain, record })
+        })
+        .collect();
+
+    if json {
+        let json = serde_json::to_string_pretty(&matches)
+            .map_err(|msg| format!("failed to encode matches: {msg}"))?;
+        println!("{json}");
+        return Ok(());
+    }
+    for Match { domain, record } in &matches {
+        println!(
+            "{domain}: {:<6} {} -> {}",
+            record.ty.as_str(),
+            record.name,
+            record.content
+        );
+    }
+    Ok(())
+}
+
+/// Replaces `from` with `to` in the content of every record of the selected domains.
+///
+/// Records are always fetched anew, so that stale cached records aren't written back.
+pub fn replace(
+    client: &Client,
+    from: &str,
+    to: &str,
+    selection: Selection,
+    output: Output,
+    dry_run: bool,
+) -> Result<(), String> {
+    let plans: Vec<ChangeSet> = selection
+        .refreshed()
+        .fetch(client)?
+        .iter()
+        .map(|(domain, records)| ChangeSet::replace(domain, records, from, to))
+        .filter(|x| !x.is_empty())
+        .collect();
+    if plans.is_empty() {
+        info!("no records contain {from}");
+        return Ok(());
+    }
+    output.print_plans(&plans);
+    if dry_run {
+        return Ok(());
+    }
+    if !output.confirm(&format!("Apply the changes to {} domains?", plans.len()))? {
+        return Err("cancelled".to_string());
+    }
+
+    let mut failed = 0;
+    for changes in plans.iter().cloned() {
+        if let Err(msg) = dns::apply(client, changes) {
+            error!("{msg}");
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(format!(
+            "{failed} of {} domains failed to update",
+            plans.len()
+        ));
+    }
+    Ok(())
+}
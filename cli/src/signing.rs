@@ -0,0 +1,55 @@
+//! Signatures of snapshot files, kept next to them with a `.sig` extension.
+
+use std::path::{Path, PathBuf};
+
+/// Where the signature of `path` is kept.
+fn signature_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".sig");
+    PathBuf::from(path)
+}
+
+/// Signs `text`, which was written to `path`, with the private key at `key`.
+#[cfg(feature = "signing")]
+pub fn sign(path: &Path, text: &str, key: &Path) -> Result<(), String> {
+    let key = porkbun::signing::signing_key(&read(key)?)
+        .map_err(|msg| format!("{}: {msg}", key.display()))?;
+    let signature_path = signature_path(path);
+    let signature = porkbun::signing::sign(&key, text.as_bytes());
+    std::fs::write(&signature_path, signature + "\n")
+        .map_err(|msg| format!("failed to write {}: {msg}", signature_path.display()))
+}
+
+/// Checks `text`, which was read from `path`, against its signature and the public key at
+/// `key`.
+#[cfg(feature = "signing")]
+pub fn verify(path: &Path, text: &str, key: &Path) -> Result<(), String> {
+    let key = porkbun::signing::verifying_key(&read(key)?)
+        .map_err(|msg| format!("{}: {msg}", key.display()))?;
+    let signature = read(&signature_path(path))?;
+    porkbun::signing::verify(&key, text.as_bytes(), &signature)
+        .map_err(|msg| format!("{}: {msg}", path.display()))
+}
+
+#[cfg(feature = "signing")]
+fn read(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|msg| format!("failed to read {}: {msg}", path.display()))
+}
+
+#[cfg(not(feature = "signing"))]
+pub fn sign(path: &Path, _text: &str, _key: &Path) -> Result<(), String> {
+    Err(unsupported(path))
+}
+
+#[cfg(not(feature = "signing"))]
+pub fn verify(path: &Path, _text: &str, _key: &Path) -> Result<(), String> {
+    Err(unsupported(path))
+}
+
+#[cfg(not(feature = "signing"))]
+fn unsupported(path: &Path) -> String {
+    format!(
+        "can't check {}; this build doesn't support signatures (enable the signing feature)",
+        signature_path(path).display()
+    )
+}
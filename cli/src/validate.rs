@@ -0,0 +1,90 @@
+use crate::dns;
+use crate::format::parse_specs;
+use crate::vars::Vars;
+use porkbun::DomainName;
+use std::path::{Path, PathBuf};
+use tracing::{error, info};
+
+/// Checks toml zone specs without contacting porkbun, printing each mistake with its location.
+pub fn validate(paths: &[PathBuf], vars: &Vars) -> Result<(), String> {
+    let problems: usize = paths.iter().map(|path| check(path, vars)).sum();
+    if problems > 0 {
+        return Err(format!("found {problems} problems"));
+    }
+    info!("no problems found");
+    Ok(())
+}
+
+/// Prints the problems in one file, returning how many there were.
+fn check(path: &Path, vars: &Vars) -> usize {
+    let file = path.display();
+    let text = match dns::read_input(path) {
+        Ok(text) => text,
+        Err(msg) => {
+            error!("{msg}");
+            return 1;
+        }
+    };
+    let specs = match parse_specs(&text, dns::spec_dir(path), None) {
+        Ok(specs) => specs,
+        Err(msg) => {
+            error!("{file}: {msg}");
+            return 1;
+        }
+    };
+
+    let headers = record_headers(&text);
+    let mut problems = 0;
+    for (zone, mut spec) in specs.into_iter().enumerate() {
+        let lookup = match vars.lookup(&spec.domain, None) {
+            Ok(lookup) => lookup,
+            Err(msg) => {
+                error!("{msg}");
+                return problems + 1;
+            }
+        };
+        if let Err(msg) = spec.interpolate(lookup) {
+            error!("{file}: {}: {msg}", spec.domain);
+            problems += 1;
+            continue;
+        }
+        if let Err(msg) = DomainName::new(spec.domain.trim_end_matches('.')) {
+            error!("{file}: {msg}");
+            problems += 1;
+        }
+
+        // A zone's own records come after those of its blocks and includes.
+        let own = headers.get(zone).map_or(&[][..], Vec::as_slice);
+        let shared = spec.records.len().saturating_sub(own.len());
+        for problem in spec.validate() {
+            match problem.record.checked_sub(shared).and_then(|x| own.get(x)) {
+                Some(line) => error!("{file}:{line}:1: {}: {}", spec.domain, problem.error),
+                None => error!(
+                    "{file}: {}: shared record {}: {}",
+                    spec.domain,
+                    problem.record + 1,
+                    problem.error
+                ),
+            }
+            problems += 1;
+        }
+    }
+    problems
+}
+
+/// The line numbers of the `[[records]]` headers of each zone in a spec file.
+fn record_headers(text: &str) -> Vec<Vec<usize>> {
+    let mut zones = vec![Vec::new()];
+    let mut multi_zone = false;
+    for (index, line) in text.lines().enumerate() {
+        match line.trim() {
+            "[[zones]]" if multi_zone => zones.push(Vec::new()),
+            "[[zones]]" => multi_zone = true,
+            "[[records]]" | "[[zones.records]]" => {
+                zones.last_mut().into_iter().for_each(|x| x.push(index + 1))
+            }
+            _ => {}
+        }
+    }
+    zones
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Values for `${name}` placeholders in a toml zone spec.
+///
+/// `--var` takes precedence over `--vars-file`, which takes precedence over environment
+/// variables. `${domain}` is the domain being managed, unless it's set with a flag or file.
+///
+/// When the spec's signature is checked, only the vars file is used, and it must be signed
+/// by the same key, so that nothing unsigned can change the records.
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct Vars {
+    /// Set a variable for `${NAME}` placeholders in a toml zone spec.
+    #[clap(long = "var", value_name = "NAME=VALUE", value_parser = parse_var)]
+    vars: Vec<(String, String)>,
+    /// Read variables from a TOML file of strings, such as `ip = "203.0.113.7"`.
+    #[clap(long, value_parser, value_name = "PATH")]
+    vars_file: Option<PathBuf>,
+}
+
+fn parse_var(var: &str) -> Result<(String, String), String> {
+    var.split_once('=')
+        .map(|(name, value)| (name.trim().to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected NAME=VALUE, not \"{var}\""))
+}
+
+impl Vars {
+    /// Returns a function which looks up the value of a variable.
+    ///
+    /// With a `verify_key`, the vars file must be signed by it, and `--var` is refused.
+    pub fn lookup(
+        &self,
+        domain: &str,
+        verify_key: Option<&Path>,
+    ) -> Result<impl Fn(&str) -> Option<String>, String> {
+        if verify_key.is_some() && !self.vars.is_empty() {
+            return Err("--var can't be used with --verify-key, since it isn't signed".into());
+        }
+        let mut values: HashMap<String, String> = match &self.vars_file {
+            Some(path) => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|msg| format!("failed to read {}: {msg}", path.display()))?;
+                if let Some(key) = verify_key {
+                    crate::signing::verify(path, &text, key)?;
+                }
+                toml::from_str(&text)
+                    .map_err(|msg| format!("failed to parse {}: {msg}", path.display()))?
+            }
+            None => HashMap::new(),
+        };
+        values.extend(self.vars.iter().cloned());
+        values
+            .entry("domain".to_string())
+            .or_insert_with(|| domain.to_string());
+        let signed = verify_key.is_some();
+        Ok(move |name: &str| {
+            values
+                .get(name)
+                .cloned()
+                .or_else(|| (!signed).then(|| std::env::var(name).ok())?)
+        })
+    }
+}
@@ -0,0 +1,131 @@
+use crate::dns::{self, Output, Source};
+use crate::format::parse_specs;
+use porkbun::{ChangeSet, Client, DomainName, RecordType, SyncOptions, ZoneSpec};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::time::Duration;
+use tracing::{error, warn};
+
+/// Prints the changes which would make every zone in a spec file match it.
+pub fn diff(client: &Client, source: Source, jobs: usize, output: Output) -> Result<(), String> {
+    let plans = plan(
+        client,
+        &load(source)?,
+        jobs,
+        &SyncOptions::new().prune(true),
+    )?;
+    output.print_plans(&plans);
+    Ok(())
+}
+
+/// Makes every zone in a spec file match it.
+///
+/// Each zone is applied as its own transaction, so one failing doesn't stop the others.
+/// With `lock`, every zone is locked before any is planned.
+pub fn sync(
+    client: &Client,
+    source: Source,
+    jobs: usize,
+    options: &SyncOptions,
+    output: Output,
+    dry_run: bool,
+    lock: Option<Duration>,
+) -> Result<(), String> {
+    let specs = load(source)?;
+    let _locks = match lock {
+        Some(duration) => specs
+            .iter()
+            .map(|spec| dns::lock(client, &spec.domain, duration))
+            .collect::<Result<Vec<_>, String>>()?,
+        None => Vec::new(),
+    };
+    let plans = plan(client, &specs, jobs, options)?;
+    output.print_plans(&plans);
+    if dry_run {
+        return Ok(());
+    }
+    output.confirm_deletions(&plans)?;
+
+    let plans: Vec<ChangeSet> = plans.into_iter().filter(|x| !x.is_empty()).collect();
+    let mut failed = 0;
+    for result in parallel(&plans, jobs, |changes| dns::apply(client, changes.clone())) {
+        if let Err(msg) = result {
+            error!("{msg}");
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{failed} of {} zones failed to sync", plans.len()));
+    }
+    Ok(())
+}
+
+/// Reads the zones of a spec file, with their placeholders filled in.
+///
+/// The source's format is ignored, since only toml specs can describe several zones.
+fn load(source: Source) -> Result<Vec<ZoneSpec>, String> {
+    let path = source.path;
+    let mut specs = parse_specs(&source.read()?, dns::spec_dir(path), source.verify_key)
+        .map_err(|msg| format!("failed to parse {}: {msg}", path.display()))?;
+    for spec in &mut specs {
+        let domain = spec.domain.clone();
+        spec.interpolate(source.vars.lookup(&domain, source.verify_key)?)
+            .map_err(|msg| format!("{domain}: {msg}"))?;
+        spec.domain = DomainName::new(spec.domain.trim_end_matches('.'))
+            .map_err(|msg| format!("{}: {msg}", spec.domain))?
+            .to_ascii()
+            .to_string();
+    }
+    Ok(specs)
+}
+
+fn plan(
+    client: &Client,
+    specs: &[ZoneSpec],
+    jobs: usize,
+    options: &SyncOptions,
+) -> Result<Vec<ChangeSet>, String> {
+    parallel(specs, jobs, |spec| {
+        if spec
+            .wildcard
+            .as_ref()
+            .is_some_and(|x| x.ty == RecordType::Cname)
+        {
+            for name in spec.shadowed_names() {
+                warn!(
+                    "{name}.{} has records of its own, so the wildcard CNAME doesn't answer for it",
+                    spec.domain
+                );
+            }
+        }
+        let existing = dns::fetch(client, &spec.domain)?;
+        ChangeSet::diff_with(&spec.domain, &existing, &spec.to_records(), options)
+            .map_err(|msg| format!("can't sync {}: {msg}", spec.domain))
+    })
+    .into_iter()
+    .collect()
+}
+
+/// Calls `f` on every item, at most `jobs` at a time, and returns the results in order.
+fn parallel<T: Sync, R: Send>(items: &[T], jobs: usize, f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let (send, receive) = mpsc::channel();
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            let send = send.clone();
+            let (next, f) = (&next, &f);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                // The receiver outlives every worker.
+                send.send((index, f(item))).ok();
+            });
+        }
+    });
+    drop(send);
+    let mut results: Vec<(usize, R)> = receive.into_iter().collect();
+    results.sort_by_key(|x| x.0);
+    results.into_iter().map(|x| x.1).collect()
+}
@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A ddns configuration file.
+///
+/// This is a JSON file listing every record that should be kept up to date.
+/// For example:
+/// ```json
+/// {
+///     "key": "/etc/ddns/keys.json",
+///     "records": [
+///         { "domain": "example.com", "ipv4": true, "ipv6": true },
+///         { "domain": "example.com", "subdomain": "www", "ipv4": true }
+///     ],
+///     "endpoints": {
+///         "dns/retrieve": { "timeout": 60 }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Path to the porkbun api key file, used when `--key` is not given.
+    #[serde(default)]
+    pub key: Option<PathBuf>,
+    #[serde(default)]
+    pub records: Vec<Target>,
+    /// Overrides for particular API endpoints, such as `dns/retrieve`, on top of
+    /// [Config::endpoints]'s defaults.
+    #[serde(default)]
+    pub endpoints: BTreeMap<String, Endpoint>,
+}
+
+/// How requests to one API endpoint are made.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Endpoint {
+    /// Seconds before a request is abandoned.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// Attempts made after the first is rejected by the rate limit.
+    #[serde(default)]
+    pub retries: Option<u32>,
+}
+
+impl Endpoint {
+    pub fn policy(&self) -> porkbun::EndpointPolicy {
+        porkbun::EndpointPolicy {
+            timeout: self.timeout.map(Duration::from_secs),
+            backoff: self.retries.map(|retries| porkbun::retry::Backoff {
+                max_attempts: retries + 1,
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+impl Config {
+    /// The JSON schema of config files, for editors to check them with.
+    pub fn json_schema() -> serde_json::Value {
+        schemars::schema_for!(Config).to_value()
+    }
+
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let file = std::fs::read_to_string(path).map_err(|msg| msg.to_string())?;
+        serde_json::from_str(&file).map_err(|msg| msg.to_string())
+    }
+
+    /// Finds mistakes which parsing doesn't catch: invalid domains, records which update
+    /// neither address family, and records whose name is listed twice for the same family.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (index, target) in self.records.iter().enumerate() {
+            if let Err(msg) = porkbun::DomainName::new(&target.domain) {
+                problems.push(format!("record {}: {msg}", index + 1));
+            }
+            if !target.ipv4 && !target.ipv6 {
+                problems.push(format!(
+                    "record {} ({target}) updates neither ipv4 nor ipv6",
+                    index + 1
+                ));
+            }
+            // A name may be listed once for ipv4 and once for ipv6, but not twice for either.
+            let same = |x: &&Target| x.domain == target.domain && x.subdomain == target.subdomain;
+            let earlier: Vec<&Target> = self.records[..index].iter().filter(same).collect();
+            let families = [
+                ("ipv4", target.ipv4, earlier.iter().any(|x| x.ipv4)),
+                ("ipv6", target.ipv6, earlier.iter().any(|x| x.ipv6)),
+            ];
+            for (family, updates, listed) in families {
+                if updates && listed {
+                    problems.push(format!(
+                        "record {} ({target}) updates {family} of a name listed before",
+                        index + 1
+                    ));
+                }
+            }
+        }
+        problems
+    }
+
+    /// The behavior of every endpoint which differs from the client's.
+    ///
+    /// Edits aren't retried unless configured to be, since a request which seemed to fail may
+    /// still have been applied. Creations are checked for before being retried, so they are.
+    pub fn endpoints(&self) -> BTreeMap<String, Endpoint> {
+        let no_retries = Endpoint {
+            timeout: None,
+            retries: Some(0),
+        };
+        let mut endpoints: BTreeMap<String, Endpoint> = ["dns/edit", "dns/editByNameType"]
+            .into_iter()
+            .map(|x| (x.to_string(), no_retries.clone()))
+            .collect();
+        endpoints.extend(self.endpoints.clone());
+        endpoints
+    }
+}
+
+/// A single record managed by ddns.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Target {
+    pub domain: String,
+    #[serde(default)]
+    pub subdomain: Option<String>,
+    #[serde(default)]
+    pub ipv4: bool,
+    #[serde(default)]
+    pub ipv6: bool,
+}
+
+impl Target {
+    /// The record's full name, such as `www.example.com`.
+    pub fn name(&self) -> String {
+        match &self.subdomain {
+            Some(subdomain) => format!("{subdomain}.{}", self.domain),
+            None => self.domain.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(subdomain) = &self.subdomain {
+            write!(f, "{subdomain}.")?;
+        }
+        write!(f, "{}", self.domain)?;
+        match (self.ipv4, self.ipv6) {
+            (true, true) => write!(f, " (ipv4, ipv6)"),
+            (true, false) => write!(f, " (ipv4)"),
+            (false, true) => write!(f, " (ipv6)"),
+            (false, false) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(records: serde_json::Value) -> Config {
+        serde_json::from_value(serde_json::json!({ "records": records })).unwrap()
+    }
+
+    #[test]
+    fn a_name_may_be_listed_once_per_family() {
+        let config = config(serde_json::json!([
+            { "domain": "example.com", "subdomain": "www", "ipv4": true },
+            { "domain": "example.com", "subdomain": "www", "ipv6": true },
+        ]));
+        assert_eq!(config.validate(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn a_family_listed_twice_is_a_problem() {
+        let config = config(serde_json::json!([
+            { "domain": "example.com", "ipv4": true },
+            { "domain": "example.com", "subdomain": "www", "ipv4": true },
+            { "domain": "example.com", "ipv4": true, "ipv6": true },
+        ]));
+        assert_eq!(
+            config.validate(),
+            ["record 3 (example.com (ipv4, ipv6)) updates ipv4 of a name listed before"]
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use crate::config::{Config, Target};
+use notify::Watcher;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+/// Updates every target and lease once per `interval`, forever.
+///
+/// If a config file is given, it is watched for changes and its record list is reloaded
+/// in place, without waiting for the next update. With the `netlink` feature on Linux,
+/// records are also updated as soon as the kernel reports an address change, such as when
+/// a PPPoE connection is re-established.
+pub fn run(
+    client: &porkbun::Client,
+    config_path: Option<&Path>,
+    mut targets: Vec<Target>,
+    leases: Option<&crate::leases::Source>,
+    interval: Duration,
+    options: &crate::Options,
+) -> ! {
+    let (sender, events) = mpsc::channel();
+    #[cfg(all(feature = "netlink", target_os = "linux"))]
+    if let Err(msg) = crate::netlink::watch(sender.clone()) {
+        warn!("failed to watch for address changes, only polling: {msg}");
+    }
+    // The watcher stops when dropped, so it must be kept alive for the whole loop.
+    let _watcher = config_path.and_then(|path| match watch(path, sender) {
+        Ok(watcher) => Some(watcher),
+        Err(msg) => {
+            warn!(
+                "failed to watch config file ({}), changes will require a restart: {msg}",
+                path.display()
+            );
+            None
+        }
+    });
+
+    loop {
+        crate::run_once(client, &targets, leases, options);
+
+        let deadline = Instant::now() + interval;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match events.recv_timeout(timeout) {
+                Ok(first) => {
+                    std::thread::sleep(first.settle_time());
+                    let events: Vec<Event> =
+                        std::iter::once(first).chain(events.try_iter()).collect();
+                    let mut changed = events.contains(&Event::AddressChanged);
+                    if changed {
+                        info!("network addresses changed");
+                    }
+                    if let (true, Some(path)) =
+                        (events.contains(&Event::ConfigChanged), config_path)
+                    {
+                        changed |= reload(path, &mut targets);
+                    }
+                    if changed {
+                        break;
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    std::thread::sleep(timeout);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Something which calls for an update before the interval is up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    ConfigChanged,
+    AddressChanged,
+}
+
+impl Event {
+    /// How long to wait for related events before acting on this one.
+    fn settle_time(self) -> Duration {
+        match self {
+            // Editors often write a file in several steps.
+            Event::ConfigChanged => Duration::from_millis(100),
+            // An interface coming up is given several addresses in quick succession.
+            Event::AddressChanged => Duration::from_secs(2),
+        }
+    }
+}
+
+/// Returns true if the list of targets changed.
+fn reload(path: &Path, targets: &mut Vec<Target>) -> bool {
+    let config = match Config::open(path) {
+        Ok(config) => config,
+        Err(msg) => {
+            error!(
+                "failed to reload config file ({}), keeping previous records: {msg}",
+                path.display()
+            );
+            return false;
+        }
+    };
+
+    let mut changed = false;
+    for target in targets.iter().filter(|x| !config.records.contains(x)) {
+        info!("removed {target}");
+        changed = true;
+    }
+    for target in config.records.iter().filter(|x| !targets.contains(x)) {
+        info!("added {target}");
+        changed = true;
+    }
+    *targets = config.records;
+    changed
+}
+
+fn watch(path: &Path, sender: mpsc::Sender<Event>) -> notify::Result<notify::RecommendedWatcher> {
+    // Watch the parent directory rather than the file itself,
+    // since many editors save by replacing the file.
+    let path = std::path::absolute(path)?;
+    let directory = path
+        .parent()
+        .map_or_else(|| PathBuf::from("/"), Path::to_path_buf);
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            if !event.kind.is_access() && event.paths.contains(&path) {
+                let _ = sender.send(Event::ConfigChanged);
+            }
+        }
+    })?;
+    watcher.watch(&directory, notify::RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
@@ -0,0 +1,107 @@
+//! The --min-update-interval guard, which keeps flapping addresses from changing a record
+//! more than once per interval.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// When each family of each record was last changed, in seconds since the unix epoch.
+type Changes = BTreeMap<String, BTreeMap<String, u64>>;
+
+/// When each record was last changed, so that none is changed more than once per interval.
+///
+/// With a state file, the times are kept between runs, such as from cron or a systemd timer.
+pub struct HoldDown {
+    interval: Duration,
+    path: Option<PathBuf>,
+    changes: Mutex<Changes>,
+}
+
+impl HoldDown {
+    /// A guard which only remembers changes while ddns runs.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            path: None,
+            changes: Mutex::default(),
+        }
+    }
+
+    /// A guard which remembers changes in the state file at `path`, reading the changes of
+    /// previous runs from it if it exists.
+    pub fn open(interval: Duration, path: &Path) -> Result<Self, String> {
+        let changes = match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|msg| msg.to_string())?,
+            Err(msg) if msg.kind() == std::io::ErrorKind::NotFound => Changes::new(),
+            Err(msg) => return Err(msg.to_string()),
+        };
+        Ok(Self {
+            interval,
+            path: Some(path.to_path_buf()),
+            changes: Mutex::new(changes),
+        })
+    }
+
+    /// How long until a family of the record `name` may be changed again, if it can't yet.
+    pub fn remaining(&self, name: &str, family: &str) -> Option<Duration> {
+        let changed = *self.lock().get(name)?.get(family)?;
+        let elapsed = now().saturating_sub(changed);
+        Some(self.interval.saturating_sub(Duration::from_secs(elapsed))).filter(|x| !x.is_zero())
+    }
+
+    pub fn changed(&self, name: String, family: &str) {
+        if self.interval.is_zero() {
+            return;
+        }
+        let mut changes = self.lock();
+        changes
+            .entry(name)
+            .or_default()
+            .insert(family.to_string(), now());
+        if let Some(path) = &self.path {
+            if let Err(msg) = write(path, &changes) {
+                warn!("failed to write state file ({}): {msg}", path.display());
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Changes> {
+        self.changes
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs())
+}
+
+/// Writes the changes to `path` as JSON, replacing it atomically.
+fn write(path: &Path, changes: &Changes) -> std::io::Result<()> {
+    let text = serde_json::to_string_pretty(changes)?;
+    crate::write_atomically(path, &(text + "\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_are_remembered_between_runs() {
+        let path = std::env::temp_dir().join(format!("ddns-hold-down-{}.json", std::process::id()));
+        let interval = Duration::from_secs(300);
+
+        let first = HoldDown::open(interval, &path).unwrap();
+        assert_eq!(first.remaining("www.example.com", "ipv4"), None);
+        first.changed("www.example.com".to_string(), "ipv4");
+
+        let second = HoldDown::open(interval, &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(second.remaining("www.example.com", "ipv4").is_some());
+        assert_eq!(second.remaining("www.example.com", "ipv6"), None);
+    }
+}
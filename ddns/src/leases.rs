@@ -0,0 +1,395 @@
+use porkbun::{canonicalize, DnsRecord, RecordType, Zone};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
+
+/// Publishes a record for every hostname in a DHCP lease file.
+#[derive(Clone, Debug)]
+pub struct Source {
+    pub path: PathBuf,
+    /// The domain registered with porkbun.
+    pub domain: String,
+    /// Name under which hosts are published, such as `lan.example.com`.
+    ///
+    /// This must be a subdomain of `domain`. A and AAAA records which were created for leases
+    /// are deleted once their lease expires.
+    pub suffix: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Lease {
+    pub hostname: String,
+    pub address: IpAddr,
+    /// Seconds since the unix epoch, or `None` if the lease never expires.
+    pub expires: Option<u64>,
+}
+
+/// Reads a dnsmasq or Kea (CSV) lease file.
+///
+/// Only leases which are still active are returned.
+pub fn read(path: &Path) -> Result<Vec<Lease>, String> {
+    let file = std::fs::read_to_string(path).map_err(|msg| msg.to_string())?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs());
+    let leases = if file.starts_with("address,") {
+        parse_kea(&file)?
+    } else {
+        parse_dnsmasq(&file)?
+    };
+    Ok(leases
+        .into_iter()
+        .filter(|lease| lease.expires.is_none_or(|expires| expires > now))
+        .collect())
+}
+
+/// Each line is `<expiry> <mac or iaid> <address> <hostname> <client id>`.
+///
+/// ipv6 leases are preceded by a `duid` line, which is skipped.
+fn parse_dnsmasq(file: &str) -> Result<Vec<Lease>, String> {
+    let mut leases = Vec::new();
+    for (number, line) in file.lines().enumerate() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.is_empty() || fields[0] == "duid" {
+            continue;
+        }
+        let [expires, _, address, hostname, ..] = fields[..] else {
+            return Err(format!("line {}: expected at least 4 fields", number + 1));
+        };
+        let expires: u64 = expires
+            .parse()
+            .map_err(|msg| format!("line {}: invalid expiry: {msg}", number + 1))?;
+        let address = address
+            .parse()
+            .map_err(|msg| format!("line {}: invalid address: {msg}", number + 1))?;
+        if hostname == "*" {
+            continue;
+        }
+        leases.push(Lease {
+            hostname: hostname.to_string(),
+            address,
+            expires: (expires != 0).then_some(expires),
+        });
+    }
+    Ok(leases)
+}
+
+/// Kea's memfile backend appends a line whenever a lease changes,
+/// so later lines replace earlier ones with the same address.
+fn parse_kea(file: &str) -> Result<Vec<Lease>, String> {
+    let mut lines = file.lines().enumerate();
+    let Some((_, header)) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    let header: Vec<&str> = header.split(',').collect();
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|x| *x == name)
+            .ok_or_else(|| format!("missing \"{name}\" column"))
+    };
+    let address_column = column("address")?;
+    let expire_column = column("expire")?;
+    let hostname_column = column("hostname")?;
+    let state_column = column("state")?;
+
+    let mut leases = HashMap::new();
+    for (number, line) in lines {
+        let fields: Vec<&str> = line.split(',').collect();
+        let field = |column: usize| {
+            fields
+                .get(column)
+                .copied()
+                .ok_or_else(|| format!("line {}: missing field", number + 1))
+        };
+        let address: IpAddr = field(address_column)?
+            .parse()
+            .map_err(|msg| format!("line {}: invalid address: {msg}", number + 1))?;
+        let hostname = field(hostname_column)?;
+        // State 0 is an assigned lease; anything else has been declined or released.
+        if hostname.is_empty() || field(state_column)? != "0" {
+            leases.remove(&address);
+            continue;
+        }
+        let expires = field(expire_column)?
+            .parse()
+            .map_err(|msg| format!("line {}: invalid expiry: {msg}", number + 1))?;
+        leases.insert(
+            address,
+            Lease {
+                hostname: hostname.trim_end_matches('.').to_string(),
+                address,
+                expires: Some(expires),
+            },
+        );
+    }
+    Ok(leases.into_values().collect())
+}
+
+/// Turns a DHCP hostname into a single DNS label, if it's a valid one.
+fn label(hostname: &str) -> Option<String> {
+    let label = hostname.split('.').next()?.to_ascii_lowercase();
+    let valid = !label.is_empty()
+        && label.len() <= 63
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label.chars().all(|x| x.is_ascii_alphanumeric() || x == '-');
+    valid.then_some(label)
+}
+
+/// The tool name written into the notes of records created for leases, so that only those
+/// are ever pruned.
+const OWNER: &str = "ddns-leases";
+
+/// Brings the records beneath `source.suffix` in line with the active leases,
+/// returning the number of failed operations.
+///
+/// Only records created this way are deleted; others beneath the suffix are left alone.
+pub fn sync(client: &porkbun::Client, source: &Source, silent: bool) -> i32 {
+    let Some(prefix) = subdomain_prefix(&source.suffix, &source.domain) else {
+        error!(
+            "lease suffix ({}) must be a subdomain of {}",
+            source.suffix, source.domain
+        );
+        return 1;
+    };
+    let client = client.clone().with_owner(OWNER);
+
+    let leases = match read(&source.path) {
+        Ok(leases) => leases,
+        Err(msg) => {
+            error!(
+                "failed to read lease file ({}): {msg}",
+                source.path.display()
+            );
+            return 1;
+        }
+    };
+    let desired = desired(&leases, &source.suffix);
+
+    let zone = match client.fetch_records(&source.domain) {
+        Ok(records) => Zone::from(records),
+        Err(msg) => {
+            error!("failed to retrieve records for {}: {msg}", source.domain);
+            crate::explain(&msg);
+            return 1;
+        }
+    };
+    let parent = format!(".{}", source.suffix);
+    let existing: Vec<&DnsRecord> = zone
+        .filter()
+        .matching(|x| matches!(x.ty, RecordType::A | RecordType::Aaaa))
+        .matching(|x| {
+            x.name
+                .strip_suffix(&parent)
+                .is_some_and(|label| !label.is_empty() && !label.contains('.'))
+        })
+        .collect();
+
+    let mut error_count = 0;
+
+    for record in stale(&existing, &desired, |x| client.is_managed(x)) {
+        if let Err(msg) = client.delete_record(&source.domain, record.id) {
+            error!("failed to delete stale record {}: {msg}", record.name);
+            error_count += 1;
+        } else if !silent {
+            info!("deleted stale record {} ({})", record.name, record.content);
+        }
+    }
+
+    for ((name, ty), lease) in &desired {
+        let content = lease.address.to_string();
+        let same_name = || existing.iter().filter(|x| x.name == *name && x.ty == *ty);
+        if same_name().any(|x| canonicalize(x.ty, &x.content) == content) {
+            continue;
+        }
+        if same_name().any(|x| !client.is_managed(x)) {
+            warn!(
+                "not publishing {name} ({content}), which already has records ddns didn't create"
+            );
+            continue;
+        }
+        let label = name.trim_end_matches(&parent);
+        let subdomain = format!("{label}.{prefix}");
+        if let Err(msg) =
+            client.create_record(&source.domain, Some(&subdomain), *ty, &content, None, None)
+        {
+            error!("failed to create record {name}: {msg}");
+            error_count += 1;
+        } else if !silent {
+            info!("created record {name} ({content})");
+        }
+    }
+
+    error_count
+}
+
+/// The lease to publish at each name and type beneath `suffix`.
+///
+/// When a host holds several leases of the same family, the longest-lived one is published.
+fn desired<'a>(leases: &'a [Lease], suffix: &str) -> HashMap<(String, RecordType), &'a Lease> {
+    let mut desired: HashMap<(String, RecordType), &Lease> = HashMap::new();
+    for lease in leases {
+        let Some(label) = label(&lease.hostname) else {
+            warn!("skipping lease with invalid hostname: {}", lease.hostname);
+            continue;
+        };
+        let ty = match lease.address {
+            IpAddr::V4(_) => RecordType::A,
+            IpAddr::V6(_) => RecordType::Aaaa,
+        };
+        desired
+            .entry((format!("{label}.{suffix}"), ty))
+            .and_modify(|x| {
+                if x.expires
+                    .is_some_and(|a| lease.expires.is_none_or(|b| b > a))
+                {
+                    *x = lease;
+                }
+            })
+            .or_insert(lease);
+    }
+    desired
+}
+
+/// The `existing` records which were created for leases, but which no active lease
+/// publishes any more.
+fn stale<'a>(
+    existing: &[&'a DnsRecord],
+    desired: &HashMap<(String, RecordType), &Lease>,
+    managed: impl Fn(&DnsRecord) -> bool,
+) -> Vec<&'a DnsRecord> {
+    existing
+        .iter()
+        .copied()
+        .filter(|record| managed(record))
+        .filter(|record| {
+            !desired
+                .get(&(record.name.clone(), record.ty))
+                .is_some_and(|lease| {
+                    lease.address.to_string() == canonicalize(record.ty, &record.content)
+                })
+        })
+        .collect()
+}
+
+/// Returns the part of `suffix` before `domain`, such as `lan` for `lan.example.com`,
+/// or `None` if `suffix` isn't a subdomain of `domain`.
+///
+/// The domain itself isn't allowed, since every single-label record of the zone would then
+/// look like a lease.
+fn subdomain_prefix<'a>(suffix: &'a str, domain: &str) -> Option<&'a str> {
+    suffix
+        .strip_suffix(domain)
+        .and_then(|x| x.strip_suffix('.'))
+        .filter(|x| !x.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use porkbun::RecordId;
+
+    fn record(id: u64, name: &str, ty: RecordType, content: &str, notes: &str) -> DnsRecord {
+        DnsRecord {
+            id: RecordId(id),
+            name: name.to_string(),
+            ty,
+            content: content.to_string(),
+            ttl: "600".to_string(),
+            prio: "0".to_string(),
+            notes: Some(notes.to_string()),
+        }
+    }
+
+    #[test]
+    fn dnsmasq_leases_skip_duids_and_unnamed_hosts() {
+        let file = "\
+1700000000 aa:bb:cc:dd:ee:ff 192.168.1.20 laptop 01:aa:bb:cc:dd:ee:ff
+0 11:22:33:44:55:66 192.168.1.21 * *
+duid 00:01:00:01:2c:1f:aa:bb:cc:dd:ee:ff
+0 1234 fd00::20 printer *
+";
+        let leases = parse_dnsmasq(file).unwrap();
+        let parsed: Vec<(&str, String, Option<u64>)> = leases
+            .iter()
+            .map(|x| (x.hostname.as_str(), x.address.to_string(), x.expires))
+            .collect();
+        assert_eq!(
+            parsed,
+            [
+                ("laptop", "192.168.1.20".to_string(), Some(1700000000)),
+                ("printer", "fd00::20".to_string(), None),
+            ]
+        );
+        assert!(parse_dnsmasq("1700000000 aa:bb 192.168.1.20").is_err());
+    }
+
+    #[test]
+    fn later_kea_lines_replace_earlier_ones() {
+        let file = "\
+address,hwaddr,client_id,valid_lifetime,expire,subnet_id,fqdn_fwd,fqdn_rev,hostname,state
+192.168.1.20,aa:bb:cc:dd:ee:ff,,3600,1700000000,1,0,0,laptop.,0
+192.168.1.21,11:22:33:44:55:66,,3600,1700000000,1,0,0,phone,0
+192.168.1.20,aa:bb:cc:dd:ee:ff,,3600,1700003600,1,0,0,laptop.,0
+192.168.1.21,11:22:33:44:55:66,,3600,1700000000,1,0,0,phone,2
+";
+        let leases = parse_kea(file).unwrap();
+        assert_eq!(leases.len(), 1);
+        assert_eq!(leases[0].hostname, "laptop");
+        assert_eq!(leases[0].expires, Some(1700003600));
+        assert!(parse_kea("address,hostname\n").is_err());
+    }
+
+    #[test]
+    fn only_managed_records_without_a_lease_are_stale() {
+        let leases = [Lease {
+            hostname: "Laptop".to_string(),
+            address: "192.168.1.20".parse().unwrap(),
+            expires: None,
+        }];
+        let desired = desired(&leases, "lan.example.com");
+        let records = [
+            record(
+                1,
+                "laptop.lan.example.com",
+                RecordType::A,
+                "192.168.1.20",
+                "lease",
+            ),
+            record(
+                2,
+                "laptop.lan.example.com",
+                RecordType::A,
+                "192.168.1.9",
+                "lease",
+            ),
+            record(
+                3,
+                "phone.lan.example.com",
+                RecordType::A,
+                "192.168.1.21",
+                "lease",
+            ),
+            record(4, "nas.lan.example.com", RecordType::A, "192.168.1.5", ""),
+        ];
+        let existing: Vec<&DnsRecord> = records.iter().collect();
+
+        let stale = stale(&existing, &desired, |x| x.notes.as_deref() == Some("lease"));
+        let ids: Vec<RecordId> = stale.iter().map(|x| x.id).collect();
+        assert_eq!(ids, [RecordId(2), RecordId(3)]);
+    }
+
+    #[test]
+    fn the_suffix_must_be_below_the_apex() {
+        assert_eq!(
+            subdomain_prefix("lan.example.com", "example.com"),
+            Some("lan")
+        );
+        assert_eq!(subdomain_prefix("example.com", "example.com"), None);
+        assert_eq!(subdomain_prefix("lanexample.com", "example.com"), None);
+        assert_eq!(subdomain_prefix("lan.example.net", "example.com"), None);
+    }
+}
@@ -1,19 +1,129 @@
 use clap::Parser;
+use config::{Config, Target};
+use hold_down::HoldDown;
+use porkbun::ip::IpSource;
+use porkbun::resolve::Resolver;
+use porkbun::{AddressUpdate, RecordType};
+use results::{Action, RecordResult};
+use std::net::{IpAddr, Ipv6Addr};
 use std::path::PathBuf;
 use std::process::exit;
-use tracing::{error, info};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+mod config;
+mod daemon;
+mod hold_down;
+mod leases;
+mod metrics;
+#[cfg(all(feature = "netlink", target_os = "linux"))]
+mod netlink;
+mod results;
 
 #[derive(clap::Parser)]
 #[clap(author, version, about, long_about = None)]
 struct Cli {
     /// Path to the porkbun api key file.
     #[clap(short, long, value_parser, value_name = "PATH")]
-    key: PathBuf,
+    key: Option<PathBuf>,
+
+    /// Path to a config file listing the records to update.
+    #[clap(short, long, value_parser, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Keep running, updating records periodically.
+    ///
+    /// When used with --config, the config file is reloaded whenever it changes.
+    #[clap(short, long)]
+    daemon: bool,
+
+    /// How long to wait between updates in daemon mode.
+    #[clap(long, value_parser = humantime::parse_duration, value_name = "DURATION", default_value = "5m")]
+    interval: Duration,
 
     /// Silence successful log messages.
     #[clap(short, long)]
     silent: bool,
 
+    /// After updating a record, check that it resolves to the new address.
+    #[clap(long)]
+    resolve_check: bool,
+
+    /// How --resolve-check looks up records: system, cloudflare, google, quad9,
+    /// or the url of a DNS-over-HTTPS service.
+    ///
+    /// DNS-over-HTTPS works on networks which block outbound DNS.
+    #[clap(
+        long,
+        value_name = "RESOLVER",
+        default_value = "system",
+        requires = "resolve_check"
+    )]
+    resolver: Resolver,
+
+    /// Where to detect the public ip address: porkbun, or the url of a service which
+    /// responds with the address in plain text, such as https://icanhazip.com.
+    ///
+    /// May be given several times, in which case a --quorum of them must agree.
+    #[clap(long, value_name = "SOURCE", default_value = "porkbun")]
+    ip_source: Vec<IpSource>,
+
+    /// How many ip sources must report the same address before it is published.
+    ///
+    /// Defaults to a majority of them.
+    #[clap(long, value_name = "N")]
+    quorum: Option<usize>,
+
+    /// How many times in a row a new address must be detected before it is published.
+    #[clap(long, value_name = "N", default_value_t = 1)]
+    confirm_checks: u32,
+
+    /// How long to wait between --confirm-checks.
+    #[clap(long, value_parser = humantime::parse_duration, value_name = "DURATION", default_value = "30s")]
+    confirm_interval: Duration,
+
+    /// Share one rate-limit budget with other porkbun tools through the coordinator
+    /// listening on this socket, as started by `porkbun coordinate`.
+    #[clap(long, value_parser, value_name = "PATH", env = "PORKBUN_COORDINATOR")]
+    coordinator: Option<PathBuf>,
+
+    /// Wait a random time of up to DURATION before starting, so that many hosts run from the
+    /// same cron minute don't all call porkbun at once and hit its rate limits.
+    #[clap(long, value_parser = humantime::parse_duration, value_name = "DURATION")]
+    splay: Option<Duration>,
+
+    /// Change each record at most once per interval, such as when a broken router reports
+    /// alternating addresses.
+    ///
+    /// Outside daemon mode, this needs a --state-file to remember changes between runs.
+    #[clap(long, value_parser = humantime::parse_duration, value_name = "DURATION")]
+    min_update_interval: Option<Duration>,
+
+    /// Where to remember when each record was last changed, so that --min-update-interval
+    /// holds across separate runs, such as from cron or a systemd timer.
+    #[clap(
+        long,
+        value_parser,
+        value_name = "PATH",
+        requires = "min_update_interval"
+    )]
+    state_file: Option<PathBuf>,
+
+    /// Refuse to publish addresses which aren't reachable from the internet, such as those
+    /// handed out by carrier-grade NAT (100.64.0.0/10) or private networks (RFC 1918).
+    #[clap(long, value_name = "BOOL", default_value_t = true, action = clap::ArgAction::Set)]
+    reject_private: bool,
+
+    /// When updating both ipv4 and ipv6, only warn if one of them can't be detected, such as
+    /// when this host has no ipv6 route.
+    #[clap(long)]
+    best_effort: bool,
+
+    /// Publish every global ipv6 address of this host's interfaces as its own AAAA record,
+    /// rather than the one address the --ip-source reports.
+    #[clap(long)]
+    publish_all_v6: bool,
+
     /// Update ipv4 address.
     #[clap(short = '4', long)]
     ipv4: bool,
@@ -27,156 +137,654 @@ struct Cli {
     #[clap(short = 'w', long)]
     subdomain: Option<String>,
 
+    /// Path to a dnsmasq or Kea lease file.
+    ///
+    /// A and AAAA records are published for the hostname of every active lease,
+    /// and records for expired leases are deleted.
+    #[clap(long, value_parser, value_name = "PATH", requires_all = ["domain", "suffix"])]
+    leases: Option<PathBuf>,
+
+    /// Subdomain of the domain under which lease hostnames are published, such as
+    /// `lan.example.com`.
+    #[clap(long, value_name = "NAME", requires = "leases")]
+    suffix: Option<String>,
+
+    /// After updating, write metrics about the run to a file for node_exporter's textfile
+    /// collector, such as /var/lib/node_exporter/textfile/ddns.prom.
+    #[clap(long, value_parser, value_name = "PATH", conflicts_with = "daemon")]
+    metrics_textfile: Option<PathBuf>,
+
+    /// After updating, write a JSON summary of what happened to each record to a file.
+    #[clap(long, value_parser, value_name = "PATH", conflicts_with = "daemon")]
+    result_file: Option<PathBuf>,
+
+    /// Print the JSON schema of config files and exit, so editors can check them.
+    #[clap(long)]
+    print_config_schema: bool,
+
+    /// Check the --config file for mistakes and exit, with an error if there are any.
+    #[clap(long, requires = "config")]
+    validate_config: bool,
+
     /// Domain to update.
-    #[clap(value_parser, value_name = "PATH")]
-    domain: String,
+    #[clap(
+        value_parser,
+        value_name = "PATH",
+        required_unless_present_any = ["config", "print_config_schema"]
+    )]
+    domain: Option<String>,
 }
 
 fn main() {
-    let config = Cli::parse();
+    let cli = Cli::parse();
     tracing_subscriber::fmt::init();
 
-    let client = porkbun::Client::open_keys(&config.key).unwrap_or_else(|msg| {
-        error!("failed to open key file ({}): {msg}", config.key.display());
+    if cli.print_config_schema {
+        println!("{:#}", Config::json_schema());
+        return;
+    }
+
+    let config = match &cli.config {
+        Some(path) => Config::open(path).unwrap_or_else(|msg| {
+            error!("failed to open config file ({}): {msg}", path.display());
+            exit(1);
+        }),
+        None => Config {
+            key: None,
+            records: Vec::new(),
+            endpoints: Default::default(),
+        },
+    };
+
+    if cli.validate_config {
+        let problems = config.validate();
+        for problem in &problems {
+            error!("{problem}");
+        }
+        if !problems.is_empty() {
+            exit(1);
+        }
+        info!("no problems found");
+        return;
+    }
+
+    if cli.min_update_interval.is_some() && !cli.daemon && cli.state_file.is_none() {
+        error!("--min-update-interval needs --state-file to remember changes between runs");
+        exit(1);
+    }
+
+    let Some(key) = cli.key.as_ref().or(config.key.as_ref()) else {
+        error!("no key file was provided; use --key or set \"key\" in the config file");
+        exit(1);
+    };
+    let mut builder = porkbun::ClientBuilder::new();
+    if cli.daemon {
+        // Keep the connection open between updates, rather than reconnecting every time.
+        builder = builder
+            .pool_idle_timeout(Some(cli.interval + Duration::from_secs(60)))
+            .tcp_keepalive(Duration::from_secs(60));
+    }
+    let mut client = builder.open_keys(key).unwrap_or_else(|msg| {
+        error!("failed to open key file ({}): {msg}", key.display());
+        if let porkbun::Error::InsecureKeyFile { .. } = msg {
+            info!(
+                "to make it readable only by its owner, run: chmod 600 {}",
+                key.display()
+            );
+        } else {
+            explain(&msg);
+        }
         exit(1);
     });
+    #[cfg(unix)]
+    if let Some(path) = &cli.coordinator {
+        client = client.with_coordinator(path);
+    }
+    for (endpoint, behavior) in config.endpoints() {
+        client = client.with_endpoint_policy(&endpoint, behavior.policy());
+    }
 
-    let record_name = config
-        .subdomain
-        .as_deref()
-        .unwrap_or(config.domain.as_ref());
+    // Presence of the domain and suffix is enforced by clap.
+    let leases = cli.leases.map(|path| leases::Source {
+        path,
+        domain: cli.domain.clone().unwrap_or_default(),
+        suffix: cli.suffix.unwrap_or_default(),
+    });
 
-    let mut error_count = 0;
+    let mut targets = config.records;
+    if let Some(domain) = cli.domain {
+        targets.push(Target {
+            domain,
+            subdomain: cli.subdomain,
+            ipv4: cli.ipv4,
+            ipv6: cli.ipv6,
+        });
+    }
 
-    if config.ipv4 && !update_ipv4(&client, &config, record_name) {
-        error_count += 1;
+    let ip_source = match <[IpSource; 1]>::try_from(cli.ip_source) {
+        Ok([source]) if cli.quorum.is_none() => source,
+        Ok(sources) => IpSource::consensus(sources, cli.quorum.unwrap_or(1)),
+        Err(sources) => {
+            let quorum = cli.quorum.unwrap_or(sources.len() / 2 + 1);
+            IpSource::consensus(sources, quorum)
+        }
+    };
+    if let Some(splay) = cli.splay.filter(|x| !x.is_zero()) {
+        let delay = random_delay(splay);
+        if !cli.silent {
+            info!(
+                "waiting {} before starting",
+                humantime::format_duration(delay)
+            );
+        }
+        std::thread::sleep(delay);
     }
 
-    if config.ipv6 && !update_ipv6(&client, &config, record_name) {
-        error_count += 1;
+    let interval = cli.min_update_interval.unwrap_or_default();
+    let hold_down = match &cli.state_file {
+        Some(path) => HoldDown::open(interval, path).unwrap_or_else(|msg| {
+            error!("failed to read state file ({}): {msg}", path.display());
+            exit(1);
+        }),
+        None => HoldDown::new(interval),
+    };
+    let options = Options {
+        silent: cli.silent,
+        ip_source,
+        reject_private: cli.reject_private,
+        best_effort: cli.best_effort,
+        confirm_checks: cli.confirm_checks,
+        confirm_interval: cli.confirm_interval,
+        resolve_check: cli.resolve_check.then_some(cli.resolver),
+        publish_all_v6: cli.publish_all_v6,
+        hold_down,
+    };
+    if cli.daemon {
+        daemon::run(
+            &client,
+            cli.config.as_deref(),
+            targets,
+            leases.as_ref(),
+            cli.interval,
+            &options,
+        );
     }
 
+    let start = std::time::Instant::now();
+    let (error_count, results) = run_once(&client, &targets, leases.as_ref(), &options);
+    if let Some(path) = &cli.metrics_textfile {
+        if let Err(msg) = metrics::write(path, error_count, start.elapsed()) {
+            error!("failed to write metrics to {}: {msg}", path.display());
+        }
+    }
+    if let Some(path) = &cli.result_file {
+        if let Err(msg) = results::write(path, error_count, &results) {
+            error!("failed to write results to {}: {msg}", path.display());
+        }
+    }
     exit(error_count);
 }
 
-fn update_ipv6(client: &porkbun::Client, config: &Cli, record_name: &str) -> bool {
-    let ip_address = match client.ping_ipv6() {
-        Ok(Some(address)) => address,
-        Ok(None) => {
-            error!("ipv6 address is not present");
-            return false;
+/// Replaces the file at `path` with `text` in a single step, so that nothing reading it ever
+/// sees a partial file.
+fn write_atomically(path: &std::path::Path, text: &str) -> std::io::Result<()> {
+    // A rename within one directory replaces the file at once.
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    std::fs::write(&temporary, text)?;
+    std::fs::rename(&temporary, path)
+}
+
+/// A random duration of at most `max`, to the millisecond.
+fn random_delay(max: Duration) -> Duration {
+    use std::hash::BuildHasher;
+
+    // RandomState is seeded randomly for each process, which is all the randomness needed.
+    let random = std::collections::hash_map::RandomState::new().hash_one(std::process::id());
+    let millis = u64::try_from(max.as_millis()).unwrap_or(u64::MAX);
+    Duration::from_millis(random % millis.saturating_add(1))
+}
+
+/// Settings which apply to every update.
+pub struct Options {
+    /// Silence successful log messages.
+    pub silent: bool,
+    /// Where to detect the public ip address.
+    pub ip_source: IpSource,
+    /// Refuse to publish private and carrier-grade NAT addresses.
+    pub reject_private: bool,
+    /// Only fail if no address family can be detected.
+    pub best_effort: bool,
+    /// How many times in a row a new address must be detected before it is published.
+    pub confirm_checks: u32,
+    pub confirm_interval: Duration,
+    /// Checks that updated records resolve to their new address.
+    pub resolve_check: Option<Resolver>,
+    /// Publish every global ipv6 address of this host's interfaces.
+    pub publish_all_v6: bool,
+    pub hold_down: HoldDown,
+}
+
+/// Updates every target and lease, returning the number of failures and what happened to
+/// each target.
+fn run_once(
+    client: &porkbun::Client,
+    targets: &[Target],
+    leases: Option<&leases::Source>,
+    options: &Options,
+) -> (i32, Vec<RecordResult>) {
+    let (mut error_count, results) = update_all(client, targets, options);
+    if let Some(source) = leases {
+        let _span = tracing::info_span!("leases", path = %source.path.display()).entered();
+        error_count += leases::sync(client, source, options.silent);
+    }
+    (error_count, results)
+}
+
+/// Updates every target, returning the number of failed updates and what happened to each.
+fn update_all(
+    client: &porkbun::Client,
+    targets: &[Target],
+    options: &Options,
+) -> (i32, Vec<RecordResult>) {
+    // Every update is an independent round trip to the API, so run them side by side.
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = targets
+            .iter()
+            .map(|target| {
+                let spawn = |ipv6| {
+                    scope.spawn(move || {
+                        let _span = tracing::info_span!("record", name = %target).entered();
+                        update(client, target, options, ipv6)
+                    })
+                };
+                [
+                    target.ipv4.then(|| spawn(false)),
+                    target.ipv6.then(|| spawn(true)),
+                ]
+            })
+            .collect();
+        let mut error_count = 0;
+        let mut results = Vec::new();
+        for (target, handles) in targets.iter().zip(handles) {
+            let updates: Vec<_> = handles
+                .into_iter()
+                .flatten()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| RecordResult::new(target.name(), "", Action::Failed))
+                })
+                .collect();
+            // With --best-effort, one reachable family is enough.
+            let reachable = updates.iter().any(|x| x.action != Action::Unreachable);
+            if options.best_effort && !reachable {
+                error!("no address family is reachable");
+            }
+            let failed = |x: &&RecordResult| match x.action {
+                Action::Failed => true,
+                Action::Unreachable => !(options.best_effort && reachable),
+                _ => false,
+            };
+            error_count += updates.iter().filter(failed).count() as i32;
+            results.extend(updates);
+        }
+        (error_count, results)
+    })
+}
+
+/// Logs how to fix an error, walking through the dashboard for the most common one.
+fn explain(error: &porkbun::Error) {
+    if let porkbun::Error::ApiAccessDisabled { domain } = error {
+        info!("to enable API access for {domain}:");
+        info!("  1. sign in at https://porkbun.com/account/domainsSpeedy");
+        info!("  2. click \"Details\" next to {domain}");
+        info!("  3. switch on \"API ACCESS\"");
+    } else if let Some(hint) = error.explanation() {
+        info!("hint: {hint}");
+    }
+}
+
+/// Detects the public address of one family and publishes it.
+fn update(
+    client: &porkbun::Client,
+    target: &Target,
+    options: &Options,
+    ipv6: bool,
+) -> RecordResult {
+    let family = if ipv6 { "ipv6" } else { "ipv4" };
+    let unreachable = |msg: String| {
+        if options.best_effort {
+            warn!("{msg}");
+        } else {
+            error!("{msg}");
         }
+        RecordResult::new(target.name(), family, Action::Unreachable).with_error(msg)
+    };
+    if ipv6 && options.publish_all_v6 {
+        return match interface_ipv6_addresses(options.reject_private) {
+            Ok(addresses) if addresses.is_empty() => {
+                unreachable("no global ipv6 address is assigned to this host".to_string())
+            }
+            Ok(addresses) => publish_all(client, target, options, &addresses),
+            Err(msg) => unreachable(format!("failed to list network interfaces: {msg}")),
+        };
+    }
+    let address = match detect(client, options, ipv6) {
+        Ok(Some(address)) => address,
+        Ok(None) => return unreachable(format!("{family} address is not present")),
         Err(msg) => {
-            error!("failed to retreive public ipv6 address: {msg}");
-            return false;
+            return unreachable(format!("failed to retreive public {family} address: {msg}"))
         }
     };
+    if !confirm(client, target, options, ipv6, address) {
+        // Not a failure; the next run checks again.
+        return RecordResult {
+            new: vec![address.to_string()],
+            ..RecordResult::new(target.name(), family, Action::Deferred)
+        };
+    }
+    publish(client, target, options, family, address)
+}
+
+fn detect(
+    client: &porkbun::Client,
+    options: &Options,
+    ipv6: bool,
+) -> porkbun::Result<Option<IpAddr>> {
+    if ipv6 {
+        Ok(options.ip_source.ipv6(client)?.map(IpAddr::V6))
+    } else {
+        Ok(options.ip_source.ipv4(client)?.map(IpAddr::V4))
+    }
+}
 
-    match client
-        .fetch_ipv6_records(&config.domain, config.subdomain.as_deref())
-        .map(|records| {
-            records
+/// Checks that a new `address` is detected --confirm-checks times in a row before it is
+/// published, so that a transitional address reported while a router reconnects doesn't
+/// cause the record to flap.
+///
+/// Addresses which are already published don't need confirming.
+fn confirm(
+    client: &porkbun::Client,
+    target: &Target,
+    options: &Options,
+    ipv6: bool,
+    address: IpAddr,
+) -> bool {
+    if options.confirm_checks <= 1 {
+        return true;
+    }
+    let ty = if ipv6 {
+        RecordType::Aaaa
+    } else {
+        RecordType::A
+    };
+    let published =
+        client.fetch_records_by_name_type(&target.domain, ty, target.subdomain.as_deref());
+    if published.is_ok_and(|records| {
+        !records.is_empty()
+            && records
                 .iter()
-                .find(|x| x.name == record_name)
-                .map(|x| x.address == ip_address)
-        }) {
-        Ok(Some(true)) => {
-            if !config.silent {
-                info!("current ipv6 record matches public ip address");
-            }
-            true
-        }
-        Ok(Some(false)) => {
-            if let Err(msg) =
-                client.edit_ipv6_address(&config.domain, config.subdomain.as_deref(), &ip_address)
-            {
-                error!("failed to edit ipv6 address: {msg}");
+                .all(|x| x.content.parse::<IpAddr>() == Ok(address))
+    }) {
+        return true;
+    }
+
+    for check in 2..=options.confirm_checks {
+        std::thread::sleep(options.confirm_interval);
+        match detect(client, options, ipv6) {
+            Ok(Some(x)) if x == address => {}
+            Ok(x) => {
+                warn!(
+                    "address changed from {address} to {} during check {check} of {}; not updating yet",
+                    x.map_or("nothing".to_string(), |x| x.to_string()),
+                    options.confirm_checks
+                );
                 return false;
-            } else if !config.silent {
-                info!("successfully updated ipv6 record to {ip_address}");
             }
-            true
-        }
-        Ok(None) => {
-            if let Err(msg) = client.create_record(
-                &config.domain,
-                config.subdomain.as_deref(),
-                porkbun::RecordType::Aaaa,
-                &ip_address.to_string(),
-                None,
-                None,
-            ) {
-                error!("failed to create ipv6 record: {msg}");
+            Err(msg) => {
+                warn!("failed to confirm {address} during check {check}: {msg}; not updating yet");
                 return false;
-            } else if !config.silent {
-                info!("successfully created ipv6 record: {ip_address}");
             }
-            true
-        }
-        Err(msg) => {
-            error!("failed to retrieve previous ipv6 address: {msg}");
-            false
         }
     }
+    true
 }
 
-fn update_ipv4(client: &porkbun::Client, config: &Cli, record_name: &str) -> bool {
-    let ip_address = match client.ping_ipv4() {
-        Ok(Some(address)) => address,
-        Ok(None) => {
-            error!("ipv4 address is not present");
-            return false;
+fn publish(
+    client: &porkbun::Client,
+    target: &Target,
+    options: &Options,
+    family: &'static str,
+    ip_address: IpAddr,
+) -> RecordResult {
+    let result = |action| RecordResult {
+        new: vec![ip_address.to_string()],
+        ..RecordResult::new(target.name(), family, action)
+    };
+    if let Some(range) = options
+        .reject_private
+        .then(|| private_range(ip_address))
+        .flatten()
+    {
+        let msg = format!(
+            "refusing to publish {ip_address}, which is {range} and can't be reached from the internet; \
+             use --reject-private=false to publish it anyway"
+        );
+        error!("{msg}");
+        return result(Action::Failed).with_error(msg);
+    }
+
+    let silent = options.silent;
+    if let Some(remaining) = options.hold_down.remaining(&target.name(), family) {
+        let ty = if ip_address.is_ipv6() {
+            RecordType::Aaaa
+        } else {
+            RecordType::A
+        };
+        let published =
+            client.fetch_records_by_name_type(&target.domain, ty, target.subdomain.as_deref());
+        if published.is_ok_and(|records| {
+            !records.is_empty()
+                && records
+                    .iter()
+                    .all(|x| x.content.parse::<IpAddr>() == Ok(ip_address))
+        }) {
+            return result(Action::Unchanged);
+        }
+        warn!(
+            "{family} record was changed recently; not changing it to {ip_address} for another {}",
+            humantime::format_duration(Duration::from_secs(remaining.as_secs()))
+        );
+        return result(Action::Deferred);
+    }
+    let published = match client.ensure_address(
+        &target.domain,
+        target.subdomain.as_deref(),
+        ip_address,
+        None,
+    ) {
+        Ok(AddressUpdate::Unchanged) => {
+            if !silent {
+                info!("current {family} record matches public ip address");
+            }
+            result(Action::Unchanged)
+        }
+        Ok(AddressUpdate::Updated { previous }) => {
+            if !silent {
+                info!("successfully updated {family} record to {ip_address}");
+            }
+            options.hold_down.changed(target.name(), family);
+            RecordResult {
+                old: previous,
+                ..result(Action::Updated)
+            }
+        }
+        Ok(AddressUpdate::Created(_)) => {
+            if !silent {
+                info!("successfully created {family} record: {ip_address}");
+            }
+            options.hold_down.changed(target.name(), family);
+            result(Action::Created)
         }
         Err(msg) => {
-            error!("failed to retreive public ipv4 address: {msg}");
-            return false;
+            error!("failed to update {family} record: {msg}");
+            explain(&msg);
+            return result(Action::Failed).with_error(msg);
         }
     };
+    if let Some(resolver) = &options.resolve_check {
+        resolve_check(resolver, target, family, ip_address, silent);
+    }
+    published
+}
+
+/// The ipv6 addresses of this host's interfaces which can be reached from the internet,
+/// in order so that unchanged addresses always produce the same record set.
+fn interface_ipv6_addresses(reject_private: bool) -> std::io::Result<Vec<Ipv6Addr>> {
+    let mut addresses: Vec<Ipv6Addr> = if_addrs::get_if_addrs()?
+        .into_iter()
+        .filter_map(|x| match x.ip() {
+            IpAddr::V6(ip) => Some(ip),
+            IpAddr::V4(_) => None,
+        })
+        .filter(|ip| {
+            let link_local = ip.segments()[0] & 0xffc0 == 0xfe80;
+            !(link_local || ip.is_loopback() || ip.is_multicast() || ip.is_unspecified())
+        })
+        .filter(|ip| !reject_private || private_range(IpAddr::V6(*ip)).is_none())
+        .collect();
+    addresses.sort_unstable();
+    addresses.dedup();
+    Ok(addresses)
+}
 
-    match client
-        .fetch_ipv4_records(&config.domain, config.subdomain.as_deref())
-        .map(|records| {
-            records
+/// Makes the target's AAAA records hold exactly `addresses`, one record each.
+fn publish_all(
+    client: &porkbun::Client,
+    target: &Target,
+    options: &Options,
+    addresses: &[Ipv6Addr],
+) -> RecordResult {
+    let new: Vec<String> = addresses.iter().map(Ipv6Addr::to_string).collect();
+    let values: Vec<&str> = new.iter().map(String::as_str).collect();
+    let subdomain = target.subdomain.as_deref();
+    let result = |action| RecordResult {
+        new: new.clone(),
+        ..RecordResult::new(target.name(), "ipv6", action)
+    };
+    if let Some(remaining) = options.hold_down.remaining(&target.name(), "ipv6") {
+        let published =
+            client.fetch_records_by_name_type(&target.domain, RecordType::Aaaa, subdomain);
+        let mut published: Vec<Ipv6Addr> = match published {
+            Ok(records) => records
                 .iter()
-                .find(|x| x.name == record_name)
-                .map(|x| x.address == ip_address)
-        }) {
-        Ok(Some(true)) => {
-            if !config.silent {
-                info!("current ipv4 record matches public ip address");
+                .filter_map(|x| x.content.parse().ok())
+                .collect(),
+            Err(msg) => return result(Action::Failed).with_error(msg),
+        };
+        published.sort_unstable();
+        if published == addresses {
+            return result(Action::Unchanged);
+        }
+        warn!(
+            "ipv6 records were changed recently; not changing them for another {}",
+            humantime::format_duration(Duration::from_secs(remaining.as_secs()))
+        );
+        return result(Action::Deferred);
+    }
+    match client.set_records(&target.domain, subdomain, RecordType::Aaaa, &values) {
+        Ok(report) if report.changes.is_empty() => {
+            if !options.silent {
+                info!("current ipv6 records match every interface address");
             }
-            true
+            result(Action::Unchanged)
         }
-        Ok(Some(false)) => {
-            if let Err(msg) =
-                client.edit_ipv4_address(&config.domain, config.subdomain.as_deref(), &ip_address)
-            {
-                error!("failed to edit ipv4 address: {msg}");
-                return false;
-            } else if !config.silent {
-                info!("successfully updated ipv4 record to {ip_address}");
+        Ok(report) if report.is_success() => {
+            if !options.silent {
+                let n = report.changes.len();
+                info!("made {n} changes to publish {}", values.join(", "));
             }
-            true
-        }
-        Ok(None) => {
-            if let Err(msg) = client.create_record(
-                &config.domain,
-                config.subdomain.as_deref(),
-                porkbun::RecordType::Aaaa,
-                &ip_address.to_string(),
-                None,
-                None,
-            ) {
-                error!("failed to create ipv4 record: {msg}");
-                return false;
-            } else if !config.silent {
-                info!("successfully created ipv4 record: {ip_address}");
+            options.hold_down.changed(target.name(), "ipv6");
+            RecordResult {
+                old: report.snapshot.into_iter().map(|x| x.content).collect(),
+                ..result(Action::Updated)
+            }
+        }
+        Ok(report) => {
+            let mut failure = result(Action::Failed);
+            for (_, outcome) in &report.changes {
+                if let porkbun::ChangeOutcome::Failed(msg) = outcome {
+                    error!("failed to update ipv6 records, rolled back: {msg}");
+                    explain(msg);
+                    failure = failure.with_error(msg);
+                }
             }
-            true
+            failure
         }
         Err(msg) => {
-            error!("failed to retrieve previous ipv4 address: {msg}");
-            false
+            error!("failed to update ipv6 records: {msg}");
+            explain(&msg);
+            result(Action::Failed).with_error(msg)
+        }
+    }
+}
+
+/// Warns if a published record doesn't resolve to `ip_address` yet.
+///
+/// Resolvers may cache the previous address for up to the record's ttl, so this isn't an error.
+fn resolve_check(
+    resolver: &Resolver,
+    target: &Target,
+    family: &str,
+    ip_address: IpAddr,
+    silent: bool,
+) {
+    let name = match &target.subdomain {
+        Some(subdomain) => format!("{subdomain}.{}", target.domain),
+        None => target.domain.clone(),
+    };
+    match resolver.lookup_addresses(&name) {
+        Ok(addresses) if addresses.contains(&ip_address) => {
+            if !silent {
+                info!("{name} resolves to {ip_address}");
+            }
+        }
+        Ok(addresses) => {
+            let addresses: Vec<_> = addresses
+                .iter()
+                .filter(|x| x.is_ipv6() == ip_address.is_ipv6())
+                .collect();
+            warn!("{family} record of {name} does not resolve to {ip_address} yet (got {addresses:?})");
+        }
+        Err(msg) => warn!("failed to resolve {name}: {msg}"),
+    }
+}
+
+/// Describes the range `ip` belongs to if it isn't a public address.
+fn private_range(ip: IpAddr) -> Option<&'static str> {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            if a == 100 && (64..128).contains(&b) {
+                Some("a carrier-grade NAT address (RFC 6598); your ISP is likely sharing one public address between many customers")
+            } else if ip.is_private() {
+                Some("a private network address (RFC 1918); this machine is likely behind NAT")
+            } else if ip.is_loopback() || ip.is_link_local() || ip.is_unspecified() {
+                Some("a local address")
+            } else {
+                None
+            }
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            if first & 0xfe00 == 0xfc00 {
+                Some("a unique local address (RFC 4193)")
+            } else if first & 0xffc0 == 0xfe80 || ip.is_loopback() || ip.is_unspecified() {
+                Some("a local address")
+            } else {
+                None
+            }
         }
     }
 }
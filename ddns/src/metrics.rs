@@ -0,0 +1,67 @@
+//! Prometheus metrics about the last run, for node_exporter's textfile collector.
+
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const LAST_SUCCESS: &str = "porkbun_ddns_last_success_timestamp_seconds";
+
+/// Writes the outcome of a run to `path`, replacing it atomically so the collector never reads
+/// a partial file.
+///
+/// The time of the last successful run is carried over from the previous file when this run
+/// failed, so alerts can fire on how long updates have been failing.
+pub fn write(path: &Path, errors: i32, duration: Duration) -> std::io::Result<()> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    let last_success = if errors == 0 {
+        Some(now)
+    } else {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|x| previous_success(&x))
+    };
+
+    let mut text = String::new();
+    let mut metric = |name: &str, help: &str, value: f64| {
+        writeln!(text, "# HELP {name} {help}").ok();
+        writeln!(text, "# TYPE {name} gauge").ok();
+        writeln!(text, "{name} {value}").ok();
+    };
+    metric(
+        "porkbun_ddns_last_run_timestamp_seconds",
+        "When ddns last ran.",
+        now,
+    );
+    metric(
+        "porkbun_ddns_last_run_success",
+        "Whether every update of the last run succeeded.",
+        f64::from(u8::from(errors == 0)),
+    );
+    metric(
+        "porkbun_ddns_last_run_errors",
+        "How many updates failed in the last run.",
+        f64::from(errors),
+    );
+    metric(
+        "porkbun_ddns_last_run_duration_seconds",
+        "How long the last run took.",
+        duration.as_secs_f64(),
+    );
+    if let Some(last_success) = last_success {
+        metric(
+            LAST_SUCCESS,
+            "When every update of a run last succeeded.",
+            last_success,
+        );
+    }
+
+    crate::write_atomically(path, &text)
+}
+
+fn previous_success(text: &str) -> Option<f64> {
+    text.lines()
+        .find_map(|x| x.strip_prefix(LAST_SUCCESS)?.trim().parse().ok())
+}
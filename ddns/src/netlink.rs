@@ -0,0 +1,28 @@
+//! Address change notifications from the Linux kernel.
+
+use crate::daemon::Event;
+use netlink_sys::{protocols::NETLINK_ROUTE, Socket, SocketAddr};
+use std::sync::mpsc;
+use tracing::warn;
+
+/// The multicast groups announcing ipv4 and ipv6 address changes (see rtnetlink(7)).
+const RTMGRP_IPV4_IFADDR: u32 = 0x10;
+const RTMGRP_IPV6_IFADDR: u32 = 0x100;
+
+/// Sends an [Event::AddressChanged] whenever an address is added to or removed from an
+/// interface, until the receiver is dropped.
+pub fn watch(sender: mpsc::Sender<Event>) -> std::io::Result<()> {
+    let mut socket = Socket::new(NETLINK_ROUTE)?;
+    socket.bind(&SocketAddr::new(0, RTMGRP_IPV4_IFADDR | RTMGRP_IPV6_IFADDR))?;
+    std::thread::spawn(move || loop {
+        // Any message in these groups is an address change, so there's no need to parse it.
+        if let Err(msg) = socket.recv_from_full() {
+            warn!("stopped watching for address changes: {msg}");
+            break;
+        }
+        if sender.send(Event::AddressChanged).is_err() {
+            break;
+        }
+    });
+    Ok(())
+}
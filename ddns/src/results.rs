@@ -0,0 +1,69 @@
+//! The --result-file summary of a run, for tools which call ddns to act on without parsing
+//! its logs.
+
+use std::path::Path;
+
+/// What happened to one address family of one record.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct RecordResult {
+    /// The record's full name, such as `www.example.com`.
+    pub name: String,
+    pub family: &'static str,
+    pub action: Action,
+    /// The published addresses before the run, if they changed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub old: Vec<String>,
+    /// The addresses which were detected.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub new: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Unchanged,
+    Created,
+    Updated,
+    /// A new address is waiting on --confirm-checks, and wasn't published yet.
+    Deferred,
+    Failed,
+    /// No address of the family could be detected.
+    Unreachable,
+}
+
+impl RecordResult {
+    pub fn new(name: String, family: &'static str, action: Action) -> Self {
+        Self {
+            name,
+            family,
+            action,
+            old: Vec::new(),
+            new: Vec::new(),
+            error: None,
+        }
+    }
+
+    pub fn with_error(mut self, error: impl ToString) -> Self {
+        self.error = Some(error.to_string());
+        self
+    }
+}
+
+/// Writes the results of a run to `path` as JSON, replacing it atomically.
+pub fn write(path: &Path, errors: i32, records: &[RecordResult]) -> std::io::Result<()> {
+    #[derive(serde::Serialize)]
+    struct Summary<'a> {
+        success: bool,
+        errors: i32,
+        records: &'a [RecordResult],
+    }
+
+    let text = serde_json::to_string_pretty(&Summary {
+        success: errors == 0,
+        errors,
+        records,
+    })?;
+    crate::write_atomically(path, &(text + "\n"))
+}
@@ -0,0 +1,216 @@
+//! A small daemon embedding the porkbun crate, kept compiling alongside it as a check of the
+//! public API.
+//!
+//! It keeps the zones described by a directory of TOML specs in sync, counts API calls for
+//! metrics, and reports its state to systemd when run as a `Type=notify` service:
+//! ```text
+//! porkbun-example-daemon keys.json zones/ 300
+//! ```
+
+use porkbun::{
+    CallInfo, ChangeOutcome, ChangeSet, Client, DomainName, RecordSpec, SyncOptions, ZoneSpec,
+};
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Counts of API calls made by every clone of the client.
+#[derive(Debug, Default)]
+struct Metrics {
+    calls: AtomicU64,
+    retries: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl Metrics {
+    fn observe(&self, call: &CallInfo) {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.retries.fetch_add(
+            u64::from(call.attempt_count.saturating_sub(1)),
+            Ordering::Relaxed,
+        );
+        if call.http_status.is_none_or(|x| x >= 400) {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl std::fmt::Display for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} calls, {} retries, {} failures",
+            self.calls.load(Ordering::Relaxed),
+            self.retries.load(Ordering::Relaxed),
+            self.failures.load(Ordering::Relaxed)
+        )
+    }
+}
+
+fn main() {
+    tracing_subscriber::fmt::init();
+    let args: Vec<String> = std::env::args().collect();
+    let [_, keys, specs, interval] = args.as_slice() else {
+        eprintln!("usage: porkbun-example-daemon KEYS SPEC_DIR INTERVAL_SECONDS");
+        exit(2);
+    };
+    let interval = Duration::from_secs(interval.parse().unwrap_or(300));
+
+    let metrics = Arc::new(Metrics::default());
+    let observer = metrics.clone();
+    let client = Client::open_keys(keys)
+        .unwrap_or_else(|msg| {
+            error!("failed to open key file ({keys}): {msg}");
+            exit(1);
+        })
+        .with_call_hook(move |call| observer.observe(call));
+
+    notify("READY=1");
+    loop {
+        let failed = sync_all(&client, Path::new(specs));
+        info!("sync finished: {metrics}");
+        notify(&format!("STATUS={failed} zones failed to sync; {metrics}"));
+        notify("WATCHDOG=1");
+        std::thread::sleep(interval);
+    }
+}
+
+/// Syncs every spec in `dir` on its own thread, returning how many failed.
+fn sync_all(client: &Client, dir: &Path) -> usize {
+    let specs = match specs(dir) {
+        Ok(specs) => specs,
+        Err(msg) => {
+            error!("failed to read {}: {msg}", dir.display());
+            return 0;
+        }
+    };
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = specs
+            .into_iter()
+            .map(|path| {
+                // Clones share one connection pool and rate limit.
+                let client = client.clone();
+                scope.spawn(move || sync(&client, &path))
+            })
+            .collect();
+        let mut failed = 0;
+        for worker in workers {
+            match worker.join() {
+                Ok(Ok(())) => {}
+                Ok(Err(msg)) => {
+                    error!("{msg}");
+                    failed += 1;
+                }
+                Err(_) => failed += 1,
+            }
+        }
+        failed
+    })
+}
+
+fn specs(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut specs = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|x| x == "toml") {
+            specs.push(path);
+        }
+    }
+    Ok(specs)
+}
+
+/// A file of records which zone specs can include, as read by the CLI.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Snippet {
+    records: Vec<RecordSpec>,
+}
+
+/// Reads a zone spec the way the CLI does, with its included files added and its
+/// placeholders filled in from the environment.
+fn load(path: &Path) -> Result<ZoneSpec, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|msg| format!("failed to read {}: {msg}", path.display()))?;
+    let mut spec: ZoneSpec = toml::from_str(&text)
+        .map_err(|msg| format!("failed to parse {}: {msg}", path.display()))?;
+    // Blocks are only defined by files of several zones, which this daemon doesn't read.
+    if !spec.uses.is_empty() {
+        return Err(format!(
+            "{}: blocks can't be used by a single zone's spec",
+            path.display()
+        ));
+    }
+    let dir = path.parent().unwrap_or(Path::new("."));
+    spec.resolve_includes(|include| {
+        let path = dir.join(include);
+        let text = std::fs::read_to_string(&path)
+            .map_err(|msg| format!("failed to read {}: {msg}", path.display()))?;
+        let snippet: Snippet = toml::from_str(&text)
+            .map_err(|msg| format!("failed to parse {}: {msg}", path.display()))?;
+        Ok::<_, String>(snippet.records)
+    })?;
+    let domain = spec.domain.clone();
+    spec.interpolate(|name| match name {
+        "domain" => Some(domain.clone()),
+        name => std::env::var(name).ok(),
+    })
+    .map_err(|msg| format!("{}: {msg}", path.display()))?;
+    spec.domain = DomainName::new(spec.domain.trim_end_matches('.'))
+        .map_err(|msg| format!("{}: {msg}", spec.domain))?
+        .to_ascii()
+        .to_string();
+    Ok(spec)
+}
+
+fn sync(client: &Client, path: &Path) -> Result<(), String> {
+    let spec = load(path)?;
+    let existing = client
+        .fetch_records(&spec.domain)
+        .map_err(|msg| format!("failed to fetch records of {}: {msg}", spec.domain))?;
+    // Records missing from the spec are left alone, as by the CLI without `--prune`.
+    let changes = ChangeSet::diff_with(
+        &spec.domain,
+        &existing,
+        &spec.to_records(),
+        &SyncOptions::new(),
+    )
+    .map_err(|msg| format!("can't sync {}: {msg}", spec.domain))?;
+    if changes.is_empty() {
+        return Ok(());
+    }
+    info!("{}: applying {} changes", spec.domain, changes.len());
+    let report = client
+        .apply_transaction(&spec.domain, changes.into_changes())
+        .map_err(|msg| format!("failed to apply changes to {}: {msg}", spec.domain))?;
+    for (change, outcome) in &report.changes {
+        if let ChangeOutcome::Failed(msg) | ChangeOutcome::RevertFailed(msg) = outcome {
+            warn!("{}: {change:?}: {msg}", spec.domain);
+        }
+    }
+    if report.is_success() {
+        Ok(())
+    } else if report.is_consistent() {
+        Err(format!("{}: changes were rolled back", spec.domain))
+    } else {
+        Err(format!("{}: zone was left partially changed", spec.domain))
+    }
+}
+
+/// Sends a state update to systemd, if it started this process.
+#[cfg(unix)]
+fn notify(state: &str) {
+    let Some(socket) = std::env::var_os("NOTIFY_SOCKET") else {
+        return;
+    };
+    let result = std::os::unix::net::UnixDatagram::unbound()
+        .and_then(|x| x.send_to(state.as_bytes(), &socket));
+    if let Err(msg) = result {
+        warn!("failed to notify systemd: {msg}");
+    }
+}
+
+#[cfg(not(unix))]
+fn notify(_state: &str) {}
@@ -0,0 +1,55 @@
+use crate::{Client, Error, RecordType, Result};
+
+/// Whether a client's keys can manage a domain, as found by [Client::verify_access].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Access {
+    Granted,
+    /// Porkbun rejected the keys themselves, with this message.
+    BadCredentials {
+        message: String,
+    },
+    /// The keys work, but not for this domain: it belongs to another account, or API access
+    /// isn't enabled for it in the Porkbun dashboard.
+    NotAuthorized {
+        message: String,
+    },
+}
+
+impl Access {
+    pub fn is_granted(&self) -> bool {
+        *self == Access::Granted
+    }
+}
+
+/// Access checks
+impl Client {
+    /// Checks that this client's keys work, and then that they can read the records of
+    /// `domain`, such as before saving keys or starting a daemon.
+    ///
+    /// Errors which say nothing about the keys, such as network failures, are returned
+    /// as errors rather than as an [Access].
+    /// ```no_run
+    /// let client = porkbun::Client::open_keys("keys.json")?;
+    /// match client.verify_access("example.com")? {
+    ///     porkbun::Access::Granted => println!("ready"),
+    ///     access => println!("{access:?}"),
+    /// }
+    /// # Ok::<(), porkbun::Error>(())
+    /// ```
+    pub fn verify_access(&self, domain: &str) -> Result<Access> {
+        match self.ping() {
+            Ok(_) => {}
+            Err(Error::Api { message }) => return Ok(Access::BadCredentials { message }),
+            Err(error) => return Err(error),
+        }
+        // Every zone has NS records at its apex, and there are only a few of them.
+        match self.fetch_records_by_name_type(domain, RecordType::Ns, None) {
+            Ok(_) => Ok(Access::Granted),
+            Err(error @ Error::ApiAccessDisabled { .. }) => Ok(Access::NotAuthorized {
+                message: error.to_string(),
+            }),
+            Err(Error::Api { message }) => Ok(Access::NotAuthorized { message }),
+            Err(error) => Err(error),
+        }
+    }
+}
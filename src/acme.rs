@@ -0,0 +1,149 @@
+use crate::resolve::{PropagationReport, Resolver};
+use crate::{subdomain_of, Client, Error, RecordId, RecordType, Result};
+use std::time::Duration;
+
+/// The name of the TXT record an ACME DNS-01 challenge for `identifier` is answered at,
+/// such as `_acme-challenge.www.example.com` for `www.example.com`.
+///
+/// Wildcard identifiers share the challenge of the name they cover.
+/// ```
+/// assert_eq!(porkbun::challenge_name("*.example.com"), "_acme-challenge.example.com");
+/// ```
+pub fn challenge_name(identifier: &str) -> String {
+    let identifier = identifier.trim_end_matches('.');
+    let identifier = identifier.strip_prefix("*.").unwrap_or(identifier);
+    format!("_acme-challenge.{identifier}")
+}
+
+/// ACME challenges
+impl Client {
+    /// Finds the domain in this account which `name` is, or is beneath, preferring the
+    /// longest if several match.
+    ///
+    /// Fails with [Error::NoZone] if there is none.
+    pub fn find_zone(&self, name: &str) -> Result<String> {
+        let name = name.trim_end_matches('.');
+        self.list_domains()?
+            .into_iter()
+            .map(|x| x.domain)
+            .filter(|x| name.eq_ignore_ascii_case(x) || name.ends_with(&format!(".{x}")))
+            .max_by_key(String::len)
+            .ok_or_else(|| Error::NoZone {
+                name: name.to_string(),
+            })
+    }
+
+    /// Answers a DNS-01 challenge for `identifier` within `domain` by creating a TXT record
+    /// holding `validation`.
+    pub fn create_acme_challenge(
+        &self,
+        domain: &str,
+        identifier: &str,
+        validation: &str,
+    ) -> Result<Option<RecordId>> {
+        let name = challenge_name(identifier);
+        let subdomain = subdomain_of(&name, domain);
+        self.create_record(domain, subdomain, RecordType::Txt, validation, None, None)
+    }
+
+    /// Deletes the challenge records of `identifier` holding `validation`, returning how many
+    /// there were.
+    ///
+    /// Other challenges for the same name, such as for a wildcard certificate covering it,
+    /// are left alone.
+    pub fn delete_acme_challenge(
+        &self,
+        domain: &str,
+        identifier: &str,
+        validation: &str,
+    ) -> Result<usize> {
+        let name = challenge_name(identifier);
+        let subdomain = subdomain_of(&name, domain);
+        let records = self.fetch_records_by_name_type(domain, RecordType::Txt, subdomain)?;
+        let mut deleted = 0;
+        for record in records
+            .iter()
+            .filter(|x| x.content.trim_matches('"') == validation)
+        {
+            self.delete_record(domain, record.id)?;
+            deleted += 1;
+        }
+        Ok(deleted)
+    }
+}
+
+/// The answer to a DNS-01 challenge, which is deleted when this is dropped.
+///
+/// This works with any ACME client. With instant-acme, for example, the value is
+/// `order.key_authorization(challenge).dns_value()`; once [Dns01Challenge::wait] returns,
+/// mark the challenge ready, and drop this after the order becomes valid or invalid.
+#[derive(Debug)]
+pub struct Dns01Challenge<'a> {
+    client: &'a Client,
+    domain: String,
+    identifier: String,
+    value: String,
+    cleaned_up: bool,
+}
+
+impl Dns01Challenge<'_> {
+    /// The fully qualified name of the challenge record.
+    pub fn name(&self) -> String {
+        challenge_name(&self.identifier)
+    }
+
+    /// Waits for `resolvers` to return the answer, on the client's clock.
+    /// See [PropagationReport::wait].
+    pub fn wait(&self, resolvers: &[Resolver], timeout: Duration) -> PropagationReport {
+        PropagationReport::wait_with_clock(
+            self.client.clock.as_ref(),
+            resolvers,
+            &self.name(),
+            RecordType::Txt,
+            &self.value,
+            timeout,
+            |_| {},
+        )
+    }
+
+    /// Deletes the challenge record, reporting any error rather than ignoring it as
+    /// dropping does.
+    pub fn cleanup(mut self) -> Result<()> {
+        self.cleaned_up = true;
+        self.client
+            .delete_acme_challenge(&self.domain, &self.identifier, &self.value)
+            .map(|_| ())
+    }
+}
+
+impl Drop for Dns01Challenge<'_> {
+    fn drop(&mut self) {
+        if self.cleaned_up {
+            return;
+        }
+        let _result =
+            self.client
+                .delete_acme_challenge(&self.domain, &self.identifier, &self.value);
+        #[cfg(feature = "tracing")]
+        if let Err(msg) = _result {
+            tracing::warn!("failed to delete challenge of {}: {msg}", self.identifier);
+        }
+    }
+}
+
+/// DNS-01 challenges
+impl Client {
+    /// Answers a DNS-01 challenge for `identifier` in whichever of this account's domains
+    /// contains it.
+    pub fn present_dns01(&self, identifier: &str, value: &str) -> Result<Dns01Challenge<'_>> {
+        let domain = self.find_zone(identifier.strip_prefix("*.").unwrap_or(identifier))?;
+        self.create_acme_challenge(&domain, identifier, value)?;
+        Ok(Dns01Challenge {
+            client: self,
+            domain,
+            identifier: identifier.to_string(),
+            value: value.to_string(),
+            cleaned_up: false,
+        })
+    }
+}
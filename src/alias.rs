@@ -0,0 +1,88 @@
+use crate::{Change, Client, Error, NewRecord, RecordType, Result, TransactionReport};
+
+/// Checks that `target` could be the target of an ALIAS record at the apex of `domain`,
+/// returning it without a trailing dot.
+fn validate_target<'a>(domain: &str, target: &'a str) -> Result<&'a str> {
+    let invalid = |reason| {
+        Err(Error::InvalidAliasTarget {
+            target: target.to_string(),
+            reason,
+        })
+    };
+    let hostname = target.strip_suffix('.').unwrap_or(target);
+    if hostname.is_empty() {
+        return invalid("the target is empty");
+    }
+    if hostname.contains("://") || hostname.contains('/') {
+        return invalid("the target must be a hostname, not a url");
+    }
+    if hostname.parse::<std::net::IpAddr>().is_ok() {
+        return invalid("the target must be a hostname; use an A or AAAA record for addresses");
+    }
+    if hostname.chars().any(|x| x.is_whitespace()) || !hostname.contains('.') {
+        return invalid("the target must be a fully-qualified hostname");
+    }
+    if hostname.eq_ignore_ascii_case(domain) {
+        return invalid("the target is the apex itself");
+    }
+    Ok(hostname)
+}
+
+/// Apex records
+impl Client {
+    /// Points the apex of `domain` at `target` with an ALIAS record.
+    ///
+    /// A CNAME can't be used at the apex, so porkbun's ALIAS record is flattened into
+    /// addresses when queried instead.
+    /// Any A, AAAA, or CNAME records at the apex conflict with it and are deleted, as are
+    /// extra ALIAS records. Every change is applied as a [transaction](Client::apply_transaction);
+    /// the report is empty if the apex already aliased `target` alone.
+    pub fn set_apex_alias(&self, domain: &str, target: &str) -> Result<TransactionReport> {
+        let target = validate_target(domain, target)?;
+        let apex: Vec<_> = self
+            .fetch_records(domain)?
+            .into_iter()
+            .filter(|x| x.name.eq_ignore_ascii_case(domain))
+            .collect();
+
+        let mut changes = Vec::new();
+        let mut alias = None;
+        for record in &apex {
+            match record.ty {
+                RecordType::Alias if alias.is_none() => alias = Some(record),
+                RecordType::A | RecordType::Aaaa | RecordType::Cname | RecordType::Alias => {
+                    changes.push(Change::Delete { id: record.id })
+                }
+                _ => {}
+            }
+        }
+
+        let record = NewRecord {
+            name: None,
+            ty: RecordType::Alias,
+            content: target.to_string(),
+            ttl: None,
+            prio: None,
+        };
+        match alias {
+            Some(alias) if alias.content.trim_end_matches('.') == target => {}
+            Some(alias) => changes.push(Change::Edit {
+                id: alias.id,
+                record: NewRecord {
+                    ttl: alias.ttl.parse().ok(),
+                    ..record
+                },
+            }),
+            None => changes.push(Change::Create(record)),
+        }
+
+        #[cfg(feature = "tracing")]
+        if !changes.is_empty() {
+            tracing::info!(
+                "aliasing {domain} to {target} with {} changes",
+                changes.len()
+            );
+        }
+        self.apply_transaction(domain, changes)
+    }
+}
@@ -0,0 +1,226 @@
+use crate::{ChangeSet, DnsRecord, NewRecord, RecordEdit};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// The kind of change made by a mutating operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    Create,
+    Edit,
+    Delete,
+}
+
+/// A record of a single change made through a [Client](crate::Client).
+///
+/// `before` and `after` hold the affected records as they were fetched immediately before
+/// and after the change.
+/// Either may be empty, such as `before` for a creation, or if fetching the records failed.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: SystemTime,
+    /// Who made the change, as set with [Client::with_actor](crate::Client::with_actor).
+    #[serde(default)]
+    pub actor: Option<String>,
+    pub domain: String,
+    pub operation: Operation,
+    pub before: Vec<DnsRecord>,
+    pub after: Vec<DnsRecord>,
+    /// The SHA-256 of the line before this one in a [JsonlSink]'s file, in hex, so that
+    /// editing or removing an entry breaks the chain. `None` for the first entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+}
+
+impl AuditEntry {
+    /// Plans reversing the change: records missing from `after` are re-created, those missing
+    /// from `before` are deleted, and the rest are edited back.
+    ///
+    /// Re-created records get new ids, so entries older than a deletion which touch the same
+    /// records can no longer be undone.
+    pub fn undo(&self) -> ChangeSet {
+        let mut changes = ChangeSet {
+            domain: self.domain.clone(),
+            ..ChangeSet::default()
+        };
+        for before in &self.before {
+            let restored = NewRecord::from_existing(&self.domain, before);
+            match self.after.iter().find(|x| x.id == before.id) {
+                Some(after) if NewRecord::from_existing(&self.domain, after) == restored => {}
+                Some(after) => changes.edit.push(RecordEdit {
+                    before: after.clone(),
+                    after: restored,
+                }),
+                None => changes.create.push(restored),
+            }
+        }
+        changes.delete = self
+            .after
+            .iter()
+            .filter(|x| !self.before.iter().any(|y| y.id == x.id))
+            .cloned()
+            .collect();
+        changes
+    }
+}
+
+/// A destination for [AuditEntry]s.
+///
+/// Register one with [Client::with_audit](crate::Client::with_audit).
+pub trait AuditSink: std::fmt::Debug + Send + Sync {
+    fn record(&self, entry: &AuditEntry) -> std::io::Result<()>;
+}
+
+impl<T: AuditSink + ?Sized> AuditSink for Arc<T> {
+    fn record(&self, entry: &AuditEntry) -> std::io::Result<()> {
+        (**self).record(entry)
+    }
+}
+
+/// Keeps every entry in memory.
+///
+/// Wrap this in an [Arc] to read the entries back after passing it to a client.
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    entries: Mutex<Vec<AuditEntry>>,
+}
+
+impl MemorySink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl AuditSink for MemorySink {
+    fn record(&self, entry: &AuditEntry) -> std::io::Result<()> {
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(entry.clone());
+        Ok(())
+    }
+}
+
+/// Appends each entry to a file as a line of JSON, chained to the line before it by
+/// [AuditEntry::prev_hash] so that [JsonlSink::verify] can detect tampering.
+#[derive(Debug)]
+pub struct JsonlSink {
+    file: Mutex<File>,
+}
+
+impl JsonlSink {
+    /// Opens `path` for appending, creating it if it doesn't exist.
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl JsonlSink {
+    /// Reads back every entry in a file written by a [JsonlSink], oldest first.
+    pub fn read(path: impl AsRef<Path>) -> std::io::Result<Vec<AuditEntry>> {
+        std::fs::read_to_string(path)?
+            .lines()
+            .filter(|x| !x.trim().is_empty())
+            .map(|x| serde_json::from_str(x).map_err(std::io::Error::from))
+            .collect()
+    }
+
+    /// Checks the hash chain of a file written by a [JsonlSink], returning the index of the
+    /// first entry which doesn't follow from the line before it, if any.
+    ///
+    /// Logs written before entries were chained fail at their second entry.
+    pub fn verify(path: impl AsRef<Path>) -> std::io::Result<Option<usize>> {
+        let text = std::fs::read_to_string(path)?;
+        let mut previous: Option<&str> = None;
+        for (i, line) in text.lines().filter(|x| !x.trim().is_empty()).enumerate() {
+            let entry: AuditEntry = serde_json::from_str(line)?;
+            if entry.prev_hash != previous.map(|x| hash(x.as_bytes())) {
+                return Ok(Some(i));
+            }
+            previous = Some(line);
+        }
+        Ok(None)
+    }
+}
+
+impl AuditSink for JsonlSink {
+    fn record(&self, entry: &AuditEntry) -> std::io::Result<()> {
+        let file = self
+            .file
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        // Other processes appending to the log take the same lock, so the last line is still
+        // the last line when this one is written after it.
+        file.lock()?;
+        let result = append(&file, entry);
+        file.unlock()?;
+        result
+    }
+}
+
+fn append(mut file: &File, entry: &AuditEntry) -> std::io::Result<()> {
+    let previous = last_line(file)?;
+    let entry = AuditEntry {
+        prev_hash: (!previous.is_empty()).then(|| hash(&previous)),
+        ..entry.clone()
+    };
+    let mut line = serde_json::to_string(&entry)?;
+    line.push('\n');
+    // One write call, so a line can't be split by another writer which doesn't take the lock.
+    let written = file.write(line.as_bytes())?;
+    if written < line.len() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::WriteZero,
+            "audit entry was only partly written",
+        ));
+    }
+    file.flush()
+}
+
+/// The last non-empty line of `file`, without its newline, read backwards from the end.
+fn last_line(mut file: &File) -> std::io::Result<Vec<u8>> {
+    let mut position = file.seek(SeekFrom::End(0))?;
+    let mut tail = Vec::new();
+    let mut chunk = [0; 4096];
+    while position > 0 {
+        let size = chunk
+            .len()
+            .min(usize::try_from(position).unwrap_or(usize::MAX));
+        position -= size as u64;
+        file.seek(SeekFrom::Start(position))?;
+        file.read_exact(&mut chunk[..size])?;
+        tail.splice(0..0, chunk[..size].iter().copied());
+        let end = tail.iter().rposition(|x| !x.is_ascii_whitespace());
+        let start = end.and_then(|end| tail[..end].iter().rposition(|x| *x == b'\n'));
+        if let (Some(start), Some(end)) = (start, end) {
+            return Ok(tail[start + 1..=end].to_vec());
+        }
+    }
+    let end = tail.iter().rposition(|x| !x.is_ascii_whitespace());
+    Ok(end.map_or_else(Vec::new, |end| tail[..=end].to_vec()))
+}
+
+fn hash(line: &[u8]) -> String {
+    Sha256::digest(line)
+        .iter()
+        .map(|x| format!("{x:02x}"))
+        .collect()
+}
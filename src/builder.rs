@@ -0,0 +1,197 @@
+use crate::clock::SystemClock;
+use crate::endpoints::{API_URL, IPV4_API_URL};
+use crate::{retry, Client, Error, ParseMode, Result, TtlPolicy};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Configures the connection used by a [Client].
+///
+/// The defaults match [Client::open_keys].
+/// Long-running programs which make infrequent requests may want to keep idle connections
+/// open for longer, so each request can skip reconnecting and the TLS handshake.
+#[derive(Clone, Debug, Default)]
+pub struct ClientBuilder {
+    pool_idle_timeout: Option<Option<Duration>>,
+    pool_max_idle_per_host: Option<usize>,
+    tcp_keepalive: Option<Duration>,
+    http2: bool,
+    timeout: Option<Duration>,
+    #[cfg(feature = "gzip")]
+    gzip: bool,
+    api_url: Option<String>,
+    ipv4_api_url: Option<String>,
+    allow_insecure_key_file: bool,
+}
+
+impl ClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How long an unused connection is kept open, or `None` to keep it open indefinitely.
+    ///
+    /// Defaults to 90 seconds.
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// The maximum number of unused connections kept open.
+    pub fn pool_max_idle(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Sends TCP keepalive probes at this interval, so idle connections aren't dropped by
+    /// routers and firewalls along the way.
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Allows HTTP/2 to be negotiated with porkbun.
+    ///
+    /// Defaults to false, which only uses HTTP/1.1.
+    pub fn http2(mut self, enable: bool) -> Self {
+        self.http2 = enable;
+        self
+    }
+
+    /// How long a request may take, from connecting until the response is read, before it fails.
+    ///
+    /// Defaults to 30 seconds. Individual calls can override this with [Client::with_timeout].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Asks porkbun to compress responses with gzip.
+    ///
+    /// Defaults to false. This is mostly useful when fetching large zones.
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self, enable: bool) -> Self {
+        self.gzip = enable;
+        self
+    }
+
+    /// Sends every request to `url` instead of porkbun, such as a mock server during tests.
+    ///
+    /// This is the root of the API, like `https://api.porkbun.com/api/json/v3`.
+    /// It replaces the ipv4-only url as well, unless one is set with [Self::ipv4_base_url].
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.api_url = Some(url.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Replaces the url used by [Client::ping_ipv4], `https://api-ipv4.porkbun.com/api/json/v3`.
+    pub fn ipv4_base_url(mut self, url: impl Into<String>) -> Self {
+        self.ipv4_api_url = Some(url.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Opens key files which other users can read or write.
+    ///
+    /// By default, on Unix, [Self::open_keys] fails with [Error::InsecureKeyFile] for these,
+    /// the way ssh refuses to use private keys which aren't private.
+    pub fn allow_insecure_key_file(mut self, allow: bool) -> Self {
+        self.allow_insecure_key_file = allow;
+        self
+    }
+
+    /// Builds a client using an api key and secret api key directly.
+    pub fn build(self, api_key: &str, secret_api_key: &str) -> Result<Client> {
+        let key_file = serde_json::json!({
+            "apikey": api_key,
+            "secretapikey": secret_api_key,
+        })
+        .to_string();
+        self.finish(key_file, api_key.to_string(), secret_api_key.to_string())
+    }
+
+    /// Builds a client using a porkbun api key file.
+    ///
+    /// See [Client::open_keys] for the format of this file.
+    pub fn open_keys(self, path: impl AsRef<std::path::Path>) -> Result<Client> {
+        #[cfg(unix)]
+        if !self.allow_insecure_key_file {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path)?.permissions().mode() & 0o777;
+            if mode & 0o077 != 0 {
+                return Err(Error::InsecureKeyFile { mode });
+            }
+        }
+        let key_file = std::fs::read_to_string(path)?;
+        self.parse_keys(key_file)
+    }
+
+    /// Builds a client from the contents of a key file.
+    pub(crate) fn parse_keys(self, key_file: String) -> Result<Client> {
+        #[derive(serde::Deserialize)]
+        struct Keys {
+            #[serde(rename = "apikey")]
+            api: String,
+            #[serde(rename = "secretapikey")]
+            secret_api: String,
+        }
+
+        let keys: Keys = serde_json::from_str(&key_file)?;
+        self.finish(key_file, keys.api, keys.secret_api)
+    }
+
+    fn finish(self, key_file: String, api_key: String, secret_api_key: String) -> Result<Client> {
+        let api_url = self.api_url.clone().unwrap_or_else(|| API_URL.to_string());
+        let ipv4_api_url = self
+            .ipv4_api_url
+            .clone()
+            .or_else(|| self.api_url.clone())
+            .unwrap_or_else(|| IPV4_API_URL.to_string());
+        let timeout = self.timeout;
+        Ok(Client {
+            client: self.build_http()?,
+            api_key: api_key.into(),
+            secret_api_key: secret_api_key.into(),
+            key_file: key_file.into(),
+            audit: None,
+            backoff: retry::Backoff::default(),
+            call_hook: None,
+            ttl_policy: TtlPolicy::default(),
+            parse_mode: ParseMode::default(),
+            owner: None,
+            actor: None,
+            endpoint_policies: Default::default(),
+            pings: Default::default(),
+            paused_until: Default::default(),
+            timeout,
+            deadline: None,
+            clock: Arc::new(SystemClock),
+            #[cfg(unix)]
+            coordinator: None,
+            api_url: api_url.into(),
+            ipv4_api_url: ipv4_api_url.into(),
+        })
+    }
+
+    fn build_http(self) -> reqwest::Result<reqwest::blocking::Client> {
+        let mut builder = reqwest::blocking::Client::builder();
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if !self.http2 {
+            builder = builder.http1_only();
+        }
+        #[cfg(feature = "gzip")]
+        {
+            builder = builder.gzip(self.gzip);
+        }
+        builder.build()
+    }
+}
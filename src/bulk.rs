@@ -0,0 +1,143 @@
+use crate::{
+    replace_whole, subdomain_of, ChangeSet, Client, DnsRecord, NewRecord, RecordEdit, RecordType,
+    Result, TransactionReport, Ttl,
+};
+
+impl ChangeSet {
+    /// Plans replacing `from` with `to` in the content of every one of the `records` of
+    /// `domain`, such as to move records from one server's address to another's.
+    ///
+    /// Only whole fields and labels are replaced, so replacing `203.0.113.10` leaves
+    /// `203.0.113.100` alone.
+    pub fn replace(domain: &str, records: &[DnsRecord], from: &str, to: &str) -> Self {
+        let edit = records
+            .iter()
+            .filter_map(|before| {
+                let content = replace_whole(&before.content, from, to);
+                (content != before.content).then(|| RecordEdit {
+                    after: NewRecord {
+                        content,
+                        ..NewRecord::from_existing(domain, before)
+                    },
+                    before: before.clone(),
+                })
+            })
+            .collect();
+        Self {
+            domain: domain.to_string(),
+            edit,
+            ..Self::default()
+        }
+    }
+}
+
+/// Bulk edits
+impl Client {
+    /// Plans giving every record of `domain` which matches `filter` a ttl of `ttl`, such as to
+    /// lower ttls before a migration and raise them after.
+    ///
+    /// Records which already have this ttl are left out.
+    /// ```no_run
+    /// # let client = porkbun::Client::open_keys("keys.json")?;
+    /// use porkbun::{RecordType, Ttl};
+    ///
+    /// let plan = client.plan_zone_ttl("example.com", Ttl::MIN, |x| x.ty == RecordType::A)?;
+    /// print!("{}", porkbun::PlanRenderer::new().render(&plan));
+    /// # Ok::<(), porkbun::Error>(())
+    /// ```
+    pub fn plan_zone_ttl(
+        &self,
+        domain: &str,
+        ttl: Ttl,
+        filter: impl Fn(&DnsRecord) -> bool,
+    ) -> Result<ChangeSet> {
+        let ttl = self.ttl_policy.apply(Some(ttl))?;
+        let edit = self
+            .fetch_records(domain)?
+            .into_iter()
+            .filter(|x| filter(x) && x.ttl.parse().ok() != ttl)
+            .map(|before| RecordEdit {
+                after: NewRecord {
+                    ttl,
+                    ..NewRecord::from_existing(domain, &before)
+                },
+                before,
+            })
+            .collect();
+        Ok(ChangeSet {
+            domain: domain.to_string(),
+            edit,
+            ..ChangeSet::default()
+        })
+    }
+
+    /// Applies [Client::plan_zone_ttl] as a [transaction](Client::apply_transaction).
+    pub fn set_zone_ttl(
+        &self,
+        domain: &str,
+        ttl: Ttl,
+        filter: impl Fn(&DnsRecord) -> bool,
+    ) -> Result<TransactionReport> {
+        let changes = self.plan_zone_ttl(domain, ttl, filter)?;
+        self.apply_transaction(domain, changes.into_changes())
+    }
+
+    /// Plans copying the records of `domain` at the subdomain `from` to the subdomain `to`,
+    /// where `None` is the domain itself, such as to stand up `staging` beside `www`.
+    ///
+    /// Only records of the given `types` are copied, or every type if none are given.
+    /// Records which `to` already has are left out.
+    /// ```no_run
+    /// # let client = porkbun::Client::open_keys("keys.json")?;
+    /// use porkbun::RecordType;
+    ///
+    /// let types = [RecordType::A, RecordType::Aaaa];
+    /// let plan = client.plan_copy_records("example.com", Some("www"), Some("staging"), &types)?;
+    /// print!("{}", porkbun::PlanRenderer::new().render(&plan));
+    /// # Ok::<(), porkbun::Error>(())
+    /// ```
+    pub fn plan_copy_records(
+        &self,
+        domain: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+        types: &[RecordType],
+    ) -> Result<ChangeSet> {
+        let records = self.fetch_records(domain)?;
+        let at = |record: &&DnsRecord, name: Option<&str>| {
+            subdomain_of(&record.name, domain).map(str::to_ascii_lowercase)
+                == name.map(str::to_ascii_lowercase)
+        };
+        let existing: Vec<&DnsRecord> = records.iter().filter(|x| at(x, to)).collect();
+        let create = records
+            .iter()
+            .filter(|x| at(x, from) && (types.is_empty() || types.contains(&x.ty)))
+            .filter(|x| {
+                !existing
+                    .iter()
+                    .any(|y| y.ty == x.ty && y.content == x.content)
+            })
+            .map(|record| NewRecord {
+                name: to.map(str::to_string),
+                ..NewRecord::from_existing(domain, record)
+            })
+            .collect();
+        Ok(ChangeSet {
+            domain: domain.to_string(),
+            create,
+            ..ChangeSet::default()
+        })
+    }
+
+    /// Applies [Client::plan_copy_records] as a [transaction](Client::apply_transaction).
+    pub fn copy_records(
+        &self,
+        domain: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+        types: &[RecordType],
+    ) -> Result<TransactionReport> {
+        let changes = self.plan_copy_records(domain, from, to, types)?;
+        self.apply_transaction(domain, changes.into_changes())
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{subdomain_of, ChangeSet, DnsRecord, NewRecord, RecordType};
+
+/// Which certificate authorities a domain's CAA records allow to issue certificates for it.
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct CaaPosture {
+    pub domain: String,
+    /// Authorities named by `issue` records. An empty name means none may issue, while no
+    /// `issue` records at all means any may.
+    pub issuers: Vec<String>,
+    /// Authorities named by `issuewild` records, which override `issuers` for wildcards.
+    pub wildcard_issuers: Vec<String>,
+    /// Where authorities report requests they refused.
+    pub iodef: Vec<String>,
+    /// The CAA records at the domain itself. Those of subdomains aren't considered.
+    pub records: Vec<DnsRecord>,
+}
+
+impl CaaPosture {
+    pub fn from_records(domain: &str, records: &[DnsRecord]) -> Self {
+        let mut posture = Self {
+            domain: domain.to_string(),
+            ..Self::default()
+        };
+        for record in records
+            .iter()
+            .filter(|x| x.ty == RecordType::Caa && subdomain_of(&x.name, domain).is_none())
+        {
+            posture.records.push(record.clone());
+            let Some((tag, value)) = parse(&record.content) else {
+                continue;
+            };
+            // Parameters such as `; validationmethods=dns-01` follow the authority's name.
+            let issuer = value
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            match tag.to_ascii_lowercase().as_str() {
+                "issue" => posture.issuers.push(issuer),
+                "issuewild" => posture.wildcard_issuers.push(issuer),
+                "iodef" => posture.iodef.push(value),
+                _ => {}
+            }
+        }
+        posture
+    }
+
+    /// Whether any CAA records exist. Without them, every authority may issue certificates.
+    pub fn has_caa(&self) -> bool {
+        !self.records.is_empty()
+    }
+
+    /// The `expected` authorities which the records don't allow to issue certificates.
+    pub fn missing<'a>(&self, expected: &'a [String]) -> Vec<&'a str> {
+        if self.issuers.is_empty() {
+            return Vec::new();
+        }
+        expected
+            .iter()
+            .filter(|x| !self.issuers.iter().any(|y| y.eq_ignore_ascii_case(x)))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Splits CAA content such as `0 issue "letsencrypt.org"` into its tag and unquoted value.
+fn parse(content: &str) -> Option<(&str, String)> {
+    let mut fields = content.trim().splitn(3, char::is_whitespace);
+    let _flags = fields.next()?;
+    let tag = fields.next()?;
+    let value = fields.next()?.trim().trim_matches('"').to_string();
+    Some((tag, value))
+}
+
+/// A standard set of CAA records to apply to many domains.
+/// ```
+/// let policy = porkbun::CaaPolicy::new(["letsencrypt.org"]).with_iodef("mailto:security@example.com");
+/// let records = policy.records();
+/// assert_eq!(records[0].content, "0 issue \"letsencrypt.org\"");
+/// assert_eq!(records[1].content, "0 iodef \"mailto:security@example.com\"");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CaaPolicy {
+    /// The only authorities allowed to issue certificates.
+    pub issuers: Vec<String>,
+    pub iodef: Option<String>,
+}
+
+impl CaaPolicy {
+    pub fn new(issuers: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            issuers: issuers.into_iter().map(Into::into).collect(),
+            iodef: None,
+        }
+    }
+
+    /// Asks authorities to report refused requests to `url`, such as a `mailto:` address.
+    pub fn with_iodef(mut self, url: impl Into<String>) -> Self {
+        self.iodef = Some(url.into());
+        self
+    }
+
+    /// The CAA records of the domain itself which express this policy.
+    pub fn records(&self) -> Vec<NewRecord> {
+        let record = |tag: &str, value: &str| NewRecord {
+            name: None,
+            ty: RecordType::Caa,
+            content: format!("0 {tag} \"{value}\""),
+            ttl: None,
+            prio: None,
+        };
+        let mut records: Vec<NewRecord> = self.issuers.iter().map(|x| record("issue", x)).collect();
+        records.extend(self.iodef.iter().map(|x| record("iodef", x)));
+        records
+    }
+
+    /// Plans replacing the CAA records of `domain` itself with this policy's, leaving every
+    /// other record alone.
+    pub fn plan(&self, domain: &str, records: &[DnsRecord]) -> ChangeSet {
+        let existing = CaaPosture::from_records(domain, records).records;
+        ChangeSet::diff(domain, &existing, &self.records())
+    }
+}
@@ -0,0 +1,118 @@
+use crate::retry::Backoff;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Describes a single call to the porkbun API, including any retries.
+///
+/// Register a hook to receive these with [Client::with_call_hook](crate::Client::with_call_hook).
+#[derive(Clone, Debug)]
+pub struct CallInfo {
+    /// The API operation, such as `dns/retrieve` or `ping`, without any domain or id.
+    pub endpoint: String,
+    /// The time taken by every attempt, including delays between them.
+    pub duration: Duration,
+    pub attempt_count: u32,
+    /// The HTTP status of the last response, or `None` if no response was received.
+    pub http_status: Option<u16>,
+    /// The rate limit reported by the last response, if it included any rate limit headers.
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl CallInfo {
+    /// Reduces a request url to its operation, such as `dns/retrieve`.
+    pub(crate) fn endpoint(url: &str) -> String {
+        let path = url
+            .split_once("/api/json/v3/")
+            .map_or(url, |(_, path)| path);
+        path.split('/').take(2).collect::<Vec<_>>().join("/")
+    }
+
+    /// The domain a request url is about, which follows the operation in every endpoint with one.
+    pub(crate) fn domain(url: &str) -> Option<&str> {
+        let path = url.split_once("/api/json/v3/")?.1;
+        path.split('/').nth(2).filter(|x| !x.is_empty())
+    }
+}
+
+/// How requests to one endpoint differ from the rest of a client's.
+///
+/// Set one with [Client::with_endpoint_policy](crate::Client::with_endpoint_policy).
+/// Unset fields fall back to the client's own settings.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EndpointPolicy {
+    pub timeout: Option<Duration>,
+    pub backoff: Option<Backoff>,
+}
+
+impl EndpointPolicy {
+    pub fn timeout(timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..Self::default()
+        }
+    }
+
+    pub fn backoff(backoff: Backoff) -> Self {
+        Self {
+            backoff: Some(backoff),
+            ..Self::default()
+        }
+    }
+}
+
+/// A rate limit reported by the API through response headers.
+///
+/// Porkbun doesn't document these headers, so each field is only present if a response
+/// included it. Both the common `X-RateLimit-*` and standard `RateLimit-*` names are read.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RateLimit {
+    /// How many requests are allowed per window.
+    pub limit: Option<u64>,
+    /// How many requests are left in the current window.
+    pub remaining: Option<u64>,
+    /// How long until the current window ends.
+    pub reset: Option<Duration>,
+}
+
+impl RateLimit {
+    pub(crate) fn from_headers(headers: &reqwest::header::HeaderMap) -> Option<Self> {
+        let header = |name: &str| {
+            [format!("x-ratelimit-{name}"), format!("ratelimit-{name}")]
+                .iter()
+                .find_map(|x| headers.get(x))
+                .and_then(|x| x.to_str().ok())
+                .and_then(|x| x.trim().parse::<u64>().ok())
+        };
+        let rate_limit = Self {
+            limit: header("limit"),
+            remaining: header("remaining"),
+            reset: header("reset").map(reset_after),
+        };
+        (rate_limit != Self::default()).then_some(rate_limit)
+    }
+}
+
+/// Reads a reset header, which some servers send as a number of seconds and others as the
+/// unix time the window ends at.
+///
+/// No window lasts anywhere near as long as the time since the epoch, so a value larger than
+/// a year is taken to be a timestamp.
+fn reset_after(value: u64) -> Duration {
+    const YEAR: u64 = 365 * 24 * 60 * 60;
+    if value <= YEAR {
+        return Duration::from_secs(value);
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    Duration::from_secs(value).saturating_sub(now)
+}
+
+#[derive(Clone)]
+pub(crate) struct CallHook(pub Arc<dyn Fn(&CallInfo) + Send + Sync>);
+
+impl std::fmt::Debug for CallHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CallHook")
+    }
+}
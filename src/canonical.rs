@@ -0,0 +1,55 @@
+use crate::RecordType;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Rewrites a record's content into one form, so that contents which only differ in how
+/// they're written compare equal.
+///
+/// Addresses are written as [Ipv4Addr] and [Ipv6Addr] display them, hostnames are lowercased
+/// without a trailing dot, and the fields of MX and SRV records are separated by single
+/// spaces. Other types, whose content may be case or space sensitive, are left as they are.
+/// ```
+/// use porkbun::{canonicalize, RecordType};
+///
+/// assert_eq!(canonicalize(RecordType::Aaaa, "2001:DB8:0:0::1"), "2001:db8::1");
+/// assert_eq!(canonicalize(RecordType::Cname, "Host.Example.com."), "host.example.com");
+/// assert_eq!(canonicalize(RecordType::Srv, "5  0443 sip.example.com."), "5 443 sip.example.com");
+/// assert_eq!(canonicalize(RecordType::Txt, "Hello  World"), "Hello  World");
+/// ```
+pub fn canonicalize(ty: RecordType, content: &str) -> String {
+    let trimmed = content.trim();
+    match ty {
+        RecordType::A => trimmed
+            .parse::<Ipv4Addr>()
+            .map_or_else(|_| trimmed.to_string(), |x| x.to_string()),
+        RecordType::Aaaa => trimmed
+            .parse::<Ipv6Addr>()
+            .map_or_else(|_| trimmed.to_string(), |x| x.to_string()),
+        RecordType::Cname | RecordType::Alias | RecordType::Ns => hostname(trimmed),
+        // Porkbun keeps the priority apart, but contents copied from zone files may have it.
+        RecordType::Mx | RecordType::Srv => {
+            let mut fields: Vec<String> = trimmed
+                .split_whitespace()
+                .map(|x| {
+                    x.parse::<u16>()
+                        .map_or_else(|_| x.to_string(), |x| x.to_string())
+                })
+                .collect();
+            if let Some(target) = fields.last_mut() {
+                *target = hostname(target);
+            }
+            fields.join(" ")
+        }
+        RecordType::Txt
+        | RecordType::Tlsa
+        | RecordType::Caa
+        | RecordType::Https
+        | RecordType::Svcb => content.to_string(),
+    }
+}
+
+fn hostname(name: &str) -> String {
+    match name {
+        "." => name.to_string(),
+        name => name.trim_end_matches('.').to_ascii_lowercase(),
+    }
+}
@@ -0,0 +1,86 @@
+//! Time as seen by [Client](crate::Client) and the helpers built on it, so that retries and
+//! hold-down times can be tested without real sleeps.
+//!
+//! ```
+//! use porkbun::clock::{Clock, MockClock};
+//! use porkbun::retry::{retry_with_clock, Backoff, Retry};
+//! use std::time::Duration;
+//!
+//! let clock = MockClock::new();
+//! let result: Result<(), &str> =
+//!     retry_with_clock(&clock, &Backoff::default(), |_| Err("busy"), |_| Retry::Backoff);
+//! assert!(result.is_err());
+//! // Two retries waited 1 and 2 seconds, without the test taking any time.
+//! assert_eq!(clock.elapsed(), Duration::from_secs(3));
+//! ```
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub trait Clock: std::fmt::Debug + Send + Sync {
+    fn now(&self) -> Instant;
+    fn sleep(&self, duration: Duration);
+}
+
+/// The real time, which [Client](crate::Client) uses by default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// A clock which only moves when told to, or when something sleeps on it.
+///
+/// Sleeping returns immediately after advancing the clock. Clones share the same time.
+#[derive(Clone, Debug)]
+pub struct MockClock {
+    start: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Arc::default(),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.lock() += duration;
+    }
+
+    /// How far the clock has moved since it was created.
+    pub fn elapsed(&self) -> Duration {
+        *self.lock()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Duration> {
+        self.elapsed
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
@@ -0,0 +1,361 @@
+//! Checks that porkbun's responses still parse, by replaying saved ("golden") responses
+//! through this crate's parsers.
+//!
+//! Each [Golden] is one response of one endpoint. [Golden::check] reports whether the client
+//! can still read it, and which of its fields the client ignores, such as ones porkbun added
+//! since. [Client::capture] saves a response with the account's domains, addresses, and notes
+//! redacted, so a user getting [Error::MalformedApi] can attach it to a bug report.
+//! ```
+//! use porkbun::compat::Golden;
+//! use porkbun::testing::fixtures;
+//!
+//! let golden = Golden {
+//!     operation: "dns/retrieve".into(),
+//!     response: fixtures::RETRIEVE.replacen(r#""notes":"""#, r#""notes":"","locked":false"#, 1),
+//! };
+//! let report = golden.check();
+//! assert!(report.is_compatible());
+//! assert_eq!(report.unknown_fields, ["/records/0/locked"]);
+//! ```
+
+use crate::endpoints::Endpoint;
+use crate::testing::MockServer;
+use crate::{retry, Client, Error, NewRecord, RecordId, RecordType, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A response from one of porkbun's endpoints, as saved in a golden file.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Golden {
+    /// The endpoint's operation, such as `dns/retrieve`.
+    pub operation: String,
+    /// The body of the response, exactly as porkbun sent it.
+    pub response: String,
+}
+
+/// Whether a [Golden] response can still be read, as found by [Golden::check].
+#[derive(Debug)]
+pub struct CompatReport {
+    pub operation: String,
+    /// The error the client returned when given the response, if any.
+    ///
+    /// Porkbun's own errors, such as [Error::Api], mean the response was read.
+    pub error: Option<Error>,
+    /// JSON pointers to the fields of the response which the client ignores, such as
+    /// `/domains/0/autoRenew`.
+    pub unknown_fields: Vec<String>,
+}
+
+impl CompatReport {
+    pub fn is_compatible(&self) -> bool {
+        !matches!(
+            self.error,
+            Some(
+                Error::MalformedApi { .. }
+                    | Error::MalformedApiSerde { .. }
+                    | Error::UnsupportedEndpoint { .. }
+            )
+        )
+    }
+}
+
+impl Golden {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        Ok(std::fs::write(path, serde_json::to_string_pretty(self)?)?)
+    }
+
+    /// Replays the response to a client, as if porkbun had sent it, and reports how it was
+    /// read.
+    ///
+    /// # Panics
+    ///
+    /// If the local server the response is replayed from can't be started.
+    pub fn check(&self) -> CompatReport {
+        let server = MockServer::start();
+        server.respond(&self.operation, &self.response);
+        let client = server.client().with_backoff(retry::Backoff::none());
+        let unknown_fields = match (known_fields(&self.operation), self.response.parse()) {
+            (Some(known), Ok(value)) => {
+                let mut unknown = Vec::new();
+                find_unknown(&value, "", "", known, &mut unknown);
+                unknown
+            }
+            _ => Vec::new(),
+        };
+        CompatReport {
+            operation: self.operation.clone(),
+            error: replay(&client, &self.operation).err(),
+            unknown_fields,
+        }
+    }
+}
+
+/// Checks every golden file (ending in `.json`) in `dir`, in order of their names.
+pub fn check_dir(dir: impl AsRef<Path>) -> Result<Vec<(PathBuf, CompatReport)>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|x| x == "json") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let report = Golden::open(&path)?.check();
+            Ok((path, report))
+        })
+        .collect()
+}
+
+/// Calls the client method which reads `operation`'s responses.
+fn replay(client: &Client, operation: &str) -> Result<()> {
+    let domain = "example.com";
+    let record = NewRecord {
+        name: Some("www".into()),
+        ty: RecordType::A,
+        content: "203.0.113.7".into(),
+        ttl: None,
+        prio: None,
+    };
+    let name = record.name.as_deref();
+    match operation {
+        "ping" => client.ping().map(drop),
+        "domain/listAll" => client.list_domains().map(drop),
+        "dns/retrieve" => client.fetch_records(domain).map(drop),
+        "dns/retrieveByNameType" => client
+            .fetch_records_by_name_type(domain, record.ty, name)
+            .map(drop),
+        "dns/create" => client
+            .create_record(domain, name, record.ty, &record.content, None, None)
+            .map(drop),
+        "dns/edit" => client.edit_record(domain, RecordId(1), &record),
+        "dns/editByNameType" => {
+            client.edit_records_by_name_type(domain, record.ty, name, &record.content, None, None)
+        }
+        "dns/delete" => client.delete_record(domain, RecordId(1)),
+        "dns/deleteByNameType" => client.delete_records_by_name_type(domain, record.ty, name),
+        _ => Err(Error::UnsupportedEndpoint {
+            operation: operation.to_string(),
+            reason: "isn't known to this version",
+        }),
+    }
+}
+
+/// The fields the client reads from each operation's response, as paths where `[]` stands
+/// for every element of an array. Every response may also have `status` and `message`.
+fn known_fields(operation: &str) -> Option<&'static [&'static str]> {
+    const RECORDS: &[&str] = &[
+        "records",
+        "records/[]",
+        "records/[]/id",
+        "records/[]/name",
+        "records/[]/type",
+        "records/[]/content",
+        "records/[]/ttl",
+        "records/[]/prio",
+        "records/[]/notes",
+    ];
+    Some(match operation {
+        "ping" => &["yourIp"],
+        "domain/listAll" => &[
+            "domains",
+            "domains/[]",
+            "domains/[]/domain",
+            "domains/[]/status",
+            "domains/[]/tld",
+            "domains/[]/createDate",
+            "domains/[]/expireDate",
+        ],
+        "dns/retrieve" | "dns/retrieveByNameType" => RECORDS,
+        "dns/create" => &["id"],
+        "dns/edit" | "dns/editByNameType" | "dns/delete" | "dns/deleteByNameType" => &[],
+        _ => return None,
+    })
+}
+
+fn find_unknown(
+    value: &Value,
+    path: &str,
+    pointer: &str,
+    known: &[&str],
+    unknown: &mut Vec<String>,
+) {
+    let join = |path: &str, key: &str| match path {
+        "" => key.to_string(),
+        path => format!("{path}/{key}"),
+    };
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                let field_path = join(path, key);
+                let field_pointer =
+                    format!("{pointer}/{}", key.replace('~', "~0").replace('/', "~1"));
+                if known.contains(&field_path.as_str())
+                    || path.is_empty() && matches!(key.as_str(), "status" | "message")
+                {
+                    find_unknown(value, &field_path, &field_pointer, known, unknown);
+                } else {
+                    unknown.push(field_pointer);
+                }
+            }
+        }
+        Value::Array(elements) => {
+            let element_path = join(path, "[]");
+            for (i, element) in elements.iter().enumerate() {
+                find_unknown(
+                    element,
+                    &element_path,
+                    &format!("{pointer}/{i}"),
+                    known,
+                    unknown,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Capturing responses
+impl Client {
+    /// Fetches a response from `endpoint` as a [Golden], with the account's domains replaced
+    /// by `example.com`, `example2.com`, and so on, addresses replaced by documentation
+    /// addresses, and record notes and TXT contents replaced by `redacted`.
+    ///
+    /// Only endpoints which read records or domains can be captured. At most 100 domains are
+    /// kept from [Endpoint::ListDomains], and JSON responses are re-encoded with their fields
+    /// in alphabetical order.
+    pub fn capture(&self, endpoint: Endpoint) -> Result<Golden> {
+        let domain = match endpoint {
+            Endpoint::Ping | Endpoint::ListDomains => None,
+            Endpoint::DnsRetrieve { domain }
+            | Endpoint::DnsRetrieveRecord { domain, .. }
+            | Endpoint::DnsRetrieveByNameType { domain, .. } => Some(domain),
+            _ => {
+                return Err(Error::UnsupportedEndpoint {
+                    operation: endpoint.operation().to_string(),
+                    reason: "changes records, so it can't be captured",
+                })
+            }
+        };
+        let response = self.post(&endpoint.url(&self.api_url), self.key_file.to_string())?;
+        Ok(Golden {
+            operation: endpoint.operation().to_string(),
+            response: redact(&response, domain),
+        })
+    }
+}
+
+/// How many domains of a [Endpoint::ListDomains] response [Client::capture] keeps.
+const CAPTURED_DOMAINS: usize = 100;
+
+fn redact(response: &str, domain: Option<&str>) -> String {
+    let Ok(mut value) = response.parse::<Value>() else {
+        return match domain {
+            Some(domain) => {
+                replace_ignoring_case(response, &domain.to_ascii_lowercase(), "example.com")
+            }
+            None => response.to_string(),
+        };
+    };
+    if let Some(domains) = value.get_mut("domains").and_then(Value::as_array_mut) {
+        domains.truncate(CAPTURED_DOMAINS);
+    }
+
+    let mut domains: Vec<String> = domain.into_iter().map(str::to_ascii_lowercase).collect();
+    let listed = value.get("domains").and_then(Value::as_array);
+    for listed in listed.into_iter().flatten() {
+        let domain = listed.get("domain").and_then(Value::as_str);
+        if let Some(domain) = domain.map(str::to_ascii_lowercase) {
+            if !domains.contains(&domain) {
+                domains.push(domain);
+            }
+        }
+    }
+    let mut replacements: Vec<(String, String)> = domains
+        .into_iter()
+        .enumerate()
+        .map(|(i, domain)| match i {
+            0 => (domain, "example.com".to_string()),
+            i => (domain, format!("example{}.com", i + 1)),
+        })
+        .collect();
+    // Longer names first, so that a domain containing another isn't half replaced.
+    replacements.sort_by_key(|(domain, _)| std::cmp::Reverse(domain.len()));
+
+    let mut redactor = Redactor {
+        domains: replacements,
+        addresses: HashMap::new(),
+    };
+    redactor.redact(&mut value);
+    value.to_string()
+}
+
+struct Redactor {
+    domains: Vec<(String, String)>,
+    /// Each address seen so far and its replacement, so that equal addresses stay equal.
+    addresses: HashMap<String, String>,
+}
+
+impl Redactor {
+    fn redact(&mut self, value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                let is_txt = fields.get("type").and_then(Value::as_str) == Some("TXT");
+                for (key, value) in fields.iter_mut() {
+                    let hidden = key == "notes" || is_txt && key == "content";
+                    match value {
+                        Value::String(text) if hidden && !text.is_empty() => {
+                            *text = "redacted".to_string();
+                        }
+                        value => self.redact(value),
+                    }
+                }
+            }
+            Value::Array(elements) => elements.iter_mut().for_each(|x| self.redact(x)),
+            Value::String(text) => *text = self.redact_text(text),
+            _ => {}
+        }
+    }
+
+    fn redact_text(&mut self, text: &str) -> String {
+        if let Ok(address) = text.parse::<std::net::IpAddr>() {
+            let count = self.addresses.len() + 1;
+            let replacement =
+                self.addresses
+                    .entry(text.to_string())
+                    .or_insert_with(|| match address {
+                        std::net::IpAddr::V4(_) => format!("203.0.113.{}", count % 256),
+                        std::net::IpAddr::V6(_) => format!("2001:db8::{count:x}"),
+                    });
+            return replacement.clone();
+        }
+        let mut text = text.to_string();
+        for (domain, replacement) in &self.domains {
+            if text.to_ascii_lowercase().contains(domain.as_str()) {
+                text = replace_ignoring_case(&text, domain, replacement);
+            }
+        }
+        text
+    }
+}
+
+/// Replaces each occurrence of the lowercase `from` in `text`, in any case.
+fn replace_ignoring_case(text: &str, from: &str, to: &str) -> String {
+    let lower = text.to_ascii_lowercase();
+    let mut replaced = String::new();
+    let mut rest = 0;
+    for (start, _) in lower.match_indices(from) {
+        replaced.push_str(&text[rest..start]);
+        replaced.push_str(to);
+        rest = start + from.len();
+    }
+    replaced.push_str(&text[rest..]);
+    replaced
+}
@@ -0,0 +1,51 @@
+use crate::{subdomain_of, Client, DnsRecord, Error, RecordId, RecordType, Result, Ttl};
+
+/// Returns the records of `existing` which can't coexist with a new `ty` record at `name`.
+///
+/// A CNAME record must be the only record at its name, so it conflicts with every other
+/// record there, and every other record conflicts with it.
+fn conflicts(
+    domain: &str,
+    existing: &[DnsRecord],
+    name: Option<&str>,
+    ty: RecordType,
+) -> Vec<DnsRecord> {
+    let name = name.filter(|x| !x.is_empty());
+    existing
+        .iter()
+        .filter(|x| subdomain_of(&x.name, domain) == name)
+        .filter(|x| ty == RecordType::Cname || x.ty == RecordType::Cname)
+        .cloned()
+        .collect()
+}
+
+/// Conflict checks
+impl Client {
+    /// Like [Client::create_record], but first fetches the domain's records and fails with
+    /// [Error::Conflict] if the new record couldn't coexist with any of them.
+    ///
+    /// Porkbun doesn't always reject such records, and resolvers may silently ignore
+    /// one of the two.
+    pub fn create_record_checked(
+        &self,
+        domain: &str,
+        name: Option<&str>,
+        ty: RecordType,
+        content: &str,
+        ttl: Option<Ttl>,
+        prio: Option<&str>,
+    ) -> Result<Option<RecordId>> {
+        let records = conflicts(domain, &self.fetch_records(domain)?, name, ty);
+        if !records.is_empty() {
+            return Err(Error::Conflict {
+                name: match name.filter(|x| !x.is_empty()) {
+                    Some(name) => format!("{name}.{domain}"),
+                    None => domain.to_string(),
+                },
+                ty,
+                records,
+            });
+        }
+        self.create_record(domain, name, ty, content, ttl, prio)
+    }
+}
@@ -0,0 +1,181 @@
+//! One budget of requests shared by every porkbun tool on a host, so that a ddns daemon, a zone
+//! sync, and a certificate renewal running at once don't throttle each other.
+//!
+//! One process serves a [Coordinator] on a unix socket, and every
+//! [Client](crate::Client) given the socket with
+//! [Client::with_coordinator](crate::Client::with_coordinator) asks it before each request.
+//! When porkbun's rate limit is hit anyway, the coordinator holds back every client until it
+//! resets.
+//!
+//! ```no_run
+//! use porkbun::coordinator::Coordinator;
+//! use std::time::Duration;
+//!
+//! // In one process:
+//! std::thread::spawn(|| Coordinator::new(60, Duration::from_secs(60)).serve("/run/porkbun.sock"));
+//!
+//! // In every other:
+//! let client = porkbun::Client::open_keys("keys.json")?.with_coordinator("/run/porkbun.sock");
+//! # Ok::<(), porkbun::Error>(())
+//! ```
+
+use crate::{Client, Error, Result};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Hands out permission to send requests, at most `requests` in any `period`.
+///
+/// Clients send a line for each message and wait for `ok` in reply: `acquire` before each
+/// request, which is answered once one may be sent, and `pause MILLIS` after being rate
+/// limited.
+#[derive(Debug)]
+pub struct Coordinator {
+    requests: usize,
+    period: Duration,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// When each request in the current period was allowed, oldest first.
+    sent: VecDeque<Instant>,
+    paused_until: Option<Instant>,
+}
+
+impl Coordinator {
+    pub fn new(requests: u32, period: Duration) -> Self {
+        Self {
+            requests: usize::try_from(requests.max(1)).unwrap_or(usize::MAX),
+            period,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Listens on the socket at `path` and serves clients until it can't accept any more.
+    ///
+    /// A socket left behind by a coordinator which is no longer running is replaced.
+    pub fn serve(self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if path.exists() && UnixStream::connect(path).is_err() {
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        let coordinator = Arc::new(self);
+        loop {
+            let (stream, _) = listener.accept()?;
+            let coordinator = coordinator.clone();
+            std::thread::spawn(move || coordinator.handle(stream));
+        }
+    }
+
+    fn handle(&self, stream: UnixStream) -> std::io::Result<()> {
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 {
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next().and_then(|x| x.parse().ok())) {
+                (Some("acquire"), _) => self.acquire(),
+                (Some("pause"), Some(millis)) => self.pause(Duration::from_millis(millis)),
+                _ => {}
+            }
+            (&stream).write_all(b"ok\n")?;
+            line.clear();
+        }
+        Ok(())
+    }
+
+    /// Waits until a request may be sent, and counts it.
+    fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.lock();
+                let now = Instant::now();
+                while state
+                    .sent
+                    .front()
+                    .is_some_and(|x| now.saturating_duration_since(*x) >= self.period)
+                {
+                    state.sent.pop_front();
+                }
+                let paused = state
+                    .paused_until
+                    .map(|x| x.saturating_duration_since(now))
+                    .filter(|x| !x.is_zero());
+                match (paused, state.sent.front()) {
+                    (Some(wait), _) => wait,
+                    (None, Some(oldest)) if state.sent.len() >= self.requests => {
+                        (*oldest + self.period).saturating_duration_since(now)
+                    }
+                    (None, _) => {
+                        state.sent.push_back(now);
+                        return;
+                    }
+                }
+            };
+            std::thread::sleep(wait);
+        }
+    }
+
+    fn pause(&self, delay: Duration) {
+        let until = Instant::now() + delay;
+        let mut state = self.lock();
+        state.paused_until = state.paused_until.max(Some(until));
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Coordination
+impl Client {
+    /// Asks the [Coordinator] listening at `socket` before each request, and tells it when
+    /// porkbun's rate limit is hit so that its other clients wait too.
+    ///
+    /// Requests go ahead without asking if the coordinator can't be reached.
+    pub fn with_coordinator(mut self, socket: impl AsRef<Path>) -> Self {
+        self.coordinator = Some(socket.as_ref().into());
+        self
+    }
+
+    /// Sends a message to the coordinator and waits for its reply, failing if that's past the
+    /// deadline.
+    pub(crate) fn coordinate(&self, message: &str) -> Result<()> {
+        let Some(socket) = &self.coordinator else {
+            return Ok(());
+        };
+        let send = || {
+            let mut stream = UnixStream::connect(socket)?;
+            stream.set_read_timeout(self.remaining().filter(|x| !x.is_zero()))?;
+            writeln!(stream, "{message}")?;
+            BufReader::new(stream).read_line(&mut String::new())
+        };
+        match send() {
+            Ok(_) => Ok(()),
+            Err(error) if self.remaining().is_some() && is_timeout(&error) => {
+                Err(Error::DeadlineExceeded)
+            }
+            Err(_error) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    "can't reach the coordinator at {}: {_error}",
+                    socket.display()
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+fn is_timeout(error: &std::io::Error) -> bool {
+    matches!(
+        error.kind(),
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+    )
+}
@@ -0,0 +1,37 @@
+use crate::{Client, ClientBuilder, Result};
+
+impl ClientBuilder {
+    /// Builds a client using keys kept in the operating system's credential store under
+    /// `service` and `account`, as saved by [Client::store_keys_in_keyring].
+    pub fn from_keyring(self, service: &str, account: &str) -> Result<Client> {
+        let key_file = keyring::Entry::new(service, account)?.get_password()?;
+        self.parse_keys(key_file)
+    }
+}
+
+/// Keyring
+impl Client {
+    /// Opens keys kept in the operating system's credential store (the Keychain on macOS,
+    /// the Credential Manager on Windows, and the kernel keyring on Linux, which is cleared
+    /// on reboot), so they never have to be written to a file.
+    ///
+    /// Use a [ClientBuilder] to configure the connection.
+    pub fn from_keyring(service: &str, account: &str) -> Result<Self> {
+        ClientBuilder::new().from_keyring(service, account)
+    }
+
+    /// Saves this client's keys in the operating system's credential store, replacing any
+    /// already saved under `service` and `account`.
+    ///
+    /// A login flow can check the keys it was given before saving them:
+    /// ```no_run
+    /// let client = porkbun::ClientBuilder::new().build("pk1_...", "sk1_...")?;
+    /// client.ping()?;
+    /// client.store_keys_in_keyring("porkbun", "default")?;
+    /// # Ok::<(), porkbun::Error>(())
+    /// ```
+    pub fn store_keys_in_keyring(&self, service: &str, account: &str) -> Result<()> {
+        keyring::Entry::new(service, account)?.set_password(&self.key_file)?;
+        Ok(())
+    }
+}
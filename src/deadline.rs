@@ -0,0 +1,70 @@
+use crate::clock::{Clock, SystemClock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A point after which a [Client](crate::Client) stops making requests, which can also be
+/// cancelled early from another thread, such as when a daemon is asked to shut down.
+///
+/// Clones share cancellation, so one can be kept to cancel another given to
+/// [Client::with_deadline](crate::Client::with_deadline).
+/// ```
+/// # use std::time::Duration;
+/// let deadline = porkbun::Deadline::after(Duration::from_secs(60));
+/// let handle = deadline.clone();
+/// assert!(!deadline.is_expired());
+/// handle.cancel();
+/// assert!(deadline.is_expired());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Deadline {
+    at: Option<Instant>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Deadline {
+    pub fn at(instant: Instant) -> Self {
+        Self {
+            at: Some(instant),
+            ..Self::default()
+        }
+    }
+
+    pub fn after(duration: Duration) -> Self {
+        Self::after_with_clock(&SystemClock, duration)
+    }
+
+    /// Like [Deadline::after], but measured from the time on `clock`, such as the
+    /// [MockClock](crate::clock::MockClock) a client was given with
+    /// [Client::with_clock](crate::Client::with_clock).
+    pub fn after_with_clock(clock: &dyn Clock, duration: Duration) -> Self {
+        Self::at(clock.now() + duration)
+    }
+
+    /// A deadline which only expires once cancelled.
+    pub fn never() -> Self {
+        Self::default()
+    }
+
+    /// Expires this deadline and all of its clones immediately.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining() == Some(Duration::ZERO)
+    }
+
+    /// The time left, or `None` if there is no time limit.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.remaining_at(Instant::now())
+    }
+
+    /// The time left at `now`, such as according to a [Clock](crate::clock::Clock).
+    pub fn remaining_at(&self, now: Instant) -> Option<Duration> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Some(Duration::ZERO);
+        }
+        self.at.map(|x| x.saturating_duration_since(now))
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{subdomain_of, ChangeSet, DnsRecord, NewRecord, RecordType};
+
+/// What receivers should do with mail which fails DMARC checks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Disposition {
+    /// Only send reports, such as while finding every legitimate sender.
+    #[default]
+    None,
+    Quarantine,
+    Reject,
+}
+
+impl Disposition {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Disposition::None => "none",
+            Disposition::Quarantine => "quarantine",
+            Disposition::Reject => "reject",
+        }
+    }
+}
+
+impl std::str::FromStr for Disposition {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Disposition::None),
+            "quarantine" => Ok(Disposition::Quarantine),
+            "reject" => Ok(Disposition::Reject),
+            _ => Err(format!(
+                "unknown disposition \"{s}\"; expected none, quarantine, or reject"
+            )),
+        }
+    }
+}
+
+/// A DMARC policy to publish at `_dmarc` of many domains, sending their reports to one
+/// central mailbox.
+/// ```
+/// use porkbun::{Disposition, DmarcPolicy};
+///
+/// let policy = DmarcPolicy::new(Disposition::Quarantine).with_rua("dmarc@reports.example.net");
+/// assert_eq!(
+///     policy.content(),
+///     "v=DMARC1; p=quarantine; rua=mailto:dmarc@reports.example.net"
+/// );
+/// // Reports for other domains must be authorized by the mailbox's domain.
+/// let record = &policy.authorizations("example.com")[0];
+/// assert_eq!(record.0, "reports.example.net");
+/// assert_eq!(record.1.name.as_deref(), Some("example.com._report._dmarc"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DmarcPolicy {
+    pub policy: Disposition,
+    /// The policy for subdomains, if it differs.
+    pub subdomain_policy: Option<Disposition>,
+    /// Addresses which receive daily aggregate reports.
+    pub rua: Vec<String>,
+    /// Addresses which receive a report about each failing message.
+    pub ruf: Vec<String>,
+}
+
+impl DmarcPolicy {
+    pub fn new(policy: Disposition) -> Self {
+        Self {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_subdomain_policy(mut self, policy: Disposition) -> Self {
+        self.subdomain_policy = Some(policy);
+        self
+    }
+
+    /// Adds an address for aggregate reports. `mailto:` is added if it's missing.
+    pub fn with_rua(mut self, address: &str) -> Self {
+        self.rua.push(mailto(address));
+        self
+    }
+
+    /// Adds an address for failure reports. `mailto:` is added if it's missing.
+    pub fn with_ruf(mut self, address: &str) -> Self {
+        self.ruf.push(mailto(address));
+        self
+    }
+
+    /// The content of the `_dmarc` TXT record.
+    pub fn content(&self) -> String {
+        let mut content = format!("v=DMARC1; p={}", self.policy.as_str());
+        if let Some(policy) = self.subdomain_policy {
+            content += &format!("; sp={}", policy.as_str());
+        }
+        if !self.rua.is_empty() {
+            content += &format!("; rua={}", self.rua.join(","));
+        }
+        if !self.ruf.is_empty() {
+            content += &format!("; ruf={}", self.ruf.join(","));
+        }
+        content
+    }
+
+    /// Plans replacing the DMARC record of `domain` with this policy, or creating one if there
+    /// is none.
+    pub fn plan(&self, domain: &str, records: &[DnsRecord]) -> ChangeSet {
+        let existing: Vec<DnsRecord> = records
+            .iter()
+            .filter(|x| x.ty == RecordType::Txt && subdomain_of(&x.name, domain) == Some("_dmarc"))
+            .filter(|x| x.content.trim_matches('"').starts_with("v=DMARC1"))
+            .cloned()
+            .collect();
+        let desired = NewRecord {
+            name: Some("_dmarc".to_string()),
+            ty: RecordType::Txt,
+            content: self.content(),
+            ttl: None,
+            prio: None,
+        };
+        ChangeSet::diff(domain, &existing, &[desired])
+    }
+
+    /// The records which let the domains of report addresses accept reports about `domain`
+    /// (RFC 7489 section 7.1), alongside the domain each belongs to.
+    ///
+    /// Names are relative to that domain, which may need to be shortened to the zone it's in.
+    /// Addresses within `domain` itself don't need authorizing.
+    pub fn authorizations(&self, domain: &str) -> Vec<(String, NewRecord)> {
+        let mut domains: Vec<&str> = self
+            .rua
+            .iter()
+            .chain(&self.ruf)
+            .filter_map(|x| x.rsplit_once('@'))
+            .map(|x| x.1.trim_end_matches('.'))
+            .filter(|x| !x.eq_ignore_ascii_case(domain) && !x.ends_with(&format!(".{domain}")))
+            .collect();
+        domains.sort_unstable();
+        domains.dedup();
+        domains
+            .into_iter()
+            .map(|receiver| {
+                let record = NewRecord {
+                    name: Some(format!("{domain}._report._dmarc")),
+                    ty: RecordType::Txt,
+                    content: "v=DMARC1".to_string(),
+                    ttl: None,
+                    prio: None,
+                };
+                (receiver.to_string(), record)
+            })
+            .collect()
+    }
+}
+
+fn mailto(address: &str) -> String {
+    if address.starts_with("mailto:") {
+        address.to_string()
+    } else {
+        format!("mailto:{address}")
+    }
+}
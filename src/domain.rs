@@ -0,0 +1,102 @@
+use crate::{Error, Result};
+
+/// A domain name in the ASCII form porkbun expects, with internationalized labels
+/// encoded as punycode.
+///
+/// ```
+/// let domain = porkbun::DomainName::new("bücher.example").unwrap();
+/// assert_eq!(domain.to_ascii(), "xn--bcher-kva.example");
+/// assert_eq!(domain.to_unicode(), "bücher.example");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DomainName(String);
+
+/// Scripts which contain letters easily mistaken for each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+}
+
+impl Script {
+    fn of(c: char) -> Option<Self> {
+        match c {
+            'a'..='z' | 'A'..='Z' | '\u{c0}'..='\u{24f}' | '\u{1e00}'..='\u{1eff}' => {
+                Some(Script::Latin)
+            }
+            '\u{370}'..='\u{3ff}' | '\u{1f00}'..='\u{1fff}' => Some(Script::Greek),
+            '\u{400}'..='\u{52f}' => Some(Script::Cyrillic),
+            '\u{530}'..='\u{58f}' => Some(Script::Armenian),
+            _ => None,
+        }
+    }
+}
+
+impl DomainName {
+    /// Parses a domain in either Unicode or ASCII form.
+    pub fn new(domain: &str) -> Result<Self> {
+        let domain = domain.trim_end_matches('.');
+        idna::domain_to_ascii(domain)
+            .ok()
+            .filter(|x| !x.is_empty())
+            .map(Self)
+            .ok_or_else(|| Error::InvalidDomain {
+                domain: domain.to_string(),
+            })
+    }
+
+    /// The form used in API calls, such as `xn--bcher-kva.example`.
+    pub fn to_ascii(&self) -> &str {
+        &self.0
+    }
+
+    /// The form people read, such as `bücher.example`.
+    pub fn to_unicode(&self) -> String {
+        idna::domain_to_unicode(&self.0).0
+    }
+
+    /// Returns true if any label is internationalized.
+    pub fn is_idn(&self) -> bool {
+        self.0.split('.').any(|x| x.starts_with("xn--"))
+    }
+
+    /// Labels which mix letters from scripts that look alike, such as a Cyrillic `а`
+    /// among Latin letters, as used by phishing lookalikes of other domains.
+    /// ```
+    /// let lookalike = porkbun::DomainName::new("p\u{430}ypal.example").unwrap();
+    /// assert_eq!(lookalike.mixed_script_labels(), ["p\u{430}ypal"]);
+    /// ```
+    pub fn mixed_script_labels(&self) -> Vec<String> {
+        self.to_unicode()
+            .split('.')
+            .filter(|label| {
+                let mut scripts = label.chars().filter_map(Script::of);
+                let first = scripts.next();
+                scripts.any(|x| Some(x) != first)
+            })
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl std::fmt::Display for DomainName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for DomainName {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::new(s)
+    }
+}
+
+impl AsRef<str> for DomainName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
@@ -0,0 +1,248 @@
+use crate::endpoints::Endpoint;
+use crate::{parse_response, Client, DnsRecord, Error, Result, Status};
+use std::sync::{mpsc, Arc, Mutex};
+
+/// A domain in the account, as listed by [Client::list_domains].
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct DomainInfo {
+    pub domain: String,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub tld: Option<String>,
+    #[serde(rename = "createDate", default)]
+    pub create_date: Option<String>,
+    #[serde(rename = "expireDate", default)]
+    pub expire_date: Option<String>,
+}
+
+#[cfg(feature = "chrono")]
+impl DomainInfo {
+    /// When the domain was registered.
+    ///
+    /// Porkbun gives dates as `YYYY-MM-DD HH:MM:SS` without a time zone; they are taken to
+    /// be UTC.
+    pub fn created_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        parse_date(self.create_date.as_deref()?)
+    }
+
+    /// When the domain's registration expires, taken to be UTC like [DomainInfo::created_at].
+    pub fn expires_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        parse_date(self.expire_date.as_deref()?)
+    }
+
+    /// The number of whole days from `now` until the domain expires, which is negative once
+    /// it has.
+    /// ```
+    /// use chrono::TimeZone;
+    ///
+    /// let domain = porkbun::DomainInfo {
+    ///     domain: "example.com".into(),
+    ///     status: None,
+    ///     tld: None,
+    ///     create_date: None,
+    ///     expire_date: Some("2025-03-01 12:00:00".into()),
+    /// };
+    /// let now = chrono::Utc.with_ymd_and_hms(2025, 2, 1, 0, 0, 0).unwrap();
+    /// assert_eq!(domain.days_remaining(now), Some(28));
+    /// ```
+    pub fn days_remaining(&self, now: chrono::DateTime<chrono::Utc>) -> Option<i64> {
+        Some((self.expires_at()? - now).num_days())
+    }
+}
+
+#[cfg(feature = "chrono")]
+fn parse_date(date: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::NaiveDateTime::parse_from_str(date.trim(), "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|x| x.and_utc())
+}
+
+#[cfg(feature = "time")]
+impl DomainInfo {
+    /// When the domain was registered.
+    ///
+    /// Porkbun gives dates without a time zone; they are taken to be UTC.
+    /// ```
+    /// let domain = porkbun::DomainInfo {
+    ///     domain: "example.com".into(),
+    ///     status: None,
+    ///     tld: None,
+    ///     create_date: Some("2018-08-20 17:52:51".into()),
+    ///     expire_date: None,
+    /// };
+    /// assert_eq!(domain.create_time().unwrap().unix_timestamp(), 1534787571);
+    /// ```
+    pub fn create_time(&self) -> Option<time::OffsetDateTime> {
+        parse_time(self.create_date.as_deref()?)
+    }
+
+    /// When the domain's registration expires, taken to be UTC.
+    pub fn expire_time(&self) -> Option<time::OffsetDateTime> {
+        parse_time(self.expire_date.as_deref()?)
+    }
+}
+
+#[cfg(feature = "time")]
+fn parse_time(date: &str) -> Option<time::OffsetDateTime> {
+    let format = time::macros::format_description!("[year]-[month]-[day] [hour]:[minute]:[second]");
+    time::PrimitiveDateTime::parse(date.trim(), format)
+        .ok()
+        .map(time::PrimitiveDateTime::assume_utc)
+}
+
+/// Porkbun lists at most this many domains per request.
+const PAGE_SIZE: usize = 1000;
+
+/// Domains
+impl Client {
+    /// Lists every domain in the account.
+    pub fn list_domains(&self) -> Result<Vec<DomainInfo>> {
+        #[derive(Clone, Debug, serde::Serialize)]
+        struct Body<'a> {
+            #[serde(rename = "secretapikey")]
+            secret_api: &'a str,
+            #[serde(rename = "apikey")]
+            api: &'a str,
+            start: String,
+        }
+
+        #[derive(Clone, Debug, serde::Deserialize)]
+        struct Response {
+            status: Status,
+            #[serde(default)]
+            message: String,
+            #[serde(default)]
+            domains: Vec<DomainInfo>,
+        }
+
+        let url = Endpoint::ListDomains.url(&self.api_url);
+        let mut domains = Vec::new();
+        loop {
+            let response = self.post(
+                &url,
+                serde_json::to_string(&Body {
+                    secret_api: &self.secret_api_key,
+                    api: &self.api_key,
+                    start: domains.len().to_string(),
+                })?,
+            )?;
+            let response: Response = parse_response(response)?;
+            #[cfg(feature = "tracing_debug")]
+            tracing::debug!("parsed response: {response:?}");
+            if let Status::Error = response.status {
+                return Err(Error::Api {
+                    message: response.message,
+                });
+            }
+            let page = response.domains.len();
+            domains.extend(response.domains);
+            if page < PAGE_SIZE {
+                return Ok(domains);
+            }
+        }
+    }
+
+    /// Fetches the records of several domains, at most `jobs` at a time.
+    ///
+    /// Each domain succeeds or fails on its own, so one whose DNS isn't hosted by porkbun
+    /// doesn't hide the rest.
+    pub fn fetch_many(
+        &self,
+        domains: &[impl AsRef<str> + Sync],
+        jobs: usize,
+    ) -> Vec<(String, Result<Vec<DnsRecord>>)> {
+        let next = std::sync::atomic::AtomicUsize::new(0);
+        let mut results: Vec<(usize, String, Result<Vec<DnsRecord>>)> =
+            std::thread::scope(|scope| {
+                let workers: Vec<_> = (0..jobs.clamp(1, domains.len().max(1)))
+                    .map(|_| {
+                        scope.spawn(|| {
+                            let mut results = Vec::new();
+                            loop {
+                                let index = next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                                let Some(domain) = domains.get(index) else {
+                                    return results;
+                                };
+                                let domain = domain.as_ref();
+                                results.push((
+                                    index,
+                                    domain.to_string(),
+                                    self.fetch_records(domain),
+                                ));
+                            }
+                        })
+                    })
+                    .collect();
+                workers
+                    .into_iter()
+                    .flat_map(|x| x.join().unwrap_or_else(|x| std::panic::resume_unwind(x)))
+                    .collect()
+            });
+        results.sort_by_key(|x| x.0);
+        results
+            .into_iter()
+            .map(|(_, domain, records)| (domain, records))
+            .collect()
+    }
+
+    /// Fetches the records of several domains on `jobs` threads, yielding each as it arrives
+    /// rather than once all are done, such as to process a large account without holding
+    /// every zone in memory.
+    ///
+    /// At most `jobs` results wait to be taken, after which the threads pause until the
+    /// stream is read further. Dropping the stream stops them once their current request ends.
+    /// ```no_run
+    /// # let client = porkbun::Client::open_keys("keys.json")?;
+    /// let domains = client.list_domains()?.into_iter().map(|x| x.domain);
+    /// for (domain, records) in client.records_stream(domains, 4) {
+    ///     println!("{domain}: {} records", records?.len());
+    /// }
+    /// # Ok::<(), porkbun::Error>(())
+    /// ```
+    pub fn records_stream(
+        &self,
+        domains: impl IntoIterator<Item = String>,
+        jobs: usize,
+    ) -> RecordStream {
+        let domains: Vec<String> = domains.into_iter().collect();
+        let jobs = jobs.clamp(1, domains.len().max(1));
+        let queue = Arc::new(Mutex::new(domains.into_iter()));
+        let (sender, receiver) = mpsc::sync_channel(jobs);
+        for _ in 0..jobs {
+            let client = self.clone();
+            let queue = queue.clone();
+            let sender = sender.clone();
+            std::thread::spawn(move || loop {
+                let next = queue
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .next();
+                let Some(domain) = next else {
+                    return;
+                };
+                let records = client.fetch_records(&domain);
+                if sender.send((domain, records)).is_err() {
+                    return;
+                }
+            });
+        }
+        RecordStream { receiver }
+    }
+}
+
+/// The records of each domain, in the order they finish fetching.
+///
+/// See [Client::records_stream].
+#[derive(Debug)]
+pub struct RecordStream {
+    receiver: mpsc::Receiver<(String, Result<Vec<DnsRecord>>)>,
+}
+
+impl Iterator for RecordStream {
+    type Item = (String, Result<Vec<DnsRecord>>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
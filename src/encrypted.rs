@@ -0,0 +1,81 @@
+use crate::{Client, ClientBuilder, Error, Result};
+use age::secrecy::SecretString;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// What decrypts a key file encrypted with [age](https://age-encryption.org).
+#[derive(Clone, Debug)]
+pub enum PassphraseSource {
+    /// The passphrase the file was encrypted with, as by `age --passphrase`.
+    Passphrase(String),
+    /// An environment variable holding the passphrase.
+    Env(String),
+    /// A file holding the passphrase, without any trailing newline.
+    File(PathBuf),
+    /// An age identity file, holding a private key the file was encrypted to.
+    Identity(PathBuf),
+}
+
+impl PassphraseSource {
+    fn identities(&self) -> Result<Vec<Box<dyn age::Identity>>> {
+        let passphrase = match self {
+            PassphraseSource::Passphrase(passphrase) => passphrase.clone(),
+            PassphraseSource::Env(name) => std::env::var(name).map_err(|_| Error::InvalidKey {
+                reason: format!("${name} is not set"),
+            })?,
+            PassphraseSource::File(path) => std::fs::read_to_string(path)?
+                .trim_end_matches(['\r', '\n'])
+                .to_string(),
+            PassphraseSource::Identity(path) => {
+                let path = path.to_string_lossy().into_owned();
+                return Ok(age::IdentityFile::from_file(path)?.into_identities()?);
+            }
+        };
+        Ok(vec![Box::new(age::scrypt::Identity::new(
+            SecretString::from(passphrase),
+        ))])
+    }
+}
+
+impl ClientBuilder {
+    /// Builds a client using a porkbun api key file encrypted with age, in either its binary
+    /// or armored form.
+    ///
+    /// Since the file is useless without `passphrase`, it isn't checked for
+    /// [insecure permissions](Error::InsecureKeyFile).
+    pub fn open_keys_encrypted(
+        self,
+        path: impl AsRef<Path>,
+        passphrase: &PassphraseSource,
+    ) -> Result<Client> {
+        let file = std::fs::File::open(path)?;
+        let decryptor = age::Decryptor::new(age::armor::ArmoredReader::new(file))?;
+        let identities = passphrase.identities()?;
+        let mut key_file = String::new();
+        decryptor
+            .decrypt(identities.iter().map(|x| x.as_ref()))?
+            .read_to_string(&mut key_file)?;
+        self.parse_keys(key_file)
+    }
+}
+
+/// Encrypted keys
+impl Client {
+    /// Opens a porkbun api key file encrypted with age, so that backups of it don't expose
+    /// the keys.
+    /// ```no_run
+    /// use porkbun::PassphraseSource;
+    ///
+    /// let passphrase = PassphraseSource::Env("PORKBUN_PASSPHRASE".to_string());
+    /// let client = porkbun::Client::open_keys_encrypted("keys.json.age", &passphrase)?;
+    /// # Ok::<(), porkbun::Error>(())
+    /// ```
+    ///
+    /// Use a [ClientBuilder] to configure the connection.
+    pub fn open_keys_encrypted(
+        path: impl AsRef<Path>,
+        passphrase: &PassphraseSource,
+    ) -> Result<Self> {
+        ClientBuilder::new().open_keys_encrypted(path, passphrase)
+    }
+}
@@ -0,0 +1,143 @@
+//! The urls of porkbun's API endpoints, for custom transports and signing proxies which send
+//! requests themselves.
+//!
+//! Names are escaped, and each endpoint only takes the parameters its url has.
+//! ```
+//! use porkbun::endpoints::{Endpoint, API_URL};
+//! use porkbun::RecordType;
+//!
+//! let retrieve = Endpoint::DnsRetrieve {
+//!     domain: "example.com",
+//! };
+//! assert_eq!(
+//!     retrieve.url(API_URL),
+//!     "https://api.porkbun.com/api/json/v3/dns/retrieve/example.com"
+//! );
+//!
+//! let edit = Endpoint::DnsEditByNameType {
+//!     domain: "example.com",
+//!     ty: RecordType::Aaaa,
+//!     subdomain: Some("www"),
+//! };
+//! assert_eq!(edit.path(), "dns/editByNameType/example.com/AAAA/www");
+//! assert_eq!(edit.operation(), "dns/editByNameType");
+//! ```
+
+use crate::{RecordId, RecordType};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+/// The root of every API endpoint.
+pub const API_URL: &str = "https://api.porkbun.com/api/json/v3";
+/// Like [API_URL], but only reachable over ipv4, so that [Endpoint::Ping] reports the ipv4
+/// address of the caller.
+pub const IPV4_API_URL: &str = "https://api-ipv4.porkbun.com/api/json/v3";
+
+/// Characters which may appear unescaped in a domain or subdomain within a url path.
+///
+/// `*` is kept as-is so that wildcard records can be addressed.
+const NAME_ESCAPE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b'*');
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endpoint<'a> {
+    Ping,
+    ListDomains,
+    DnsCreate {
+        domain: &'a str,
+    },
+    DnsEdit {
+        domain: &'a str,
+        id: RecordId,
+    },
+    DnsEditByNameType {
+        domain: &'a str,
+        ty: RecordType,
+        subdomain: Option<&'a str>,
+    },
+    DnsDelete {
+        domain: &'a str,
+        id: RecordId,
+    },
+    DnsDeleteByNameType {
+        domain: &'a str,
+        ty: RecordType,
+        subdomain: Option<&'a str>,
+    },
+    /// Every record of a domain.
+    DnsRetrieve {
+        domain: &'a str,
+    },
+    DnsRetrieveRecord {
+        domain: &'a str,
+        id: RecordId,
+    },
+    DnsRetrieveByNameType {
+        domain: &'a str,
+        ty: RecordType,
+        subdomain: Option<&'a str>,
+    },
+}
+
+impl Endpoint<'_> {
+    /// The operation, such as `dns/retrieve`, as named by
+    /// [Client::with_endpoint_policy](crate::Client::with_endpoint_policy) and
+    /// [CallInfo](crate::CallInfo).
+    pub fn operation(&self) -> &'static str {
+        match self {
+            Endpoint::Ping => "ping",
+            Endpoint::ListDomains => "domain/listAll",
+            Endpoint::DnsCreate { .. } => "dns/create",
+            Endpoint::DnsEdit { .. } => "dns/edit",
+            Endpoint::DnsEditByNameType { .. } => "dns/editByNameType",
+            Endpoint::DnsDelete { .. } => "dns/delete",
+            Endpoint::DnsDeleteByNameType { .. } => "dns/deleteByNameType",
+            Endpoint::DnsRetrieve { .. } | Endpoint::DnsRetrieveRecord { .. } => "dns/retrieve",
+            Endpoint::DnsRetrieveByNameType { .. } => "dns/retrieveByNameType",
+        }
+    }
+
+    /// The endpoint's path beneath the root of the API.
+    pub fn path(&self) -> String {
+        let escape = |name| utf8_percent_encode(name, NAME_ESCAPE);
+        let operation = self.operation();
+        match *self {
+            Endpoint::Ping | Endpoint::ListDomains => operation.to_string(),
+            Endpoint::DnsCreate { domain } | Endpoint::DnsRetrieve { domain } => {
+                format!("{operation}/{}", escape(domain))
+            }
+            Endpoint::DnsEdit { domain, id }
+            | Endpoint::DnsDelete { domain, id }
+            | Endpoint::DnsRetrieveRecord { domain, id } => {
+                format!("{operation}/{}/{id}", escape(domain))
+            }
+            Endpoint::DnsEditByNameType {
+                domain,
+                ty,
+                subdomain,
+            }
+            | Endpoint::DnsDeleteByNameType {
+                domain,
+                ty,
+                subdomain,
+            }
+            | Endpoint::DnsRetrieveByNameType {
+                domain,
+                ty,
+                subdomain,
+            } => format!(
+                "{operation}/{}/{ty}/{}",
+                escape(domain),
+                escape(subdomain.unwrap_or(""))
+            ),
+        }
+    }
+
+    /// The endpoint's url beneath `base`, such as [API_URL].
+    pub fn url(&self, base: &str) -> String {
+        format!("{}/{}", base.trim_end_matches('/'), self.path())
+    }
+}
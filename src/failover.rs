@@ -0,0 +1,196 @@
+//! Keeping a record pointed at whichever of two addresses is healthy.
+//!
+//! The primary address is probed on every [Failover::step]. Once it has failed enough
+//! consecutive probes the record is moved to the backup, and once it has passed enough
+//! consecutive probes it is moved back. Neither happens within the hold-down time of the
+//! previous switch, so a flapping primary can't cause a flapping record.
+//!
+//! ```no_run
+//! use porkbun::failover::{Failover, Probe};
+//! use std::net::Ipv4Addr;
+//!
+//! let client = porkbun::Client::open_keys("keys.json").unwrap();
+//! let mut failover = Failover::new(
+//!     Some("www"),
+//!     Ipv4Addr::new(203, 0, 113, 1).into(),
+//!     Ipv4Addr::new(203, 0, 113, 2).into(),
+//!     Probe::Tcp { port: 443 },
+//! );
+//! loop {
+//!     if let Err(msg) = failover.step(&client, "example.com") {
+//!         eprintln!("failed to update record: {msg}");
+//!     }
+//!     std::thread::sleep(std::time::Duration::from_secs(30));
+//! }
+//! ```
+
+use crate::clock::{Clock, SystemClock};
+use crate::{AddressUpdate, Client, Result};
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How an address's health is checked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Probe {
+    /// Healthy if a TCP connection can be opened.
+    Tcp { port: u16 },
+    /// Healthy if a GET request to `http://{address}:{port}{path}` returns a success status.
+    Http { port: u16, path: String },
+}
+
+impl Probe {
+    /// Returns true if `address` is healthy.
+    pub fn check(&self, address: IpAddr, timeout: Duration) -> bool {
+        match self {
+            Probe::Tcp { port } => {
+                TcpStream::connect_timeout(&SocketAddr::new(address, *port), timeout).is_ok()
+            }
+            Probe::Http { port, path } => {
+                let url = format!("http://{}{path}", SocketAddr::new(address, *port));
+                reqwest::blocking::Client::builder()
+                    .timeout(timeout)
+                    .build()
+                    .and_then(|client| client.get(url).send())
+                    .is_ok_and(|response| response.status().is_success())
+            }
+        }
+    }
+}
+
+/// A record which fails over from a primary address to a backup.
+///
+/// Both addresses must belong to the same family.
+#[derive(Clone, Debug)]
+pub struct Failover {
+    subdomain: Option<String>,
+    primary: IpAddr,
+    backup: IpAddr,
+    probe: Probe,
+    timeout: Duration,
+    fall: u32,
+    rise: u32,
+    hold_down: Duration,
+    clock: Arc<dyn Clock>,
+
+    on_primary: bool,
+    failures: u32,
+    successes: u32,
+    last_switch: Option<Instant>,
+}
+
+impl Failover {
+    /// Starts out pointing at the primary, failing over after 3 failed probes and returning
+    /// after 3 passed probes, with a hold-down time of 5 minutes and a probe timeout of 5 seconds.
+    ///
+    /// # Panics
+    ///
+    /// If `primary` and `backup` belong to different families, since failing over would then
+    /// write a record of the other type and leave the original pointing at the primary.
+    pub fn new(subdomain: Option<&str>, primary: IpAddr, backup: IpAddr, probe: Probe) -> Self {
+        assert_eq!(
+            primary.is_ipv6(),
+            backup.is_ipv6(),
+            "failover addresses {primary} and {backup} belong to different families"
+        );
+        Self {
+            subdomain: subdomain.map(str::to_string),
+            primary,
+            backup,
+            probe,
+            timeout: Duration::from_secs(5),
+            fall: 3,
+            rise: 3,
+            hold_down: Duration::from_secs(300),
+            clock: Arc::new(SystemClock),
+            on_primary: true,
+            failures: 0,
+            successes: 0,
+            last_switch: None,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// How many consecutive failed probes move the record to the backup.
+    pub fn with_fall(mut self, fall: u32) -> Self {
+        self.fall = fall.max(1);
+        self
+    }
+
+    /// How many consecutive passed probes move the record back to the primary.
+    pub fn with_rise(mut self, rise: u32) -> Self {
+        self.rise = rise.max(1);
+        self
+    }
+
+    /// The minimum time between two switches.
+    pub fn with_hold_down(mut self, hold_down: Duration) -> Self {
+        self.hold_down = hold_down;
+        self
+    }
+
+    /// Measures the hold-down time with `clock`, such as a
+    /// [MockClock](crate::clock::MockClock) in tests.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// The address the record should currently point at.
+    pub fn active(&self) -> IpAddr {
+        if self.on_primary {
+            self.primary
+        } else {
+            self.backup
+        }
+    }
+
+    /// Probes the primary and decides which address should be active, without touching
+    /// any records.
+    ///
+    /// The backup is only probed before failing over to it; if it is also unhealthy,
+    /// the record stays on the primary.
+    pub fn check(&mut self) -> IpAddr {
+        if self.probe.check(self.primary, self.timeout) {
+            self.successes = self.successes.saturating_add(1);
+            self.failures = 0;
+        } else {
+            self.failures = self.failures.saturating_add(1);
+            self.successes = 0;
+        }
+
+        let held = self
+            .last_switch
+            .is_some_and(|x| self.clock.now().saturating_duration_since(x) < self.hold_down);
+        let switch = if self.on_primary {
+            self.failures >= self.fall && !held && self.probe.check(self.backup, self.timeout)
+        } else {
+            self.successes >= self.rise && !held
+        };
+        if switch {
+            self.on_primary = !self.on_primary;
+            self.last_switch = Some(self.clock.now());
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                "failing over from {} to {}",
+                if self.on_primary {
+                    self.backup
+                } else {
+                    self.primary
+                },
+                self.active()
+            );
+        }
+        self.active()
+    }
+
+    /// Runs [Failover::check] and points the record at the active address.
+    pub fn step(&mut self, client: &Client, domain: &str) -> Result<AddressUpdate> {
+        let address = self.check();
+        client.ensure_address(domain, self.subdomain.as_deref(), address, None)
+    }
+}
@@ -0,0 +1,155 @@
+//! Detecting the public address of this machine, such as for dynamic DNS.
+
+use crate::{Client, Error, Result};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+
+/// Somewhere to ask for this machine's public ip address.
+///
+/// A single service which is broken or compromised could point a domain anywhere,
+/// so several can be combined with [IpSource::consensus]:
+/// ```no_run
+/// use porkbun::ip::IpSource;
+///
+/// let source = IpSource::consensus(
+///     [
+///         IpSource::Porkbun,
+///         IpSource::Http("https://icanhazip.com".into()),
+///         IpSource::Http("https://api64.ipify.org".into()),
+///     ],
+///     2,
+/// );
+/// # let client = porkbun::Client::open_keys("keys.json")?;
+/// let address = source.ipv4(&client)?;
+/// # Ok::<(), porkbun::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum IpSource {
+    /// Porkbun's ping endpoint, which also tests authentication.
+    #[default]
+    Porkbun,
+    /// A service which responds with the address in plain text, such as `https://icanhazip.com`.
+    ///
+    /// The service must be reachable over both ipv4 and ipv6 to detect both addresses.
+    Http(String),
+    /// Several sources, of which at least `quorum` must report the same address.
+    Consensus {
+        sources: Vec<IpSource>,
+        quorum: usize,
+    },
+}
+
+impl IpSource {
+    pub fn consensus(sources: impl IntoIterator<Item = IpSource>, quorum: usize) -> Self {
+        IpSource::Consensus {
+            sources: sources.into_iter().collect(),
+            quorum,
+        }
+    }
+
+    /// Returns the public ipv4 address, or `None` if there isn't one.
+    pub fn ipv4(&self, client: &Client) -> Result<Option<Ipv4Addr>> {
+        match self.detect(client, false) {
+            Ok(Some(IpAddr::V4(ip))) => Ok(Some(ip)),
+            Ok(Some(IpAddr::V6(ip))) => Err(Error::UnexpectedIpv6(ip)),
+            Ok(None) => Ok(None),
+            Err(msg) => Err(msg),
+        }
+    }
+
+    /// Returns the public ipv6 address, or `None` if there isn't one.
+    pub fn ipv6(&self, client: &Client) -> Result<Option<Ipv6Addr>> {
+        match self.detect(client, true) {
+            Ok(Some(IpAddr::V4(ip))) => Err(Error::UnexpectedIpv4(ip)),
+            Ok(Some(IpAddr::V6(ip))) => Ok(Some(ip)),
+            Ok(None) => Ok(None),
+            Err(msg) => Err(msg),
+        }
+    }
+
+    fn detect(&self, client: &Client, ipv6: bool) -> Result<Option<IpAddr>> {
+        match self {
+            IpSource::Porkbun if ipv6 => Ok(client.ping_ipv6()?.map(IpAddr::V6)),
+            IpSource::Porkbun => Ok(client.ping_ipv4()?.map(IpAddr::V4)),
+            IpSource::Http(url) => http_detect(url, ipv6),
+            IpSource::Consensus { sources, quorum } => {
+                // Each source is an independent round trip, so ask them all at once.
+                let answers: Vec<_> = std::thread::scope(|scope| {
+                    let handles: Vec<_> = sources
+                        .iter()
+                        .map(|x| scope.spawn(move || x.detect(client, ipv6)))
+                        .collect();
+                    handles
+                        .into_iter()
+                        .map(|x| x.join().unwrap_or(Ok(None)))
+                        .collect()
+                });
+
+                // Failed sources don't vote.
+                let mut votes: Vec<(Option<IpAddr>, usize)> = Vec::new();
+                for answer in answers {
+                    let answer = match answer {
+                        Ok(answer) => answer,
+                        Err(_msg) => {
+                            #[cfg(feature = "tracing")]
+                            tracing::warn!("ip source failed: {_msg}");
+                            continue;
+                        }
+                    };
+                    match votes.iter_mut().find(|x| x.0 == answer) {
+                        Some((_, count)) => *count += 1,
+                        None => votes.push((answer, 1)),
+                    }
+                }
+                match votes.iter().find(|x| x.1 >= *quorum) {
+                    Some((answer, _)) => Ok(*answer),
+                    None => Err(Error::NoConsensus {
+                        quorum: *quorum,
+                        answers: votes.into_iter().map(|x| x.0).collect(),
+                    }),
+                }
+            }
+        }
+    }
+}
+
+/// Parses `porkbun` or the url of a plain text service.
+impl std::str::FromStr for IpSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "porkbun" => Ok(IpSource::Porkbun),
+            url if url.starts_with("https://") || url.starts_with("http://") => {
+                Ok(IpSource::Http(url.to_string()))
+            }
+            _ => Err(format!(
+                "unknown ip source \"{s}\"; expected porkbun or an http(s):// url"
+            )),
+        }
+    }
+}
+
+/// Asks a plain text service for the address, connecting over ipv6 if `ipv6` is set.
+fn http_detect(url: &str, ipv6: bool) -> Result<Option<IpAddr>> {
+    // Binding to an unspecified address of one family forces the connection to use it.
+    let local_address = match ipv6 {
+        true => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        false => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+    };
+    let response = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .local_address(local_address)
+        .build()?
+        .get(url)
+        .send()?
+        .error_for_status()?
+        .text()?;
+    match response.trim().parse() {
+        Ok(ip) => Ok(Some(ip)),
+        Err(_) => Err(Error::MalformedIpResponse {
+            url: url.to_string(),
+            response,
+        }),
+    }
+}
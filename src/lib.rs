@@ -1,15 +1,138 @@
 #![warn(clippy::unwrap_used)]
 
+use endpoints::Endpoint;
+use std::collections::HashMap;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+mod access;
+mod acme;
+mod alias;
+mod audit;
+mod builder;
+mod bulk;
+mod caa;
+mod call_info;
+mod canonical;
+pub mod clock;
+#[cfg(feature = "compat")]
+pub mod compat;
+mod conflict;
+#[cfg(unix)]
+pub mod coordinator;
+#[cfg(feature = "keyring")]
+mod credentials;
+mod deadline;
+mod dmarc;
+mod domain;
+mod domains;
+#[cfg(feature = "encryption")]
+mod encrypted;
+pub mod endpoints;
+pub mod failover;
+pub mod ip;
+mod lock;
+mod mta_sts;
+mod ownership;
+mod plan;
+pub mod pool;
+pub mod preset;
+mod report;
+pub mod resolve;
+pub mod retry;
+mod rotate;
+#[cfg(feature = "signing")]
+pub mod signing;
+mod snapshot;
+mod spec;
+pub mod spf;
+mod svcb;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod transaction;
+mod ttl;
+mod txt;
+mod zone;
+
+pub use access::Access;
+pub use acme::{challenge_name, Dns01Challenge};
+pub use audit::{AuditEntry, AuditSink, JsonlSink, MemorySink, Operation};
+pub use builder::ClientBuilder;
+pub use caa::{CaaPolicy, CaaPosture};
+pub use call_info::{CallInfo, EndpointPolicy, RateLimit};
+pub use canonical::canonicalize;
+pub use deadline::Deadline;
+pub use dmarc::{Disposition, DmarcPolicy};
+pub use domain::DomainName;
+pub use domains::{DomainInfo, RecordStream};
+#[cfg(feature = "encryption")]
+pub use encrypted::PassphraseSource;
+pub use lock::{ZoneLock, LOCK_NAME};
+pub use mta_sts::{MtaStsPolicy, StsMode, TlsRpt};
+pub use plan::{ChangeSet, ConflictStrategy, PlanRenderer, RecordEdit, SyncOptions};
+pub use report::ZoneReport;
+pub use snapshot::{ChangeSummary, ZoneSnapshot};
+pub use spec::{RecordSpec, SpecProblem, SpecSet, WildcardSpec, ZoneSpec};
+pub use svcb::{SvcParam, SvcbRecord};
+pub use transaction::{Change, ChangeOutcome, TransactionReport};
+pub use ttl::{Ttl, TtlPolicy};
+pub use zone::{Filter, Zone};
+
+/// A connection to the porkbun API.
+///
+/// Cloning is cheap: clones share one connection pool, along with the keys and settings,
+/// so a daemon can give each worker thread its own clone without repeating TLS handshakes.
+/// Clones also share the rate limit: once one is told it's used up, all of them wait.
+/// ```no_run
+/// let client = porkbun::Client::open_keys("keys.json")?;
+/// let workers: Vec<_> = ["example.com", "example.org"]
+///     .into_iter()
+///     .map(|domain| {
+///         let client = client.clone();
+///         std::thread::spawn(move || client.fetch_records(domain))
+///     })
+///     .collect();
+/// for worker in workers {
+///     println!("{} records", worker.join().unwrap()?.len());
+/// }
+/// # Ok::<(), porkbun::Error>(())
+/// ```
 #[derive(Clone, Debug)]
 pub struct Client {
+    /// Shares its connection pool with its clones.
     client: reqwest::blocking::Client,
-    key_file: String,
-    secret_api_key: String,
-    api_key: String,
+    key_file: Arc<str>,
+    secret_api_key: Arc<str>,
+    api_key: Arc<str>,
+    audit: Option<Arc<dyn AuditSink>>,
+    backoff: retry::Backoff,
+    call_hook: Option<call_info::CallHook>,
+    ttl_policy: TtlPolicy,
+    parse_mode: ParseMode,
+    /// The ownership marker written to the notes of records this client creates or edits.
+    owner: Option<String>,
+    /// Who makes this client's changes, as recorded in its [AuditEntry]s.
+    actor: Option<String>,
+    /// Overrides of the timeout and backoff for particular endpoints, such as `dns/create`.
+    endpoint_policies: Arc<HashMap<String, EndpointPolicy>>,
+    pings: Arc<Mutex<Pings>>,
+    /// When every clone may send requests again, after one was told the rate limit is used up,
+    /// so that clones share one budget rather than each spending it in turn.
+    paused_until: Arc<Mutex<Option<std::time::Instant>>>,
+    timeout: Option<Duration>,
+    deadline: Option<Deadline>,
+    clock: Arc<dyn clock::Clock>,
+    /// The socket of the [coordinator::Coordinator] asked before each request.
+    #[cfg(unix)]
+    coordinator: Option<Arc<std::path::Path>>,
+    api_url: Arc<str>,
+    ipv4_api_url: Arc<str>,
 }
 
+/// The last address each ping url returned, and when.
+type Pings = HashMap<String, (std::time::Instant, Option<IpAddr>)>;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
@@ -20,11 +143,25 @@ pub enum Error {
     Json(#[from] serde_json::Error),
     #[error("porkbun API returned an error: \"{message}\"")]
     Api { message: String },
+    /// Porkbun rejected the request because too many were made recently.
+    ///
+    /// This is only returned once the client's [retry::Backoff] gives up.
+    #[error("porkbun API rate limit exceeded{}", match retry_after {
+        Some(delay) => format!(" (retry after {}s)", delay.as_secs()),
+        None => String::new(),
+    })]
+    RateLimited { retry_after: Option<Duration> },
     #[error("porkbun API returned an unrecognized response ({response})")]
     MalformedApi { response: String },
-    #[error("porkbun API returned an unrecognized response ({response}): {error}")]
+    /// `pointer` is the JSON pointer to the part of the response which couldn't be parsed,
+    /// such as `/records/2/ttl`.
+    #[error("porkbun API returned an unrecognized response ({response}): {error}{}", match pointer.as_str() {
+        "" => String::new(),
+        pointer => format!(" at {pointer}"),
+    })]
     MalformedApiSerde {
         error: serde_json::Error,
+        pointer: String,
         response: String,
     },
 
@@ -32,10 +169,205 @@ pub enum Error {
     UnexpectedIpv4(Ipv4Addr),
     #[error("porkbun API returned an ipv6 address ({0}) when an ipv4 was expected")]
     UnexpectedIpv6(Ipv6Addr),
+
+    #[error("{url} returned an unrecognized ip address ({response})")]
+    MalformedIpResponse { url: String, response: String },
+    /// `answers` holds each distinct address which was reported, where `None` means a source
+    /// reported having no address.
+    #[error("fewer than {quorum} ip sources agreed on an address (got {answers:?})")]
+    NoConsensus {
+        quorum: usize,
+        answers: Vec<Option<IpAddr>>,
+    },
+
+    #[error("ttl of {ttl}s is below porkbun's minimum of {minimum}s")]
+    TtlTooLow { ttl: Ttl, minimum: Ttl },
+
+    /// CNAME records can't coexist with the SOA and NS records every zone apex has;
+    /// an ALIAS record should be used instead, such as with [Client::set_apex_alias].
+    #[error("a CNAME record can't be placed at the apex of {domain}; use an ALIAS record instead")]
+    CnameAtApex { domain: String },
+    /// `records` are the existing records which a new `ty` record at `name` would clash with.
+    #[error("a {ty} record at {name} would conflict with {} existing record(s): {}", records.len(), records.iter().map(|x| format!("{} {}", x.ty, x.content)).collect::<Vec<_>>().join(", "))]
+    Conflict {
+        name: String,
+        ty: RecordType,
+        records: Vec<DnsRecord>,
+    },
+    #[error("the {ty} record at {name} {reason}")]
+    SpecConflict {
+        name: String,
+        ty: RecordType,
+        reason: &'static str,
+    },
+    /// Returned by [ChangeSet::diff_with] for types whose strategy is
+    /// [ConflictStrategy::Fail].
+    #[error("the live {ty} record at {name} {reason}, but its conflict strategy is fail")]
+    SyncConflict {
+        name: String,
+        ty: RecordType,
+        reason: &'static str,
+    },
+    #[error("invalid {ty} record \"{content}\": {reason}")]
+    InvalidContent {
+        ty: RecordType,
+        content: String,
+        reason: &'static str,
+    },
+    #[error("\"{domain}\" is not a valid domain name")]
+    InvalidDomain { domain: String },
+    #[error("invalid ALIAS target \"{target}\": {reason}")]
+    InvalidAliasTarget {
+        target: String,
+        reason: &'static str,
+    },
+    #[error("invalid HTTPS/SVCB record \"{content}\": {reason}")]
+    InvalidSvcb {
+        content: String,
+        reason: &'static str,
+    },
+    #[error("no value was given for the variable ${{{name}}}")]
+    UndefinedVariable { name: String },
+    #[error("invalid template \"{text}\": {reason}")]
+    InvalidTemplate { text: String, reason: &'static str },
+    #[error("invalid MX pattern \"{pattern}\": {reason}")]
+    InvalidMxPattern {
+        pattern: String,
+        reason: &'static str,
+    },
+    #[error("invalid report address \"{uri}\": {reason}")]
+    InvalidReportUri { uri: String, reason: &'static str },
+    #[error("no record block is named \"{name}\"")]
+    UnknownBlock { name: String },
+
+    #[error("no domain in this account contains {name}")]
+    NoZone { name: String },
+    #[error("{domain} has no record with id {id}")]
+    NoRecord { domain: String, id: RecordId },
+    /// API access is switched off for this domain in the Porkbun dashboard, which it is
+    /// for every domain until enabled.
+    #[error("API access is not enabled for {domain}")]
+    ApiAccessDisabled { domain: String },
+
+    /// The key file can be read or written by users other than its owner, which
+    /// [ClientBuilder::allow_insecure_key_file] permits.
+    #[error(
+        "key file permissions {mode:o} are too open; it must not be accessible by other users"
+    )]
+    InsecureKeyFile { mode: u32 },
+    #[cfg(feature = "keyring")]
+    #[error(transparent)]
+    Keyring(#[from] keyring::Error),
+    #[cfg(feature = "encryption")]
+    #[error("failed to decrypt key file: {0}")]
+    Decrypt(#[from] age::DecryptError),
+    #[error("invalid key: {reason}")]
+    InvalidKey { reason: String },
+    #[error("signature doesn't match; the snapshot may have been tampered with")]
+    BadSignature,
+
+    #[error("managed records can't be identified because no owner was set")]
+    NoOwner,
+
+    #[error("{ty} records can't be looked up with the system resolver")]
+    UnsupportedLookup { ty: RecordType },
+    #[error("DNS server failed to resolve {name} (rcode {rcode})")]
+    ResolveFailed { name: String, rcode: u16 },
+    #[error("{pending} resolver(s) still hadn't caught up with the {ty} record at {name} when the time ran out")]
+    PropagationTimeout {
+        name: String,
+        ty: RecordType,
+        pending: usize,
+    },
+
+    /// Someone else holds the [ZoneLock] on the domain.
+    #[error("{domain} is locked by \"{holder}\"")]
+    ZoneLocked { domain: String, holder: String },
+
+    #[error("deadline passed or was cancelled before the request was made")]
+    DeadlineExceeded,
+
+    #[cfg(feature = "compat")]
+    #[error("the {operation} endpoint {reason}")]
+    UnsupportedEndpoint {
+        operation: String,
+        reason: &'static str,
+    },
+
+    /// A change applied by [Client::apply_transaction] couldn't be reverted because the
+    /// transaction didn't keep what undoing it needs.
+    #[error("can't revert change: {reason}")]
+    Irreversible { reason: String },
+
+    /// `created` is the id of the record the change created, if any, so that it can still be
+    /// found or removed.
+    #[error("change was applied but could not be recorded in the audit log: {error}")]
+    Audit {
+        #[source]
+        error: std::io::Error,
+        created: Option<RecordId>,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+impl Error {
+    /// A suggestion for fixing the error, for the errors whose cause isn't obvious from the
+    /// message alone.
+    ///
+    /// ```
+    /// let error = porkbun::Error::Api {
+    ///     message: "Invalid domain.".to_string(),
+    /// };
+    /// assert!(error.explanation().unwrap().contains("dashboard"));
+    /// ```
+    pub fn explanation(&self) -> Option<&'static str> {
+        match self {
+            Error::Api { message } => {
+                let message = message.to_ascii_lowercase();
+                if message.contains("invalid domain") {
+                    Some("API access may not be enabled for this domain; enable it in the Porkbun dashboard")
+                } else if message.contains("all api requests require")
+                    || message.contains("invalid api key")
+                {
+                    Some("the API key or secret key is missing or wrong; check the key file, and that API access is enabled for the account")
+                } else {
+                    None
+                }
+            }
+            Error::RateLimited { .. } => Some(
+                "too many requests were made recently; wait a minute, or use fewer parallel jobs",
+            ),
+            Error::TtlTooLow { .. } => {
+                Some("use a ttl of at least 600s, or a TtlPolicy which raises lower ones")
+            }
+            Error::ApiAccessDisabled { .. } => Some(
+                "enable API access for the domain in the Porkbun dashboard, under the domain's details",
+            ),
+            Error::InsecureKeyFile { .. } => {
+                Some("run `chmod 600` on the key file, so that only its owner can read it")
+            }
+            #[cfg(feature = "keyring")]
+            Error::Keyring(keyring::Error::NoEntry) => {
+                Some("no keys are saved under this service and account in the keyring yet")
+            }
+            Error::ZoneLocked { .. } => Some(
+                "wait for the other run to finish, or for its _porkbun-lock record to expire",
+            ),
+            Error::MalformedApi { .. } | Error::MalformedApiSerde { .. } => Some(
+                "porkbun may have changed its responses; `porkbun debug capture` saves redacted copies to attach to a bug report",
+            ),
+            Error::NoZone { .. } => Some(
+                "the domain may belong to another account, or API access may not be enabled for it",
+            ),
+            Error::Request(error) if error.is_timeout() || error.is_connect() => {
+                Some("porkbun could not be reached; check the network connection")
+            }
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub enum Status {
     #[serde(rename = "SUCCESS")]
@@ -44,7 +376,8 @@ pub enum Status {
     Error,
 }
 
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum RecordType {
     #[serde(rename = "A")]
     A,
@@ -72,9 +405,129 @@ pub enum RecordType {
     Svcb,
 }
 
+impl RecordType {
+    /// The name porkbun uses for this type, such as `"AAAA"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RecordType::A => "A",
+            RecordType::Mx => "MX",
+            RecordType::Cname => "CNAME",
+            RecordType::Alias => "ALIAS",
+            RecordType::Txt => "TXT",
+            RecordType::Ns => "NS",
+            RecordType::Aaaa => "AAAA",
+            RecordType::Srv => "SRV",
+            RecordType::Tlsa => "TLSA",
+            RecordType::Caa => "CAA",
+            RecordType::Https => "HTTPS",
+            RecordType::Svcb => "SVCB",
+        }
+    }
+}
+
+impl std::fmt::Display for RecordType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parses a type's name, ignoring case.
+impl std::str::FromStr for RecordType {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        const TYPES: [RecordType; 12] = [
+            RecordType::A,
+            RecordType::Mx,
+            RecordType::Cname,
+            RecordType::Alias,
+            RecordType::Txt,
+            RecordType::Ns,
+            RecordType::Aaaa,
+            RecordType::Srv,
+            RecordType::Tlsa,
+            RecordType::Caa,
+            RecordType::Https,
+            RecordType::Svcb,
+        ];
+        TYPES
+            .into_iter()
+            .find(|x| x.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown record type \"{s}\""))
+    }
+}
+
+/// The unique id porkbun assigns to each DNS record.
+///
+/// Porkbun encodes these as either strings or numbers depending on the endpoint,
+/// so both are accepted when deserializing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize)]
+#[serde(transparent)]
+pub struct RecordId(pub u64);
+
+impl std::fmt::Display for RecordId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::str::FromStr for RecordId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl From<u64> for RecordId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for RecordId {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = RecordId;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a record id as an integer or string")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, id: u64) -> std::result::Result<RecordId, E> {
+                Ok(RecordId(id))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, id: &str) -> std::result::Result<RecordId, E> {
+                id.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// What a [Client] does with a fetched record it can't parse, such as one of an unknown type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Fail the whole fetch.
+    #[default]
+    Strict,
+    /// Leave the record out of the result. [Client::fetch_records_lenient] returns such
+    /// records alongside the rest.
+    Lenient,
+}
+
+/// A record skipped because it couldn't be parsed, as it was returned, and why.
+pub type SkippedRecord = (serde_json::Value, Error);
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct DnsRecord {
-    pub id: String,
+    pub id: RecordId,
     pub name: String,
     #[serde(rename = "type")]
     pub ty: RecordType,
@@ -84,11 +537,125 @@ pub struct DnsRecord {
     pub notes: Option<String>,
 }
 
+/// The contents of a record to be created, or to replace an existing record.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct NewRecord {
+    /// The subdomain of the record, or `None` for the domain itself.
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub ty: RecordType,
+    pub content: String,
+    pub ttl: Option<Ttl>,
+    pub prio: Option<String>,
+}
+
+impl NewRecord {
+    /// Copies an existing record of `domain`, such that creating it reproduces the original.
+    pub fn from_existing(domain: &str, record: &DnsRecord) -> Self {
+        Self {
+            name: subdomain_of(&record.name, domain).map(str::to_string),
+            ty: record.ty,
+            content: record.content.clone(),
+            ttl: record.ttl.parse().ok(),
+            prio: Some(record.prio.clone()),
+        }
+    }
+}
+
+/// Returns the part of a fully-qualified record name before `domain`,
+/// or `None` if the record is at the domain itself.
+pub(crate) fn subdomain_of<'a>(name: &'a str, domain: &str) -> Option<&'a str> {
+    name.strip_suffix(domain)
+        .and_then(|x| x.strip_suffix('.'))
+        .filter(|x| !x.is_empty())
+}
+
+/// Replaces `from` with `to` wherever it appears in `text` as whole labels or fields, ignoring
+/// ASCII case, so that `example.com` is replaced in `www.example.com.` and `ip4:203.0.113.1`
+/// but not in `myexample.com`, `example.com.au`, or `203.0.113.10`.
+pub(crate) fn replace_whole(text: &str, from: &str, to: &str) -> String {
+    if from.is_empty() {
+        return text.to_string();
+    }
+    // Characters which continue a label, address, or other field.
+    let joins = |x: u8| x.is_ascii_alphanumeric() || matches!(x, b'-' | b'_');
+    let bytes = text.as_bytes();
+    let bounded = |start: usize, end: usize| {
+        let before = start.checked_sub(1).map(|i| bytes[i]);
+        let after = bytes.get(end).copied();
+        let after_next = bytes.get(end + 1).copied();
+        !before.is_some_and(joins)
+            && match after {
+                None => true,
+                // A trailing dot ends a name, while one followed by a label extends it.
+                Some(b'.') => !after_next.is_some_and(joins),
+                Some(x) => !joins(x) && x != b':',
+            }
+    };
+
+    let lower = text.to_ascii_lowercase();
+    let from_lower = from.to_ascii_lowercase();
+    let mut replaced = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut search = 0;
+    while let Some(i) = lower[search..].find(&from_lower) {
+        let start = search + i;
+        let end = start + from.len();
+        if bounded(start, end) {
+            replaced += &text[copied..start];
+            replaced += to;
+            copied = end;
+            search = end;
+        } else {
+            search = start + lower[start..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    replaced += &text[copied..];
+    replaced
+}
+
+/// Fails if HTTPS or SVCB content mixes alias and service modes, which clients ignore.
+pub(crate) fn check_svcb(ty: RecordType, content: &str, prio: Option<&str>) -> Result<()> {
+    if matches!(ty, RecordType::Https | RecordType::Svcb) {
+        SvcbRecord::parse(prio.unwrap_or_default(), content)?;
+    }
+    Ok(())
+}
+
+/// Fails if a CNAME record would be placed at the apex of `domain`.
+pub(crate) fn check_apex_cname(
+    domain: &str,
+    ty: RecordType,
+    subdomain: Option<&str>,
+) -> Result<()> {
+    if ty == RecordType::Cname && subdomain.is_none_or(str::is_empty) {
+        return Err(Error::CnameAtApex {
+            domain: domain.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Returns the subdomain of a wildcard record beneath `parent`,
+/// or beneath the domain itself if `parent` is `None`.
+///
+/// The result can be passed as the subdomain of any record method.
+/// ```
+/// assert_eq!(porkbun::wildcard(None), "*");
+/// assert_eq!(porkbun::wildcard(Some("dev")), "*.dev");
+/// ```
+pub fn wildcard(parent: Option<&str>) -> String {
+    match parent {
+        Some(parent) => format!("*.{parent}"),
+        None => "*".to_string(),
+    }
+}
+
 macro_rules! typed_record {
     ($name:ident, $field:ident, $type:ty) => {
         #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
         pub struct $name {
-            pub id: String,
+            pub id: RecordId,
             pub name: String,
             #[serde(rename = "content")]
             pub $field: $type,
@@ -101,6 +668,266 @@ macro_rules! typed_record {
 
 typed_record!(Ipv4Record, address, Ipv4Addr);
 typed_record!(Ipv6Record, address, Ipv6Addr);
+typed_record!(TxtRecord, text, String);
+
+/// Requests
+impl Client {
+    /// Sends a request, retrying it while it is rejected by the rate limit.
+    fn post(&self, url: &str, body: String) -> Result<String> {
+        #[cfg(feature = "otel")]
+        let span = tracing::info_span!(
+            "porkbun",
+            otel.name = %CallInfo::endpoint(url),
+            otel.kind = "client",
+            otel.status_code = tracing::field::Empty,
+            http.request.method = "POST",
+            http.response.status_code = tracing::field::Empty,
+            porkbun.endpoint = %CallInfo::endpoint(url),
+            porkbun.domain = CallInfo::domain(url),
+            porkbun.attempt_count = tracing::field::Empty,
+        );
+        #[cfg(feature = "otel")]
+        let _entered = span.enter();
+
+        let endpoint = CallInfo::endpoint(url);
+        let backoff = self.backoff_for(url);
+        let timeout = self
+            .endpoint_policies
+            .get(&endpoint)
+            .and_then(|x| x.timeout)
+            .or(self.timeout);
+
+        let start = self.clock.now();
+        let attempts = std::cell::Cell::new(0);
+        let mut http_status = None;
+        let mut rate_limit = None;
+        let result = retry::retry_with_clock(
+            self.clock.as_ref(),
+            backoff,
+            |attempt| {
+                attempts.set(attempt + 1);
+                self.post_once(
+                    url,
+                    body.clone(),
+                    timeout,
+                    &mut http_status,
+                    &mut rate_limit,
+                )
+            },
+            |error| match error {
+                Error::RateLimited { retry_after } => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!("rate limited by porkbun API");
+                    // Waiting past the deadline would only delay the inevitable error.
+                    let delay = retry_after.unwrap_or(backoff.delay(attempts.get() - 1));
+                    let remaining = self.remaining();
+                    if remaining.is_some_and(|x| x < delay.min(backoff.max_delay)) {
+                        return retry::Retry::Stop;
+                    }
+                    retry_after.map_or(retry::Retry::Backoff, retry::Retry::After)
+                }
+                _ => retry::Retry::Stop,
+            },
+        );
+        let attempts = attempts.get();
+        #[cfg(feature = "otel")]
+        {
+            span.record("porkbun.attempt_count", attempts);
+            if let Some(status) = http_status {
+                span.record("http.response.status_code", status);
+            }
+            span.record(
+                "otel.status_code",
+                if result.is_ok() { "ok" } else { "error" },
+            );
+        }
+
+        if let Some(hook) = &self.call_hook {
+            (hook.0)(&CallInfo {
+                endpoint,
+                duration: self.clock.now().saturating_duration_since(start),
+                attempt_count: attempts,
+                http_status,
+                rate_limit,
+            });
+        }
+        result
+    }
+
+    /// How requests to `url` are retried.
+    fn backoff_for(&self, url: &str) -> &retry::Backoff {
+        self.endpoint_policies
+            .get(&CallInfo::endpoint(url))
+            .and_then(|x| x.backoff.as_ref())
+            .unwrap_or(&self.backoff)
+    }
+
+    /// The time left before the deadline, if there is one.
+    fn remaining(&self) -> Option<Duration> {
+        self.deadline.as_ref()?.remaining_at(self.clock.now())
+    }
+
+    fn post_once(
+        &self,
+        url: &str,
+        body: String,
+        timeout: Option<Duration>,
+        http_status: &mut Option<u16>,
+        rate_limit: &mut Option<RateLimit>,
+    ) -> Result<String> {
+        #[cfg(feature = "tracing")]
+        tracing::info!("POST {url}");
+        *http_status = None;
+        *rate_limit = None;
+        self.wait_for_pause()?;
+        #[cfg(unix)]
+        self.coordinate("acquire")?;
+        let remaining = self.remaining();
+        if remaining == Some(Duration::ZERO) {
+            return Err(Error::DeadlineExceeded);
+        }
+        let mut request = self.client.post(url).body(body);
+        // A request's timeout is the shorter of the client's and the time left, where the
+        // client's defaults to reqwest's 30 seconds.
+        let timeout = match (timeout, remaining) {
+            (Some(timeout), Some(remaining)) => Some(timeout.min(remaining)),
+            (None, Some(remaining)) => Some(remaining.min(Duration::from_secs(30))),
+            (timeout, None) => timeout,
+        };
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+        let response = request.send()?;
+        let status = response.status();
+        *http_status = Some(status.as_u16());
+        *rate_limit = RateLimit::from_headers(response.headers());
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| x.trim().parse().ok())
+            .map(Duration::from_secs);
+        let response = response.text()?;
+        #[cfg(feature = "tracing")]
+        tracing::info!("response: {response}");
+
+        let exhausted = rate_limit.and_then(|x| (x.remaining == Some(0)).then_some(x.reset)?);
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS || is_rate_limit_message(&response) {
+            if let Some(delay) = retry_after.or(exhausted) {
+                self.pause(delay);
+            }
+            return Err(Error::RateLimited { retry_after });
+        }
+        if let Some(delay) = exhausted {
+            self.pause(delay);
+        }
+        if let Some(domain) = CallInfo::domain(url).filter(|_| is_api_access_message(&response)) {
+            return Err(Error::ApiAccessDisabled {
+                domain: domain.to_string(),
+            });
+        }
+        Ok(response)
+    }
+
+    /// Holds off every clone's requests for `delay`, capped at the backoff's maximum delay,
+    /// along with those of the coordinator's other clients.
+    fn pause(&self, delay: Duration) {
+        let delay = delay.min(self.backoff.max_delay);
+        #[cfg(unix)]
+        self.coordinate(&format!("pause {}", delay.as_millis()))
+            .ok();
+        let until = self.clock.now() + delay;
+        let mut paused_until = self
+            .paused_until
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        *paused_until = (*paused_until).max(Some(until));
+    }
+
+    /// Waits until requests are no longer paused, failing if that's past the deadline.
+    fn wait_for_pause(&self) -> Result<()> {
+        let paused_until = *self
+            .paused_until
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let Some(paused_until) = paused_until else {
+            return Ok(());
+        };
+        let wait = paused_until.saturating_duration_since(self.clock.now());
+        if wait.is_zero() {
+            return Ok(());
+        }
+        if self.remaining().is_some_and(|x| x < wait) {
+            return Err(Error::DeadlineExceeded);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!("waiting {wait:?} for the rate limit");
+        self.clock.sleep(wait);
+        Ok(())
+    }
+}
+
+/// Parses a response, remembering where in it parsing failed.
+pub(crate) fn parse_response<T: serde::de::DeserializeOwned>(response: String) -> Result<T> {
+    let mut deserializer = serde_json::Deserializer::from_str(&response);
+    let parsed = serde_path_to_error::deserialize(&mut deserializer);
+    match parsed.map(|x| (x, deserializer.end())) {
+        Ok((parsed, Ok(()))) => Ok(parsed),
+        Ok((_, Err(error))) => Err(Error::MalformedApiSerde {
+            error,
+            pointer: String::new(),
+            response,
+        }),
+        Err(error) => Err(Error::MalformedApiSerde {
+            pointer: json_pointer(error.path()),
+            error: error.into_inner(),
+            response,
+        }),
+    }
+}
+
+/// Formats a serde path as a JSON pointer (RFC 6901).
+fn json_pointer(path: &serde_path_to_error::Path) -> String {
+    use serde_path_to_error::Segment;
+
+    let mut pointer = String::new();
+    for segment in path.iter() {
+        match segment {
+            Segment::Seq { index } => pointer += &format!("/{index}"),
+            Segment::Map { key } => {
+                pointer += &format!("/{}", key.replace('~', "~0").replace('/', "~1"))
+            }
+            Segment::Enum { variant } => pointer += &format!("/{variant}"),
+            Segment::Unknown => break,
+        }
+    }
+    pointer
+}
+
+/// The lowercased message of an error response.
+fn error_message(response: &str) -> Option<String> {
+    #[derive(serde::Deserialize)]
+    struct ErrorResponse {
+        status: Status,
+        #[serde(default)]
+        message: String,
+    }
+
+    let response = serde_json::from_str::<ErrorResponse>(response).ok()?;
+    matches!(response.status, Status::Error).then(|| response.message.to_ascii_lowercase())
+}
+
+/// Porkbun reports exceeded rate limits as an ordinary error (often with a 403 or 503 status),
+/// so they can only be told apart by their message.
+fn is_rate_limit_message(response: &str) -> bool {
+    error_message(response)
+        .is_some_and(|x| x.contains("rate limit") || x.contains("too many requests"))
+}
+
+/// Whether a response says the domain hasn't been opted in to API access.
+fn is_api_access_message(response: &str) -> bool {
+    error_message(response).is_some_and(|x| x.contains("not opted in to api access"))
+}
 
 /// Authentication
 impl Client {
@@ -114,36 +941,122 @@ impl Client {
     ///     "apikey": "YOUR_API_KEY",
     /// }
     /// ```
+    ///
+    /// Use a [ClientBuilder] to configure the connection.
     pub fn open_keys(path: impl AsRef<std::path::Path>) -> Result<Self> {
-        #[derive(serde::Deserialize)]
-        struct Keys {
-            #[serde(rename = "apikey")]
-            api: String,
-            #[serde(rename = "secretapikey")]
-            secret_api: String,
-        }
+        ClientBuilder::new().open_keys(path)
+    }
 
-        let key_file = std::fs::read_to_string(path)?;
-        let keys: Keys = serde_json::from_str(&key_file)?;
+    /// Sets how requests rejected by the rate limit are retried.
+    ///
+    /// By default, up to 3 attempts are made.
+    pub fn with_backoff(mut self, backoff: retry::Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
 
-        Ok(Self {
-            client: reqwest::blocking::Client::new(),
-            api_key: keys.api,
-            secret_api_key: keys.secret_api,
-            key_file,
-        })
+    /// Sets what happens to TTLs below porkbun's minimum.
+    ///
+    /// By default, they are rejected.
+    pub fn with_ttl_policy(mut self, policy: TtlPolicy) -> Self {
+        self.ttl_policy = policy;
+        self
+    }
+
+    /// Sets what happens to fetched records which can't be parsed.
+    ///
+    /// By default, they fail the fetch.
+    pub fn with_parse_mode(mut self, mode: ParseMode) -> Self {
+        self.parse_mode = mode;
+        self
+    }
+
+    /// Calls `hook` after every request to the API completes, whether it succeeded or not.
+    ///
+    /// This is intended for collecting latency and error metrics.
+    pub fn with_call_hook(mut self, hook: impl Fn(&CallInfo) + Send + Sync + 'static) -> Self {
+        self.call_hook = Some(call_info::CallHook(Arc::new(hook)));
+        self
+    }
+
+    /// Overrides the [ClientBuilder::timeout] of every request made by this client.
+    ///
+    /// Clients are cheap to clone, so this can be applied to a single slow call:
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # let client = porkbun::Client::open_keys("keys.json")?;
+    /// let records = client
+    ///     .clone()
+    ///     .with_timeout(Duration::from_secs(120))
+    ///     .fetch_records("example.com")?;
+    /// # Ok::<(), porkbun::Error>(())
+    /// ```
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Overrides the timeout or backoff of requests to one `endpoint`, such as `dns/retrieve`
+    /// or `dns/create`, named as in [CallInfo]'s `endpoint`.
+    ///
+    /// Read-only and mutating endpoints often call for different treatment:
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// use porkbun::{retry::Backoff, EndpointPolicy};
+    ///
+    /// let client = porkbun::Client::open_keys("keys.json")?
+    ///     .with_endpoint_policy("dns/retrieve", EndpointPolicy::timeout(Duration::from_secs(120)))
+    ///     .with_endpoint_policy("dns/create", EndpointPolicy::backoff(Backoff::none()));
+    /// # Ok::<(), porkbun::Error>(())
+    /// ```
+    pub fn with_endpoint_policy(mut self, endpoint: &str, policy: EndpointPolicy) -> Self {
+        Arc::make_mut(&mut self.endpoint_policies).insert(endpoint.to_string(), policy);
+        self
+    }
+
+    /// Fails requests with [Error::DeadlineExceeded] once `deadline` expires or is cancelled,
+    /// rather than starting them or waiting out the rate limit.
+    ///
+    /// Requests in flight are cut short by their timeout. [Client::apply_transaction] still
+    /// reverts the changes it applied, so that a domain isn't left half-changed.
+    pub fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Measures time and waits between retries with `clock`, such as a
+    /// [MockClock](clock::MockClock) in tests.
+    pub fn with_clock(mut self, clock: impl clock::Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Records every change made by this client (and its clones) into `sink`.
+    ///
+    /// When enabled, each mutating operation fetches the affected records before and after the
+    /// change, costing up to two extra requests.
+    pub fn with_audit(mut self, sink: impl AuditSink + 'static) -> Self {
+        self.audit = Some(Arc::new(sink));
+        self
+    }
+
+    /// Records `actor`, such as a user or CI job, as the maker of this client's changes in its
+    /// [AuditEntry]s.
+    pub fn with_actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = Some(actor.into());
+        self
     }
 
     /// Tests authentication and returns the ip address used to make the request.
     ///
     /// This will almost always be an ipv6 address. Use [ping_ipv4] to recieve an ipv4 address.
     pub fn ping(&self) -> Result<Option<IpAddr>> {
-        self.ping_url("https://api.porkbun.com/api/json/v3/ping")
+        self.ping_url(&Endpoint::Ping.url(&self.api_url))
     }
 
     /// Tests authentication and returns the ipv4 address used to make the request.
     pub fn ping_ipv4(&self) -> Result<Option<Ipv4Addr>> {
-        match self.ping_url("https://api-ipv4.porkbun.com/api/json/v3/ping") {
+        match self.ping_url(&Endpoint::Ping.url(&self.ipv4_api_url)) {
             Ok(Some(IpAddr::V4(ip))) => Ok(Some(ip)),
             Ok(Some(IpAddr::V6(ip))) => Err(Error::UnexpectedIpv6(ip)),
             Ok(None) => Ok(None),
@@ -153,7 +1066,7 @@ impl Client {
 
     /// Tests authentication and returns the ipv6 address used to make the request.
     pub fn ping_ipv6(&self) -> Result<Option<Ipv6Addr>> {
-        match self.ping_url("https://api.porkbun.com/api/json/v3/ping") {
+        match self.ping_url(&Endpoint::Ping.url(&self.api_url)) {
             Ok(Some(IpAddr::V4(ip))) => Err(Error::UnexpectedIpv4(ip)),
             Ok(Some(IpAddr::V6(ip))) => Ok(Some(ip)),
             Ok(None) => Ok(None),
@@ -161,6 +1074,37 @@ impl Client {
         }
     }
 
+    /// Like [Client::ping], but returns the address from a previous ping by this client or
+    /// its clones if it's younger than `max_age`, such as when several features need the
+    /// address at once.
+    pub fn ping_cached(&self, max_age: Duration) -> Result<Option<IpAddr>> {
+        self.ping_url_cached(&Endpoint::Ping.url(&self.api_url), max_age)
+    }
+
+    /// Like [Client::ping_ipv4], but returns the address from a previous ping if it's younger
+    /// than `max_age`.
+    pub fn ping_ipv4_cached(&self, max_age: Duration) -> Result<Option<Ipv4Addr>> {
+        match self.ping_url_cached(&Endpoint::Ping.url(&self.ipv4_api_url), max_age) {
+            Ok(Some(IpAddr::V4(ip))) => Ok(Some(ip)),
+            Ok(Some(IpAddr::V6(ip))) => Err(Error::UnexpectedIpv6(ip)),
+            Ok(None) => Ok(None),
+            Err(msg) => Err(msg),
+        }
+    }
+
+    fn ping_url_cached(&self, url: &str, max_age: Duration) -> Result<Option<IpAddr>> {
+        let cached = self
+            .pings
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(url)
+            .copied();
+        match cached {
+            Some((at, ip)) if self.clock.now().saturating_duration_since(at) < max_age => Ok(ip),
+            _ => self.ping_url(url),
+        }
+    }
+
     fn ping_url(&self, url: &str) -> Result<Option<IpAddr>> {
         #[derive(Clone, Debug, serde::Deserialize)]
         struct PingResponse {
@@ -171,22 +1115,18 @@ impl Client {
             ip: Option<IpAddr>,
         }
 
-        #[cfg(feature = "tracing")]
-        tracing::info!("POST {url}");
-        let response = self
-            .client
-            .post(url)
-            .body(self.key_file.clone())
-            .send()?
-            .text()?;
-        #[cfg(feature = "tracing")]
-        tracing::info!("response: {response}");
-        let response: PingResponse = serde_json::from_str(&response)
-            .map_err(|error| Error::MalformedApiSerde { error, response })?;
+        let response = self.post(url, self.key_file.to_string())?;
+        let response: PingResponse = parse_response(response)?;
         #[cfg(feature = "tracing_debug")]
         tracing::debug!("parsed response: {response:?}");
         match response.status {
-            Status::Success => Ok(response.ip),
+            Status::Success => {
+                self.pings
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .insert(url.to_string(), (self.clock.now(), response.ip));
+                Ok(response.ip)
+            }
             Status::Error => Err(Error::Api {
                 message: response.message,
             }),
@@ -197,23 +1137,20 @@ impl Client {
 /// Fetch records
 impl Client {
     fn fetch_records_url<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<Vec<T>> {
-        #[cfg(feature = "tracing")]
-        tracing::info!("POST {url}");
-        let response = self
-            .client
-            .post(url)
-            .body(self.key_file.clone())
-            .send()?
-            .text()?;
-        #[cfg(feature = "tracing")]
-        tracing::info!("response: {response}");
-        let object = match serde_json::from_str::<serde_json::Value>(&response) {
-            Ok(object) => object,
-            Err(error) => return Err(Error::MalformedApiSerde { error, response }),
-        };
-        let error = Error::MalformedApi { response };
-        let Some(object) = object.as_object() else {
-            return Err(error);
+        Ok(self.fetch_records_url_with(url, self.parse_mode)?.0)
+    }
+
+    /// Fetches records, returning those skipped by [ParseMode::Lenient] alongside why they
+    /// couldn't be parsed.
+    fn fetch_records_url_with<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        mode: ParseMode,
+    ) -> Result<(Vec<T>, Vec<SkippedRecord>)> {
+        let response = self.post(url, self.key_file.to_string())?;
+        let object: serde_json::Map<String, serde_json::Value> = parse_response(response.clone())?;
+        let malformed = || Error::MalformedApi {
+            response: response.clone(),
         };
         let Some(status) = object
             .get("status")
@@ -224,25 +1161,64 @@ impl Client {
                 _ => None,
             })
         else {
-            return Err(error);
+            return Err(malformed());
         };
         #[cfg(feature = "tracing_debug")]
-        tracing::debug!("parsed response: {response:?}");
+        tracing::debug!("parsed response: {object:?}");
 
         match status {
-            Status::Success => Ok(serde_json::from_value(
-                object.get("records").ok_or(error)?.to_owned(),
-            )?),
+            Status::Success => {
+                let records = object.get("records").ok_or_else(malformed)?.to_owned();
+                match mode {
+                    ParseMode::Strict => serde_path_to_error::deserialize(records)
+                        .map(|records| (records, Vec::new()))
+                        .map_err(|error| Error::MalformedApiSerde {
+                            pointer: format!("/records{}", json_pointer(error.path())),
+                            error: error.into_inner(),
+                            response,
+                        }),
+                    ParseMode::Lenient => {
+                        let serde_json::Value::Array(records) = records else {
+                            return Err(malformed());
+                        };
+                        Ok(self.parse_leniently(records))
+                    }
+                }
+            }
             Status::Error => Err(Error::Api {
                 message: object
                     .get("message")
                     .and_then(serde_json::Value::as_str)
-                    .ok_or(error)?
+                    .ok_or_else(malformed)?
                     .to_string(),
             }),
         }
     }
 
+    fn parse_leniently<T: serde::de::DeserializeOwned>(
+        &self,
+        records: Vec<serde_json::Value>,
+    ) -> (Vec<T>, Vec<SkippedRecord>) {
+        let mut parsed = Vec::with_capacity(records.len());
+        let mut skipped = Vec::new();
+        for record in records {
+            match serde_path_to_error::deserialize(&record) {
+                Ok(record) => parsed.push(record),
+                Err(error) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!("skipping unparseable record {record}: {error}");
+                    let error = Error::MalformedApiSerde {
+                        pointer: json_pointer(error.path()),
+                        error: error.into_inner(),
+                        response: record.to_string(),
+                    };
+                    skipped.push((record, error));
+                }
+            }
+        }
+        (parsed, skipped)
+    }
+
     /// Fetches all DNS records for a given domain.
     ///
     /// These can be of any type and with any name.
@@ -251,7 +1227,41 @@ impl Client {
     /// - [Client::fetch_ipv4_records]
     /// - [Client::fetch_ipv6_records]
     pub fn fetch_records(&self, domain: &str) -> Result<Vec<DnsRecord>> {
-        let url = format!("https://api.porkbun.com/api/json/v3/dns/retrieve/{domain}");
+        let url = Endpoint::DnsRetrieve { domain }.url(&self.api_url);
+        self.fetch_records_url(&url)
+    }
+
+    /// Like [Client::fetch_records], but skips records which can't be parsed whatever the
+    /// client's [ParseMode], returning them alongside why.
+    pub fn fetch_records_lenient(
+        &self,
+        domain: &str,
+    ) -> Result<(Vec<DnsRecord>, Vec<SkippedRecord>)> {
+        let url = Endpoint::DnsRetrieve { domain }.url(&self.api_url);
+        self.fetch_records_url_with(&url, ParseMode::Lenient)
+    }
+
+    /// Fetches a single DNS record by its id.
+    ///
+    /// The result is empty if no record has this id.
+    pub fn fetch_record(&self, domain: &str, id: RecordId) -> Result<Vec<DnsRecord>> {
+        let url = Endpoint::DnsRetrieveRecord { domain, id }.url(&self.api_url);
+        self.fetch_records_url(&url)
+    }
+
+    /// Fetches all DNS records of a given type and subdomain.
+    pub fn fetch_records_by_name_type(
+        &self,
+        domain: &str,
+        ty: RecordType,
+        subdomain: Option<&str>,
+    ) -> Result<Vec<DnsRecord>> {
+        let url = Endpoint::DnsRetrieveByNameType {
+            domain,
+            ty,
+            subdomain,
+        }
+        .url(&self.api_url);
         self.fetch_records_url(&url)
     }
 
@@ -261,11 +1271,12 @@ impl Client {
         domain: &str,
         subdomain: Option<&str>,
     ) -> Result<Vec<Ipv4Record>> {
-        let mut url =
-            format!("https://api.porkbun.com/api/json/v3/dns/retrieveByNameType/{domain}/A/");
-        if let Some(subdomain) = subdomain {
-            url.push_str(subdomain);
+        let url = Endpoint::DnsRetrieveByNameType {
+            domain,
+            ty: RecordType::A,
+            subdomain,
         }
+        .url(&self.api_url);
         self.fetch_records_url(&url)
     }
 
@@ -275,26 +1286,77 @@ impl Client {
         domain: &str,
         subdomain: Option<&str>,
     ) -> Result<Vec<Ipv6Record>> {
-        let mut url =
-            format!("https://api.porkbun.com/api/json/v3/dns/retrieveByNameType/{domain}/AAAA/");
-        if let Some(subdomain) = subdomain {
-            url.push_str(subdomain);
+        let url = Endpoint::DnsRetrieveByNameType {
+            domain,
+            ty: RecordType::Aaaa,
+            subdomain,
         }
+        .url(&self.api_url);
+        self.fetch_records_url(&url)
+    }
+
+    /// Fetches all DNS TXT records for a given domain.
+    ///
+    /// Use [TxtRecord::joined] to read long records which were split into several strings.
+    pub fn fetch_txt_records(
+        &self,
+        domain: &str,
+        subdomain: Option<&str>,
+    ) -> Result<Vec<TxtRecord>> {
+        let url = Endpoint::DnsRetrieveByNameType {
+            domain,
+            ty: RecordType::Txt,
+            subdomain,
+        }
+        .url(&self.api_url);
         self.fetch_records_url(&url)
     }
 }
 
 /// Create records
 impl Client {
+    /// Creates a record, returning its id.
+    ///
+    /// If the connection fails, the request is retried as the [Backoff](retry::Backoff) for
+    /// `dns/create` allows, but only after checking that it wasn't applied anyway; an identical
+    /// record found this way counts as created if it wasn't there before. This costs a request
+    /// to fetch the records with the same name and type first, unless retries are disabled.
     pub fn create_record(
         &self,
         domain: &str,
         name: Option<&str>,
         ty: RecordType,
         content: &str,
-        ttl: Option<&str>,
+        ttl: Option<Ttl>,
         prio: Option<&str>,
-    ) -> Result<Option<u32>> {
+    ) -> Result<Option<RecordId>> {
+        let record = NewRecord {
+            name: name.map(str::to_string),
+            ty,
+            content: content.to_string(),
+            ttl,
+            prio: prio.map(str::to_string),
+        };
+        self.create_record_with_notes(domain, &record, self.owner.as_deref())
+    }
+
+    /// Like [Client::create_record], but gives the record `notes` rather than this client's
+    /// ownership marker.
+    pub fn create_record_with_notes(
+        &self,
+        domain: &str,
+        record: &NewRecord,
+        notes: Option<&str>,
+    ) -> Result<Option<RecordId>> {
+        let NewRecord {
+            name,
+            ty,
+            content,
+            ttl,
+            prio,
+        } = record;
+        let (name, ty, content, prio) = (name.as_deref(), *ty, content.as_str(), prio.as_deref());
+
         #[derive(Clone, Debug, serde::Serialize)]
         struct Body<'a> {
             #[serde(rename = "secretapikey")]
@@ -305,8 +1367,10 @@ impl Client {
             #[serde(rename = "type")]
             pub ty: RecordType,
             pub content: &'a str,
-            pub ttl: Option<&'a str>,
+            pub ttl: Option<Ttl>,
             pub prio: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub notes: Option<&'a str>,
         }
 
         #[derive(Clone, Debug, serde::Deserialize)]
@@ -314,44 +1378,198 @@ impl Client {
             status: Status,
             #[serde(default)]
             message: String,
-            id: Option<u32>,
+            id: Option<RecordId>,
         }
 
-        let url = format!("https://api.porkbun.com/api/json/v3/dns/create/{domain}");
-        #[cfg(feature = "tracing")]
-        tracing::info!("POST {url}");
-        let response = self
-            .client
-            .post(&url)
-            .body(serde_json::to_string(&Body {
-                secret_api: &self.secret_api_key,
-                api: &self.api_key,
-                name,
-                ty,
-                content,
-                ttl,
-                prio,
-            })?)
-            .send()?
-            .text()?;
-        #[cfg(feature = "tracing")]
-        tracing::info!("response: {response}");
-        let response: Response = serde_json::from_str(&response)
-            .map_err(|error| Error::MalformedApiSerde { error, response })?;
+        check_apex_cname(domain, ty, name)?;
+        check_svcb(ty, content, prio)?;
+        let ttl = self.ttl_policy.apply(*ttl)?;
+        let url = Endpoint::DnsCreate { domain }.url(&self.api_url);
+        let body = serde_json::to_string(&Body {
+            secret_api: &self.secret_api_key,
+            api: &self.api_key,
+            name,
+            ty,
+            content,
+            ttl,
+            prio,
+            notes,
+        })?;
+        let backoff = self.backoff_for(&url);
+        // The records which were already there, so that a record found after a failed
+        // connection is known to be the one this request made. If they can't be fetched,
+        // failed connections aren't retried.
+        let before: Option<Vec<RecordId>> = (backoff.max_attempts > 1)
+            .then(|| self.fetch_records_by_name_type(domain, ty, name).ok())
+            .flatten()
+            .map(|records| records.into_iter().map(|x| x.id).collect());
+        let mut attempt = 0;
+        let response = loop {
+            match (self.post(&url, body.clone()), &before) {
+                (Err(Error::Request(_error)), Some(before))
+                    if attempt + 1 < backoff.max_attempts =>
+                {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        "create request failed, checking whether it was applied: {_error}"
+                    );
+                    // The request may have been applied before the connection failed, in which
+                    // case retrying it would create a duplicate.
+                    let existing = self.fetch_records_by_name_type(domain, ty, name)?;
+                    let canonical = canonicalize(ty, content);
+                    let applied = existing.into_iter().find(|x| {
+                        !before.contains(&x.id)
+                            && canonicalize(ty, &x.content) == canonical
+                            && ttl.is_none_or(|ttl| x.ttl == ttl.to_string())
+                            && prio.is_none_or(|prio| x.prio == prio)
+                    });
+                    if let Some(record) = applied {
+                        let id = record.id;
+                        let after = self.audit.as_ref().map(|_| vec![record]);
+                        self.audit_create(domain, id, after)?;
+                        return Ok(Some(id));
+                    }
+                    self.clock.sleep(backoff.delay(attempt));
+                    attempt += 1;
+                }
+                (response, _) => break response?,
+            }
+        };
+        let response: Response = parse_response(response)?;
         #[cfg(feature = "tracing_debug")]
         tracing::debug!("parsed response: {response:?}");
         match response.status {
-            Status::Success => Ok(response.id),
+            Status::Success => {
+                if let Some(id) = response.id {
+                    let after = self.audit_snapshot(|| self.fetch_record(domain, id));
+                    self.audit_create(domain, id, after)?;
+                }
+                Ok(response.id)
+            }
             Status::Error => Err(Error::Api {
                 message: response.message,
             }),
         }
     }
+
+    /// Creates an A record pointing at `address`.
+    pub fn create_a_record(
+        &self,
+        domain: &str,
+        subdomain: Option<&str>,
+        address: Ipv4Addr,
+        ttl: Option<Ttl>,
+    ) -> Result<Option<RecordId>> {
+        let content = address.to_string();
+        self.create_record(domain, subdomain, RecordType::A, &content, ttl, None)
+    }
+
+    /// Creates an AAAA record pointing at `address`.
+    pub fn create_aaaa_record(
+        &self,
+        domain: &str,
+        subdomain: Option<&str>,
+        address: Ipv6Addr,
+        ttl: Option<Ttl>,
+    ) -> Result<Option<RecordId>> {
+        let content = address.to_string();
+        self.create_record(domain, subdomain, RecordType::Aaaa, &content, ttl, None)
+    }
+
+    /// Creates a CNAME record aliasing `subdomain` to the hostname `target`.
+    pub fn create_cname(
+        &self,
+        domain: &str,
+        subdomain: Option<&str>,
+        target: &str,
+        ttl: Option<Ttl>,
+    ) -> Result<Option<RecordId>> {
+        self.create_record(domain, subdomain, RecordType::Cname, target, ttl, None)
+    }
+
+    /// Creates a TXT record, splitting `text` into several strings if it's too long for one.
+    ///
+    /// See [TxtRecord::encode].
+    pub fn create_txt(
+        &self,
+        domain: &str,
+        subdomain: Option<&str>,
+        text: &str,
+        ttl: Option<Ttl>,
+    ) -> Result<Option<RecordId>> {
+        let content = TxtRecord::encode(text);
+        self.create_record(domain, subdomain, RecordType::Txt, &content, ttl, None)
+    }
+
+    /// Creates an MX record directing mail to the hostname `exchange`.
+    ///
+    /// Lower priorities are preferred.
+    pub fn create_mx(
+        &self,
+        domain: &str,
+        subdomain: Option<&str>,
+        exchange: &str,
+        priority: u16,
+        ttl: Option<Ttl>,
+    ) -> Result<Option<RecordId>> {
+        let priority = priority.to_string();
+        self.create_record(
+            domain,
+            subdomain,
+            RecordType::Mx,
+            exchange,
+            ttl,
+            Some(&priority),
+        )
+    }
+
+    /// Creates an HTTPS or SVCB record, depending on `ty`.
+    pub fn create_svcb(
+        &self,
+        domain: &str,
+        subdomain: Option<&str>,
+        ty: RecordType,
+        record: &SvcbRecord,
+        ttl: Option<Ttl>,
+    ) -> Result<Option<RecordId>> {
+        let content = record.content();
+        if !matches!(ty, RecordType::Https | RecordType::Svcb) {
+            return Err(Error::InvalidSvcb {
+                content,
+                reason: "record type must be HTTPS or SVCB",
+            });
+        }
+        let priority = record.priority().to_string();
+        self.create_record(domain, subdomain, ty, &content, ttl, Some(&priority))
+    }
 }
 
 /// Edit records
 impl Client {
-    fn edit_record_url<T: serde::Serialize>(&self, url: &str, ty: &str, content: &T) -> Result<()> {
+    fn edit_by_name_type<T: serde::Serialize>(
+        &self,
+        domain: &str,
+        ty: RecordType,
+        subdomain: Option<&str>,
+        content: &T,
+        ttl: Option<Ttl>,
+        prio: Option<&str>,
+    ) -> Result<()> {
+        #[derive(Clone, Debug, serde::Serialize)]
+        struct Body<'a, T> {
+            #[serde(rename = "secretapikey")]
+            secret_api: &'a str,
+            #[serde(rename = "apikey")]
+            api: &'a str,
+            pub content: &'a T,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub ttl: Option<Ttl>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub prio: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub notes: Option<&'a str>,
+        }
+
         #[derive(Clone, Debug, serde::Deserialize)]
         struct EditDnsRecordResponse {
             status: Status,
@@ -359,54 +1577,379 @@ impl Client {
             message: String,
         }
 
-        let body = format!(
-            "{{\"secretapikey\":{},\"apikey\":{},\"type\":{},\"content\":{}}}",
-            self.secret_api_key,
-            self.api_key,
+        check_apex_cname(domain, ty, subdomain)?;
+        let ttl = self.ttl_policy.apply(ttl)?;
+        let url = Endpoint::DnsEditByNameType {
+            domain,
             ty,
-            serde_json::to_string(content)?
-        );
+            subdomain,
+        }
+        .url(&self.api_url);
+        let snapshot = || self.fetch_records_by_name_type(domain, ty, subdomain);
+        let before = self.audit_snapshot(snapshot);
 
-        #[cfg(feature = "tracing")]
-        tracing::info!("POST {url}");
-        let response = self.client.post(url).body(body).send()?.text()?;
-        #[cfg(feature = "tracing")]
-        tracing::info!("response: {response}");
-        let response: EditDnsRecordResponse = serde_json::from_str(&response)
-            .map_err(|error| Error::MalformedApiSerde { error, response })?;
+        let response = self.post(
+            &url,
+            serde_json::to_string(&Body {
+                secret_api: &self.secret_api_key,
+                api: &self.api_key,
+                content,
+                ttl,
+                prio,
+                notes: self.owner.as_deref(),
+            })?,
+        )?;
+        let response: EditDnsRecordResponse = parse_response(response)?;
+        #[cfg(feature = "tracing_debug")]
+        tracing::debug!("parsed response: {response:?}");
+        match response.status {
+            Status::Success => {
+                let after = self.audit_snapshot(snapshot);
+                self.audit(domain, Operation::Edit, before, after)
+            }
+            Status::Error => Err(Error::Api {
+                message: response.message,
+            }),
+        }
+    }
+
+    /// Replaces the content of every record with a given type and subdomain.
+    ///
+    /// The ttl and priority are left unchanged unless given.
+    pub fn edit_records_by_name_type(
+        &self,
+        domain: &str,
+        ty: RecordType,
+        subdomain: Option<&str>,
+        content: &str,
+        ttl: Option<Ttl>,
+        prio: Option<&str>,
+    ) -> Result<()> {
+        self.edit_by_name_type(domain, ty, subdomain, &content, ttl, prio)
+    }
+
+    /// Replaces the name, type, content, ttl, and priority of a record by its id.
+    pub fn edit_record(&self, domain: &str, id: RecordId, record: &NewRecord) -> Result<()> {
+        self.edit_record_with_notes(domain, id, record, self.owner.as_deref())
+    }
+
+    /// Like [Client::edit_record], but also replaces the record's notes with `notes` rather
+    /// than this client's ownership marker.
+    pub fn edit_record_with_notes(
+        &self,
+        domain: &str,
+        id: RecordId,
+        record: &NewRecord,
+        notes: Option<&str>,
+    ) -> Result<()> {
+        #[derive(Clone, Debug, serde::Serialize)]
+        struct Body<'a> {
+            #[serde(rename = "secretapikey")]
+            secret_api: &'a str,
+            #[serde(rename = "apikey")]
+            api: &'a str,
+            pub name: Option<&'a str>,
+            #[serde(rename = "type")]
+            pub ty: RecordType,
+            pub content: &'a str,
+            pub ttl: Option<Ttl>,
+            pub prio: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub notes: Option<&'a str>,
+        }
+
+        #[derive(Clone, Debug, serde::Deserialize)]
+        struct Response {
+            status: Status,
+            #[serde(default)]
+            message: String,
+        }
+
+        check_apex_cname(domain, record.ty, record.name.as_deref())?;
+        check_svcb(record.ty, &record.content, record.prio.as_deref())?;
+        let ttl = self.ttl_policy.apply(record.ttl)?;
+        let before = self.audit_snapshot(|| self.fetch_record(domain, id));
+        let url = Endpoint::DnsEdit { domain, id }.url(&self.api_url);
+        let response = self.post(
+            &url,
+            serde_json::to_string(&Body {
+                secret_api: &self.secret_api_key,
+                api: &self.api_key,
+                name: record.name.as_deref(),
+                ty: record.ty,
+                content: &record.content,
+                ttl,
+                prio: record.prio.as_deref(),
+                notes,
+            })?,
+        )?;
+        let response: Response = parse_response(response)?;
         #[cfg(feature = "tracing_debug")]
         tracing::debug!("parsed response: {response:?}");
         match response.status {
-            Status::Success => Ok(()),
+            Status::Success => {
+                let after = self.audit_snapshot(|| self.fetch_record(domain, id));
+                self.audit(domain, Operation::Edit, before, after)
+            }
             Status::Error => Err(Error::Api {
                 message: response.message,
             }),
         }
     }
 
+    /// Moves a record to the subdomain `new_name`, or to the domain itself if `None`,
+    /// returning the id of the moved record.
+    ///
+    /// The API has no way of renaming a record, so a copy with the same ttl, priority, and notes
+    /// is created under the new name before the original is deleted. If the original can't be
+    /// deleted, the copy is deleted again so that the record isn't left answering at both names.
+    pub fn rename_record(
+        &self,
+        domain: &str,
+        id: RecordId,
+        new_name: Option<&str>,
+    ) -> Result<Option<RecordId>> {
+        let record = self
+            .fetch_record(domain, id)?
+            .pop()
+            .ok_or_else(|| Error::NoRecord {
+                domain: domain.to_string(),
+                id,
+            })?;
+        let copy = NewRecord {
+            name: new_name.map(str::to_string),
+            ..NewRecord::from_existing(domain, &record)
+        };
+        let new_id = self.create_record_with_notes(domain, &copy, record.notes.as_deref())?;
+        match self.delete_record(domain, id) {
+            Ok(()) => Ok(new_id),
+            // The original was deleted, so the copy is all that's left of it.
+            Err(error @ Error::Audit { .. }) => Err(error),
+            Err(error) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    "failed to delete record {id} after copying it, rolling back: {error}"
+                );
+                if let Some(new_id) = new_id {
+                    self.delete_record(domain, new_id)?;
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// Replaces the address of every A record with a given subdomain.
     pub fn edit_ipv4_address(
         &self,
         domain: &str,
         subdomain: Option<&str>,
         address: &Ipv4Addr,
+        ttl: Option<Ttl>,
+        prio: Option<&str>,
     ) -> Result<()> {
-        let mut url = format!("https://api.porkbun.com/api/json/v3/dns/editByNameType/{domain}/A/");
-        if let Some(subdomain) = subdomain {
-            url.push_str(subdomain);
-        }
-        self.edit_record_url(&url, "A", address)
+        self.edit_by_name_type(domain, RecordType::A, subdomain, address, ttl, prio)
     }
 
+    /// Replaces the address of every AAAA record with a given subdomain.
     pub fn edit_ipv6_address(
         &self,
         domain: &str,
         subdomain: Option<&str>,
         address: &Ipv6Addr,
+        ttl: Option<Ttl>,
+        prio: Option<&str>,
+    ) -> Result<()> {
+        self.edit_by_name_type(domain, RecordType::Aaaa, subdomain, address, ttl, prio)
+    }
+
+    /// Makes the records with a given type and subdomain hold exactly `values`, one record each,
+    /// such as several A records for round-robin load balancing.
+    ///
+    /// Missing values are created before records with unwanted or duplicate values are deleted,
+    /// so the name never goes unanswered. Every change is applied as a
+    /// [transaction](Client::apply_transaction); the report is empty if nothing needed changing.
+    pub fn set_records(
+        &self,
+        domain: &str,
+        subdomain: Option<&str>,
+        ty: RecordType,
+        values: &[&str],
+    ) -> Result<TransactionReport> {
+        let existing = self.fetch_records_by_name_type(domain, ty, subdomain)?;
+        let changes = transaction::converge(subdomain, ty, &existing, values);
+        self.apply_transaction(domain, changes)
+    }
+}
+
+/// Delete records
+impl Client {
+    /// Deletes a single DNS record by its id.
+    pub fn delete_record(&self, domain: &str, id: RecordId) -> Result<()> {
+        #[derive(Clone, Debug, serde::Deserialize)]
+        struct DeleteDnsRecordResponse {
+            status: Status,
+            #[serde(default)]
+            message: String,
+        }
+
+        let before = self.audit_snapshot(|| self.fetch_record(domain, id));
+        let url = Endpoint::DnsDelete { domain, id }.url(&self.api_url);
+        let response = self.post(&url, self.key_file.to_string())?;
+        let response: DeleteDnsRecordResponse = parse_response(response)?;
+        #[cfg(feature = "tracing_debug")]
+        tracing::debug!("parsed response: {response:?}");
+        match response.status {
+            Status::Success => self.audit(domain, Operation::Delete, before, Some(Vec::new())),
+            Status::Error => Err(Error::Api {
+                message: response.message,
+            }),
+        }
+    }
+
+    /// Deletes every record with a given type and subdomain.
+    pub fn delete_records_by_name_type(
+        &self,
+        domain: &str,
+        ty: RecordType,
+        subdomain: Option<&str>,
+    ) -> Result<()> {
+        #[derive(Clone, Debug, serde::Deserialize)]
+        struct DeleteDnsRecordResponse {
+            status: Status,
+            #[serde(default)]
+            message: String,
+        }
+
+        let before = self.audit_snapshot(|| self.fetch_records_by_name_type(domain, ty, subdomain));
+        let url = Endpoint::DnsDeleteByNameType {
+            domain,
+            ty,
+            subdomain,
+        }
+        .url(&self.api_url);
+        let response = self.post(&url, self.key_file.to_string())?;
+        let response: DeleteDnsRecordResponse = parse_response(response)?;
+        #[cfg(feature = "tracing_debug")]
+        tracing::debug!("parsed response: {response:?}");
+        match response.status {
+            Status::Success => self.audit(domain, Operation::Delete, before, Some(Vec::new())),
+            Status::Error => Err(Error::Api {
+                message: response.message,
+            }),
+        }
+    }
+}
+
+/// The change made by [Client::ensure_address].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AddressUpdate {
+    /// Every record already pointed at the address.
+    Unchanged,
+    /// No record existed, so one was created.
+    Created(Option<RecordId>),
+    /// The existing records were changed to the address.
+    Updated {
+        /// The content of the records before they were changed.
+        previous: Vec<String>,
+    },
+}
+
+/// Dynamic DNS
+impl Client {
+    /// Makes every A or AAAA record (depending on the address family) of a subdomain point at
+    /// `address`, creating one if none exist.
+    ///
+    /// Records which already match are left untouched, so this is cheap to call repeatedly.
+    pub fn ensure_address(
+        &self,
+        domain: &str,
+        subdomain: Option<&str>,
+        address: IpAddr,
+        ttl: Option<Ttl>,
+    ) -> Result<AddressUpdate> {
+        let ty = match address {
+            IpAddr::V4(_) => RecordType::A,
+            IpAddr::V6(_) => RecordType::Aaaa,
+        };
+        let ttl = self.ttl_policy.apply(ttl)?;
+        let records = self.fetch_records_by_name_type(domain, ty, subdomain)?;
+        let content = address.to_string();
+
+        if records.is_empty() {
+            let id = self.create_record(domain, subdomain, ty, &content, ttl, None)?;
+            return Ok(AddressUpdate::Created(id));
+        }
+
+        let matches = |record: &DnsRecord| {
+            canonicalize(ty, &record.content) == content
+                && ttl.is_none_or(|ttl| record.ttl.parse() == Ok(ttl))
+        };
+        if records.iter().all(matches) {
+            return Ok(AddressUpdate::Unchanged);
+        }
+
+        self.edit_by_name_type(domain, ty, subdomain, &address, ttl, None)?;
+        Ok(AddressUpdate::Updated {
+            previous: records.into_iter().map(|x| x.content).collect(),
+        })
+    }
+}
+
+/// Audit logging
+impl Client {
+    /// Fetches records for an [AuditEntry], if auditing is enabled.
+    ///
+    /// A failed fetch shouldn't prevent the change itself, so errors produce an empty snapshot.
+    fn audit_snapshot(&self, fetch: impl Fn() -> Result<Vec<DnsRecord>>) -> Option<Vec<DnsRecord>> {
+        self.audit.as_ref()?;
+        match fetch() {
+            Ok(records) => Some(records),
+            Err(_msg) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("failed to fetch records for audit log: {_msg}");
+                Some(Vec::new())
+            }
+        }
+    }
+
+    fn audit(
+        &self,
+        domain: &str,
+        operation: Operation,
+        before: Option<Vec<DnsRecord>>,
+        after: Option<Vec<DnsRecord>>,
+    ) -> Result<()> {
+        let Some(sink) = &self.audit else {
+            return Ok(());
+        };
+        sink.record(&AuditEntry {
+            timestamp: std::time::SystemTime::now(),
+            actor: self.actor.clone(),
+            domain: domain.to_string(),
+            operation,
+            before: before.unwrap_or_default(),
+            after: after.unwrap_or_default(),
+            prev_hash: None,
+        })
+        .map_err(|error| Error::Audit {
+            error,
+            created: None,
+        })
+    }
+
+    /// Records the creation of the record `id`, keeping its id in the error if that fails.
+    fn audit_create(
+        &self,
+        domain: &str,
+        id: RecordId,
+        after: Option<Vec<DnsRecord>>,
     ) -> Result<()> {
-        let mut url = format!("https://api.porkbun.com/api/json/v3/dns/editByNameType/{domain}/A/");
-        if let Some(subdomain) = subdomain {
-            url.push_str(subdomain);
+        match self.audit(domain, Operation::Create, Some(Vec::new()), after) {
+            Err(Error::Audit { error, .. }) => Err(Error::Audit {
+                error,
+                created: Some(id),
+            }),
+            result => result,
         }
-        self.edit_record_url(&url, "A", address)
     }
 }
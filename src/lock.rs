@@ -0,0 +1,125 @@
+use crate::{Client, DnsRecord, Error, RecordId, RecordType, Result, Ttl};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The subdomain whose TXT records hold [ZoneLock]s.
+pub const LOCK_NAME: &str = "_porkbun-lock";
+
+/// A cooperative lock on a zone, held as a TXT record at `_porkbun-lock.<domain>`, so that
+/// tools on different machines (such as two CI jobs) don't interleave their changes.
+///
+/// Porkbun doesn't enforce it; it only keeps out other tools which take it too. The lock is
+/// released when dropped, and expires on its own if its holder dies without releasing it.
+/// ```no_run
+/// # use std::time::Duration;
+/// # let client = porkbun::Client::open_keys("keys.json")?;
+/// let lock = client.lock_zone("example.com", "deploy job 41", Duration::from_secs(600))?;
+/// // Plan and apply changes...
+/// lock.release()?;
+/// # Ok::<(), porkbun::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct ZoneLock {
+    client: Client,
+    domain: String,
+    id: Option<RecordId>,
+}
+
+/// A lock record's holder and expiry, in seconds since the Unix epoch.
+fn parse(record: &DnsRecord) -> Option<(&str, u64)> {
+    let content = record.content.trim_matches('"');
+    let (holder, expires) = content.strip_prefix("holder=")?.rsplit_once(" expires=")?;
+    Some((holder, expires.parse().ok()?))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Locking
+impl Client {
+    /// Takes the [lock](ZoneLock) on `domain` for up to `duration`, naming `holder` as the
+    /// one who holds it in case others have to wait.
+    ///
+    /// Locks left behind past their expiry are deleted. Fails with [Error::ZoneLocked] if
+    /// someone else holds the lock, including when two clients take it at the same moment,
+    /// in which case the earlier record wins.
+    pub fn lock_zone(&self, domain: &str, holder: &str, duration: Duration) -> Result<ZoneLock> {
+        let held = |records: Vec<DnsRecord>| -> Result<Vec<DnsRecord>> {
+            let mut held = Vec::new();
+            for record in records {
+                match parse(&record) {
+                    Some((_, expires)) if expires > now() => held.push(record),
+                    _ => {
+                        #[cfg(feature = "tracing")]
+                        tracing::info!("deleting expired lock on {domain}: {}", record.content);
+                        self.delete_record(domain, record.id)?;
+                    }
+                }
+            }
+            held.sort_by_key(|x| x.id);
+            Ok(held)
+        };
+        let locked = |record: &DnsRecord| Error::ZoneLocked {
+            domain: domain.to_string(),
+            holder: parse(record).map_or_else(String::new, |x| x.0.to_string()),
+        };
+        let fetch = || self.fetch_records_by_name_type(domain, RecordType::Txt, Some(LOCK_NAME));
+
+        if let Some(record) = held(fetch()?)?.first() {
+            return Err(locked(record));
+        }
+        let content = format!("holder={holder} expires={}", now() + duration.as_secs());
+        let id = self.create_record(
+            domain,
+            Some(LOCK_NAME),
+            RecordType::Txt,
+            &content,
+            Some(Ttl::MIN),
+            None,
+        )?;
+        let mut lock = ZoneLock {
+            client: self.clone(),
+            domain: domain.to_string(),
+            id,
+        };
+        // Someone else may have created a lock between the check and the creation.
+        match held(fetch()?)?.first() {
+            Some(record) if Some(record.id) != lock.id => {
+                let error = locked(record);
+                lock.release_now()?;
+                Err(error)
+            }
+            _ => Ok(lock),
+        }
+    }
+}
+
+impl ZoneLock {
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// Releases the lock, failing if its record can't be deleted.
+    pub fn release(mut self) -> Result<()> {
+        self.release_now()
+    }
+
+    fn release_now(&mut self) -> Result<()> {
+        match self.id.take() {
+            Some(id) => self.client.delete_record(&self.domain, id),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for ZoneLock {
+    fn drop(&mut self) {
+        if let Err(_error) = self.release_now() {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("failed to release the lock on {}: {_error}", self.domain);
+        }
+    }
+}
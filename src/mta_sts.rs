@@ -0,0 +1,246 @@
+use crate::{Client, Error, RecordType, Result, TransactionReport};
+use std::net::IpAddr;
+
+/// How sending servers should treat an MTA-STS policy (RFC 8461).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StsMode {
+    /// Refuse to deliver over connections which fail the policy.
+    Enforce,
+    /// Deliver anyway, but send TLS reports about failures.
+    #[default]
+    Testing,
+    /// Withdraw a previously published policy.
+    None,
+}
+
+impl StsMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StsMode::Enforce => "enforce",
+            StsMode::Testing => "testing",
+            StsMode::None => "none",
+        }
+    }
+}
+
+impl std::str::FromStr for StsMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "enforce" => Ok(StsMode::Enforce),
+            "testing" => Ok(StsMode::Testing),
+            "none" => Ok(StsMode::None),
+            _ => Err(format!(
+                "unknown mode \"{s}\"; expected enforce, testing, or none"
+            )),
+        }
+    }
+}
+
+/// An MTA-STS policy, which must be served at
+/// `https://mta-sts.<domain>/.well-known/mta-sts.txt` as well as announced in DNS.
+/// ```
+/// use porkbun::{MtaStsPolicy, StsMode};
+///
+/// let policy = MtaStsPolicy::new(StsMode::Enforce, &["mx1.example.com", "*.mail.example.net"])?;
+/// assert!(policy.text().contains("mx: *.mail.example.net\r\n"));
+/// assert!(policy.record_content().starts_with("v=STSv1; id="));
+///
+/// // Wildcards may only replace the leftmost label.
+/// assert!(MtaStsPolicy::new(StsMode::Enforce, &["mx.*.example.com"]).is_err());
+/// # Ok::<(), porkbun::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MtaStsPolicy {
+    pub mode: StsMode,
+    /// Names or patterns, such as `*.example.net`, which the domain's MX hosts must match.
+    pub mx: Vec<String>,
+    /// How long senders may cache the policy, in seconds.
+    pub max_age: u32,
+}
+
+impl MtaStsPolicy {
+    /// One week, which suits policies that are still being tested.
+    pub const DEFAULT_MAX_AGE: u32 = 604800;
+
+    /// Fails with [Error::InvalidMxPattern] if a pattern isn't a hostname, optionally with
+    /// a wildcard as its leftmost label.
+    pub fn new(mode: StsMode, mx_patterns: &[&str]) -> Result<Self> {
+        for pattern in mx_patterns {
+            check_mx_pattern(pattern)?;
+        }
+        Ok(Self {
+            mode,
+            mx: mx_patterns.iter().map(|x| x.to_string()).collect(),
+            max_age: Self::DEFAULT_MAX_AGE,
+        })
+    }
+
+    pub fn with_max_age(mut self, max_age: u32) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// The policy file to serve.
+    pub fn text(&self) -> String {
+        let mut text = format!("version: STSv1\r\nmode: {}\r\n", self.mode.as_str());
+        for mx in &self.mx {
+            text += &format!("mx: {mx}\r\n");
+        }
+        text += &format!("max_age: {}\r\n", self.max_age);
+        text
+    }
+
+    /// Identifies this version of the policy. Senders only fetch the policy file again when
+    /// this changes, so it's derived from the file's contents.
+    pub fn id(&self) -> String {
+        // FNV-1a, since it must stay the same across builds.
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in self.text().bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        format!("{hash:016x}")
+    }
+
+    /// The content of the `_mta-sts` TXT record announcing this policy.
+    pub fn record_content(&self) -> String {
+        format!("v=STSv1; id={}", self.id())
+    }
+}
+
+fn check_mx_pattern(pattern: &str) -> Result<()> {
+    let invalid = |reason| Error::InvalidMxPattern {
+        pattern: pattern.to_string(),
+        reason,
+    };
+    let host = pattern.strip_prefix("*.").unwrap_or(pattern);
+    if host.is_empty() || host.split('.').any(str::is_empty) {
+        return Err(invalid("empty label"));
+    }
+    if host.contains('*') {
+        return Err(invalid("a wildcard may only be the leftmost label"));
+    }
+    if !host
+        .chars()
+        .all(|x| x.is_ascii_alphanumeric() || x == '-' || x == '.')
+    {
+        return Err(invalid("not a hostname"));
+    }
+    Ok(())
+}
+
+/// Where receiving servers send reports about failed TLS connections (RFC 8460), published
+/// at `_smtp._tls`.
+/// ```
+/// let rpt = porkbun::TlsRpt::new(&["tls@example.com", "https://reports.example.net/tls"])?;
+/// assert_eq!(
+///     rpt.record_content(),
+///     "v=TLSRPTv1; rua=mailto:tls@example.com,https://reports.example.net/tls"
+/// );
+/// assert!(porkbun::TlsRpt::new(&["http://example.net"]).is_err());
+/// # Ok::<(), porkbun::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TlsRpt {
+    pub rua: Vec<String>,
+}
+
+impl TlsRpt {
+    /// Takes email addresses, with or without `mailto:`, and `https://` urls.
+    ///
+    /// Fails with [Error::InvalidReportUri] for anything else.
+    pub fn new(rua: &[&str]) -> Result<Self> {
+        let rua = rua
+            .iter()
+            .map(|uri| {
+                let invalid = |reason| Error::InvalidReportUri {
+                    uri: uri.to_string(),
+                    reason,
+                };
+                if uri.contains([',', '!', ';']) || uri.contains(char::is_whitespace) {
+                    return Err(invalid("contains a separator"));
+                }
+                if let Some(url) = uri.strip_prefix("https://") {
+                    if url.is_empty() {
+                        return Err(invalid("missing host"));
+                    }
+                    return Ok(uri.to_string());
+                }
+                if uri.contains("://") {
+                    return Err(invalid("only mailto: and https:// are allowed"));
+                }
+                let address = uri.strip_prefix("mailto:").unwrap_or(uri);
+                match address.split_once('@') {
+                    Some((user, host)) if !user.is_empty() && host.contains('.') => {
+                        Ok(format!("mailto:{address}"))
+                    }
+                    _ => Err(invalid("not an email address")),
+                }
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rua })
+    }
+
+    pub fn record_content(&self) -> String {
+        format!("v=TLSRPTv1; rua={}", self.rua.join(","))
+    }
+}
+
+/// Mail
+impl Client {
+    /// Publishes every record MTA-STS needs for `domain` in one
+    /// [transaction](Client::apply_transaction): the `_mta-sts` record announcing the policy,
+    /// the `mta-sts` host serving it, and the `_smtp._tls` record of `rpt`, replacing any
+    /// previous ones.
+    ///
+    /// `policy_host` is the web server for `mta-sts.<domain>`, either as an address, which
+    /// becomes an A or AAAA record, or as a hostname, which becomes a CNAME record.
+    /// The returned policy's [text](MtaStsPolicy::text) must still be served by it at
+    /// `https://mta-sts.<domain>/.well-known/mta-sts.txt`, which DNS alone can't do.
+    pub fn setup_mta_sts(
+        &self,
+        domain: &str,
+        mode: StsMode,
+        mx_patterns: &[&str],
+        policy_host: &str,
+        rpt: &TlsRpt,
+    ) -> Result<(MtaStsPolicy, TransactionReport)> {
+        let policy = MtaStsPolicy::new(mode, mx_patterns)?;
+        let host_type = match policy_host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(_)) => RecordType::A,
+            Ok(IpAddr::V6(_)) => RecordType::Aaaa,
+            Err(_) => RecordType::Cname,
+        };
+        let records = [
+            ("_mta-sts", RecordType::Txt, policy.record_content()),
+            ("mta-sts", host_type, policy_host.to_string()),
+            ("_smtp._tls", RecordType::Txt, rpt.record_content()),
+        ];
+        let mut changes = Vec::new();
+        for (subdomain, ty, content) in &records {
+            let existing = self.fetch_records_by_name_type(domain, *ty, Some(subdomain))?;
+            changes.extend(crate::transaction::converge(
+                Some(subdomain),
+                *ty,
+                &existing,
+                &[content],
+            ));
+        }
+        let report = self.apply_transaction(domain, changes)?;
+        Ok((policy, report))
+    }
+
+    /// Publishes where TLS failure reports for `domain` should go, replacing any previous
+    /// `_smtp._tls` record.
+    pub fn setup_tls_rpt(&self, domain: &str, rpt: &TlsRpt) -> Result<TransactionReport> {
+        self.set_records(
+            domain,
+            Some("_smtp._tls"),
+            RecordType::Txt,
+            &[&rpt.record_content()],
+        )
+    }
+}
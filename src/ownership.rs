@@ -0,0 +1,61 @@
+use crate::{Client, DnsRecord, Error, Result};
+
+/// The notes written to records owned by `tool`.
+fn marker(tool: &str) -> String {
+    format!("managed-by=porkbun-rs/{tool}")
+}
+
+/// Managed records
+impl Client {
+    /// Marks every record this client creates or edits as owned by `tool`, by writing
+    /// `managed-by=porkbun-rs/<tool>` into its notes.
+    ///
+    /// Any existing notes of an edited record are replaced.
+    /// Only records carrying this marker are touched by [Client::prune_managed], so it is safe
+    /// to use on zones which also contain records managed by hand or by other tools.
+    pub fn with_owner(mut self, tool: &str) -> Self {
+        self.owner = Some(marker(tool));
+        self
+    }
+
+    /// Returns true if `record` is owned by this client's tool.
+    pub fn is_managed(&self, record: &DnsRecord) -> bool {
+        self.owner
+            .as_deref()
+            .is_some_and(|owner| record.notes.as_deref().map(str::trim) == Some(owner))
+    }
+
+    /// Fetches the records of a domain which are owned by this client's tool.
+    ///
+    /// Fails with [Error::NoOwner] if [Client::with_owner] wasn't used.
+    pub fn fetch_managed_records(&self, domain: &str) -> Result<Vec<DnsRecord>> {
+        if self.owner.is_none() {
+            return Err(Error::NoOwner);
+        }
+        Ok(self
+            .fetch_records(domain)?
+            .into_iter()
+            .filter(|x| self.is_managed(x))
+            .collect())
+    }
+
+    /// Deletes every record owned by this client's tool for which `keep` returns false,
+    /// returning the deleted records.
+    ///
+    /// Records without this client's marker are never deleted.
+    /// Fails with [Error::NoOwner] if [Client::with_owner] wasn't used.
+    pub fn prune_managed(
+        &self,
+        domain: &str,
+        keep: impl Fn(&DnsRecord) -> bool,
+    ) -> Result<Vec<DnsRecord>> {
+        let mut pruned = Vec::new();
+        for record in self.fetch_managed_records(domain)? {
+            if !keep(&record) {
+                self.delete_record(domain, record.id)?;
+                pruned.push(record);
+            }
+        }
+        Ok(pruned)
+    }
+}
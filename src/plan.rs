@@ -0,0 +1,383 @@
+use crate::{canonicalize, Change, DnsRecord, DomainName, Error, NewRecord, RecordType, Result};
+use std::fmt::Write;
+use std::io::IsTerminal;
+
+/// The changes which turn a domain's records into a desired set of records.
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct ChangeSet {
+    pub domain: String,
+    pub create: Vec<NewRecord>,
+    pub edit: Vec<RecordEdit>,
+    pub delete: Vec<DnsRecord>,
+}
+
+/// A record which is kept, but with different content, ttl, or priority.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct RecordEdit {
+    pub before: DnsRecord,
+    pub after: NewRecord,
+}
+
+impl ChangeSet {
+    /// Compares the `existing` records of `domain` with the `desired` ones.
+    ///
+    /// Records match if they have the same name, type, and [canonical](canonicalize) content;
+    /// a desired ttl or priority of `None` matches anything. Leftover records with the same name
+    /// and type are edited into each other, and the rest are created or deleted.
+    ///
+    /// [ZoneLock](crate::ZoneLock) records are left out, since they come and go on their own.
+    pub fn diff(domain: &str, existing: &[DnsRecord], desired: &[NewRecord]) -> Self {
+        let lock_name = format!("{}.{domain}", crate::LOCK_NAME);
+        let mut unmatched: Vec<&DnsRecord> = existing
+            .iter()
+            .filter(|x| !x.name.eq_ignore_ascii_case(&lock_name))
+            .collect();
+        let mut changes = Self {
+            domain: domain.to_string(),
+            ..Self::default()
+        };
+        let same_name_type = |a: &NewRecord, b: &NewRecord| a.name == b.name && a.ty == b.ty;
+        let same_content = |a: &NewRecord, b: &NewRecord| {
+            canonicalize(a.ty, &a.content) == canonicalize(b.ty, &b.content)
+        };
+
+        let mut leftover = Vec::new();
+        for record in desired {
+            let found = unmatched.iter().position(|x| {
+                let x = NewRecord::from_existing(domain, x);
+                same_name_type(&x, record) && same_content(&x, record)
+            });
+            let Some(i) = found else {
+                leftover.push(record);
+                continue;
+            };
+            let before = unmatched.remove(i);
+            let current = NewRecord::from_existing(domain, before);
+            let stale_ttl = record.ttl.is_some() && record.ttl != current.ttl;
+            let stale_prio = record.prio.is_some() && record.prio != current.prio;
+            if stale_ttl || stale_prio {
+                changes.edit.push(RecordEdit {
+                    before: before.clone(),
+                    after: keep_unset(record, &current),
+                });
+            }
+        }
+
+        for record in leftover {
+            let found = unmatched
+                .iter()
+                .position(|x| same_name_type(&NewRecord::from_existing(domain, x), record));
+            match found {
+                Some(i) => {
+                    let before = unmatched.remove(i);
+                    let current = NewRecord::from_existing(domain, before);
+                    changes.edit.push(RecordEdit {
+                        before: before.clone(),
+                        after: keep_unset(record, &current),
+                    });
+                }
+                None => changes.create.push(record.clone()),
+            }
+        }
+        changes.delete = unmatched.into_iter().cloned().collect();
+        changes
+    }
+
+    /// Like [ChangeSet::diff], but resolves differences between the live records and the
+    /// desired ones with each type's [ConflictStrategy].
+    ///
+    /// Fails with [Error::SyncConflict] if a type's strategy is [ConflictStrategy::Fail] and
+    /// one of its records would be edited or deleted.
+    pub fn diff_with(
+        domain: &str,
+        existing: &[DnsRecord],
+        desired: &[NewRecord],
+        options: &SyncOptions,
+    ) -> Result<Self> {
+        let mut changes = Self::diff(domain, existing, desired);
+        if !options.prune {
+            changes.delete.clear();
+        }
+        let conflict = |record: &DnsRecord, reason| Error::SyncConflict {
+            name: record.name.clone(),
+            ty: record.ty,
+            reason,
+        };
+        for edit in &changes.edit {
+            if options.strategy(edit.before.ty) == ConflictStrategy::Fail {
+                return Err(conflict(&edit.before, "would be edited"));
+            }
+        }
+        for record in &changes.delete {
+            if options.strategy(record.ty) == ConflictStrategy::Fail {
+                return Err(conflict(record, "would be deleted"));
+            }
+        }
+
+        // Ignored types are left alone wherever they already exist.
+        let live = |record: &NewRecord| {
+            existing.iter().any(|x| {
+                let x = NewRecord::from_existing(domain, x);
+                x.name == record.name && x.ty == record.ty
+            })
+        };
+        changes
+            .create
+            .retain(|x| options.strategy(x.ty) != ConflictStrategy::Ignore || !live(x));
+        let edits = std::mem::take(&mut changes.edit);
+        for edit in edits {
+            match options.strategy(edit.before.ty) {
+                ConflictStrategy::Ignore => {}
+                ConflictStrategy::Merge
+                    if canonicalize(edit.before.ty, &edit.before.content)
+                        != canonicalize(edit.after.ty, &edit.after.content) =>
+                {
+                    changes.create.push(edit.after);
+                }
+                _ => changes.edit.push(edit),
+            }
+        }
+        changes.delete.retain(|x| {
+            matches!(
+                options.strategy(x.ty),
+                ConflictStrategy::Overwrite | ConflictStrategy::Fail
+            )
+        });
+        Ok(changes)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The total number of creations, edits, and deletions.
+    pub fn len(&self) -> usize {
+        self.create.len() + self.edit.len() + self.delete.len()
+    }
+
+    /// The changes to pass to [Client::apply_transaction](crate::Client::apply_transaction).
+    ///
+    /// Creations come first and deletions last, so that names are never left unanswered.
+    pub fn into_changes(self) -> Vec<Change> {
+        let create = self.create.into_iter().map(Change::Create);
+        let edit = self.edit.into_iter().map(|x| Change::Edit {
+            id: x.before.id,
+            record: x.after,
+        });
+        let delete = self.delete.into_iter().map(|x| Change::Delete { id: x.id });
+        create.chain(edit).chain(delete).collect()
+    }
+}
+
+/// The `desired` record, with the ttl and priority of the `current` one wherever it leaves
+/// them unset, so that an edit doesn't reset them to porkbun's defaults.
+fn keep_unset(desired: &NewRecord, current: &NewRecord) -> NewRecord {
+    NewRecord {
+        ttl: desired.ttl.or(current.ttl),
+        prio: desired.prio.clone().or_else(|| current.prio.clone()),
+        ..desired.clone()
+    }
+}
+
+/// What [ChangeSet::diff_with] does when live records differ from the desired ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    /// Make the live records match, editing and deleting them as needed.
+    #[default]
+    Overwrite,
+    /// Leave names which already have records of the type alone, such as records owned
+    /// by another team.
+    Ignore,
+    /// Refuse to edit or delete any record of the type.
+    Fail,
+    /// Keep the live records and add the desired ones alongside them, for multi-value sets
+    /// such as NS or TXT records.
+    Merge,
+}
+
+impl std::str::FromStr for ConflictStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "overwrite" => Ok(ConflictStrategy::Overwrite),
+            "ignore" => Ok(ConflictStrategy::Ignore),
+            "fail" => Ok(ConflictStrategy::Fail),
+            "merge" => Ok(ConflictStrategy::Merge),
+            _ => Err(format!(
+                "unknown conflict strategy \"{s}\"; expected overwrite, ignore, fail, or merge"
+            )),
+        }
+    }
+}
+
+/// How [ChangeSet::diff_with] reconciles a zone.
+/// ```
+/// use porkbun::{ConflictStrategy, RecordType, SyncOptions};
+///
+/// let options = SyncOptions::new()
+///     .prune(true)
+///     .strategy_for(RecordType::Txt, ConflictStrategy::Merge);
+/// assert_eq!(options.strategy(RecordType::Txt), ConflictStrategy::Merge);
+/// assert_eq!(options.strategy(RecordType::A), ConflictStrategy::Overwrite);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SyncOptions {
+    prune: bool,
+    default: ConflictStrategy,
+    strategies: Vec<(RecordType, ConflictStrategy)>,
+}
+
+impl SyncOptions {
+    /// Overwrites every type, without deleting records which aren't desired.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether to delete live records which aren't desired.
+    pub fn prune(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
+
+    /// The strategy for types without one of their own.
+    pub fn default_strategy(mut self, strategy: ConflictStrategy) -> Self {
+        self.default = strategy;
+        self
+    }
+
+    pub fn strategy_for(mut self, ty: RecordType, strategy: ConflictStrategy) -> Self {
+        self.strategies.retain(|x| x.0 != ty);
+        self.strategies.push((ty, strategy));
+        self
+    }
+
+    pub fn strategy(&self, ty: RecordType) -> ConflictStrategy {
+        self.strategies
+            .iter()
+            .find(|x| x.0 == ty)
+            .map_or(self.default, |x| x.1)
+    }
+}
+
+/// Formats a [ChangeSet] for people to review before it is applied.
+///
+/// Each change is a line starting with `+`, `~`, or `-`, like a terraform plan:
+/// ```text
+/// + A    www.example.com     203.0.113.7
+/// ~ A    example.com         203.0.113.7 (ttl 600) -> 203.0.113.8 (ttl 600)
+/// - TXT  _dmarc.example.com  v=DMARC1; p=none (ttl 600)
+///
+/// Plan: 1 to create, 1 to edit, 1 to delete.
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlanRenderer {
+    color: bool,
+    unicode: bool,
+}
+
+impl PlanRenderer {
+    /// Renders without color.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders with color if stdout is a terminal.
+    pub fn for_stdout() -> Self {
+        Self::new().color(std::io::stdout().is_terminal())
+    }
+
+    /// Whether to color lines with ANSI escape codes.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Whether to show internationalized names in their Unicode form rather than as punycode.
+    pub fn unicode(mut self, unicode: bool) -> Self {
+        self.unicode = unicode;
+        self
+    }
+
+    pub fn render(&self, changes: &ChangeSet) -> String {
+        let domain = &changes.domain;
+        let name = |record: &NewRecord| {
+            let name = match &record.name {
+                Some(name) => format!("{name}.{domain}"),
+                None => domain.clone(),
+            };
+            match DomainName::new(&name) {
+                Ok(name) if self.unicode => name.to_unicode(),
+                _ => name,
+            }
+        };
+        let details = |record: &NewRecord| {
+            let mut details = record.content.clone();
+            if let Some(prio) = record.prio.as_deref().filter(|x| *x != "0") {
+                write!(details, " (priority {prio})").ok();
+            }
+            if let Some(ttl) = record.ttl {
+                write!(details, " (ttl {ttl})").ok();
+            }
+            details
+        };
+
+        // Symbol, ANSI color, type, name, and details of each line.
+        let mut lines: Vec<(char, &str, &str, String, String)> = Vec::with_capacity(changes.len());
+        for record in &changes.create {
+            lines.push(('+', "32", record.ty.as_str(), name(record), details(record)));
+        }
+        for edit in &changes.edit {
+            let before = NewRecord::from_existing(domain, &edit.before);
+            let after = NewRecord {
+                ttl: edit.after.ttl.or(before.ttl),
+                prio: edit.after.prio.clone().or(before.prio.clone()),
+                ..edit.after.clone()
+            };
+            let details = format!("{} -> {}", details(&before), details(&after));
+            lines.push(('~', "33", edit.after.ty.as_str(), name(&after), details));
+        }
+        for record in &changes.delete {
+            let record = NewRecord::from_existing(domain, record);
+            lines.push((
+                '-',
+                "31",
+                record.ty.as_str(),
+                name(&record),
+                details(&record),
+            ));
+        }
+
+        let ty_width = lines.iter().map(|x| x.2.len()).max().unwrap_or(0);
+        let name_width = lines.iter().map(|x| x.3.len()).max().unwrap_or(0);
+        let mut plan = String::new();
+        for (symbol, color, ty, name, details) in lines {
+            let line = format!("{symbol} {ty:ty_width$}  {name:name_width$}  {details}");
+            let line = line.trim_end();
+            if self.color {
+                writeln!(plan, "\x1b[{color}m{line}\x1b[0m").ok();
+            } else {
+                writeln!(plan, "{line}").ok();
+            }
+        }
+        if changes.is_empty() {
+            writeln!(plan, "No changes.").ok();
+        } else {
+            writeln!(
+                plan,
+                "\nPlan: {} to create, {} to edit, {} to delete.",
+                changes.create.len(),
+                changes.edit.len(),
+                changes.delete.len()
+            )
+            .ok();
+        }
+        plan
+    }
+
+    /// Encodes a plan as JSON, for other tools to consume.
+    pub fn json(&self, changes: &ChangeSet) -> String {
+        // Records are plain strings and numbers, so this can't fail.
+        serde_json::to_string_pretty(changes).unwrap_or_default()
+    }
+}
@@ -0,0 +1,132 @@
+//! Pools of addresses published as round-robin A and AAAA records.
+//!
+//! DNS can't express how much traffic each address should receive, so a member's weight
+//! only decides which members are published when the pool is limited by
+//! [Pool::max_records], and a weight of 0 drains a member entirely.
+//! Health is not checked here; an external checker should call [Pool::set_health]
+//! and then [Pool::apply].
+//!
+//! ```no_run
+//! use porkbun::pool::Pool;
+//! use std::net::Ipv4Addr;
+//!
+//! let client = porkbun::Client::open_keys("keys.json").unwrap();
+//! let mut pool = Pool::new(Some("www"))
+//!     .member(Ipv4Addr::new(203, 0, 113, 1), 10)
+//!     .member(Ipv4Addr::new(203, 0, 113, 2), 10)
+//!     .member(Ipv4Addr::new(203, 0, 113, 3), 1)
+//!     .max_records(2);
+//! pool.set_health(Ipv4Addr::new(203, 0, 113, 2).into(), false);
+//! // Publishes 203.0.113.1 and 203.0.113.3.
+//! pool.apply(&client, "example.com").unwrap();
+//! ```
+
+use crate::transaction::converge;
+use crate::{Change, Client, DnsRecord, RecordType, Result, TransactionReport};
+use std::net::IpAddr;
+
+/// An address in a [Pool].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Member {
+    pub address: IpAddr,
+    /// Members with higher weights are preferred; 0 means the member is never published.
+    pub weight: u32,
+    pub healthy: bool,
+}
+
+/// A set of addresses published under one name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Pool {
+    /// The subdomain the pool's records are placed at, or `None` for the domain itself.
+    pub subdomain: Option<String>,
+    pub members: Vec<Member>,
+    /// The most records published for each address family.
+    pub max_records: Option<usize>,
+}
+
+impl Pool {
+    pub fn new(subdomain: Option<&str>) -> Self {
+        Self {
+            subdomain: subdomain.map(str::to_string),
+            ..Self::default()
+        }
+    }
+
+    /// Adds a healthy member.
+    pub fn member(mut self, address: impl Into<IpAddr>, weight: u32) -> Self {
+        self.members.push(Member {
+            address: address.into(),
+            weight,
+            healthy: true,
+        });
+        self
+    }
+
+    pub fn max_records(mut self, max_records: usize) -> Self {
+        self.max_records = Some(max_records);
+        self
+    }
+
+    /// Marks every member with `address` as healthy or unhealthy.
+    ///
+    /// Returns false if the pool has no such member.
+    pub fn set_health(&mut self, address: IpAddr, healthy: bool) -> bool {
+        let mut found = false;
+        for member in self.members.iter_mut().filter(|x| x.address == address) {
+            member.healthy = healthy;
+            found = true;
+        }
+        found
+    }
+
+    /// The addresses which should be published for one address family, most preferred first.
+    ///
+    /// If no member of the family is healthy, every member with a weight is published instead,
+    /// since answering with a possibly unhealthy address is better than not answering.
+    pub fn published(&self, ipv6: bool) -> Vec<IpAddr> {
+        let candidates = self
+            .members
+            .iter()
+            .filter(|x| x.weight > 0 && x.address.is_ipv6() == ipv6);
+        let mut chosen: Vec<&Member> = candidates.clone().filter(|x| x.healthy).collect();
+        if chosen.is_empty() {
+            chosen = candidates.collect();
+        }
+        chosen.sort_by_key(|x| std::cmp::Reverse(x.weight));
+
+        let mut addresses = Vec::new();
+        for member in chosen {
+            if !addresses.contains(&member.address) {
+                addresses.push(member.address);
+            }
+        }
+        if let Some(max_records) = self.max_records {
+            addresses.truncate(max_records);
+        }
+        addresses
+    }
+
+    /// The changes which make the pool's existing A and AAAA records match it.
+    ///
+    /// A family with no members is left untouched.
+    pub fn render(&self, a: &[DnsRecord], aaaa: &[DnsRecord]) -> Vec<Change> {
+        let mut changes = Vec::new();
+        for (ty, ipv6, existing) in [(RecordType::A, false, a), (RecordType::Aaaa, true, aaaa)] {
+            if !self.members.iter().any(|x| x.address.is_ipv6() == ipv6) {
+                continue;
+            }
+            let values: Vec<String> = self.published(ipv6).iter().map(IpAddr::to_string).collect();
+            changes.extend(converge(self.subdomain.as_deref(), ty, existing, &values));
+        }
+        changes
+    }
+
+    /// Fetches the pool's records and applies [Pool::render] as a
+    /// [transaction](Client::apply_transaction).
+    pub fn apply(&self, client: &Client, domain: &str) -> Result<TransactionReport> {
+        let subdomain = self.subdomain.as_deref();
+        let a = client.fetch_records_by_name_type(domain, RecordType::A, subdomain)?;
+        let aaaa = client.fetch_records_by_name_type(domain, RecordType::Aaaa, subdomain)?;
+        client.apply_transaction(domain, self.render(&a, &aaaa))
+    }
+}
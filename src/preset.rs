@@ -0,0 +1,257 @@
+//! The bundles of records common self-hosted services expect, ready for
+//! [Client::apply_preset], and those static site hosts document, for [Client::setup_site].
+//!
+//! Hosts should be fully qualified, such as `matrix.example.com`.
+//! ```
+//! let records = porkbun::preset::minecraft("play.example.com", None, Some("mc"));
+//! assert_eq!(records[0].name.as_deref(), Some("_minecraft._tcp.mc"));
+//! assert_eq!(records[0].content, "5 25565 play.example.com");
+//! ```
+
+use crate::resolve::{PropagationReport, Resolver};
+use crate::{subdomain_of, ChangeSet, Client, DnsRecord, NewRecord, RecordType, Result};
+use crate::{TransactionReport, Ttl};
+
+/// An SRV record at `name` pointing at `host`. Porkbun keeps the priority apart from the weight,
+/// port, and target.
+fn srv(name: &str, weight: u16, port: u16, host: &str) -> NewRecord {
+    NewRecord {
+        name: Some(name.to_string()),
+        ty: RecordType::Srv,
+        content: format!("{weight} {port} {}", host.trim_end_matches('.')),
+        ttl: None,
+        prio: Some("10".to_string()),
+    }
+}
+
+/// Federation for a Matrix homeserver, which listens on port 8448 by default.
+///
+/// Both the current `_matrix-fed._tcp` name and the `_matrix._tcp` name older servers look
+/// for are created.
+pub fn matrix(host: &str, port: Option<u16>) -> Vec<NewRecord> {
+    let port = port.unwrap_or(8448);
+    vec![
+        srv("_matrix-fed._tcp", 0, port, host),
+        srv("_matrix._tcp", 0, port, host),
+    ]
+}
+
+/// Client and server-to-server connections of an XMPP server, which listens on ports 5222
+/// and 5269 by default.
+pub fn xmpp(host: &str, client_port: Option<u16>, server_port: Option<u16>) -> Vec<NewRecord> {
+    vec![
+        srv("_xmpp-client._tcp", 5, client_port.unwrap_or(5222), host),
+        srv("_xmpp-server._tcp", 5, server_port.unwrap_or(5269), host),
+    ]
+}
+
+/// A SIP server over UDP and TCP (port 5060 by default) and TLS (port 5061 by default).
+pub fn sip(host: &str, port: Option<u16>, tls_port: Option<u16>) -> Vec<NewRecord> {
+    let port = port.unwrap_or(5060);
+    vec![
+        srv("_sip._udp", 0, port, host),
+        srv("_sip._tcp", 0, port, host),
+        srv("_sips._tcp", 0, tls_port.unwrap_or(5061), host),
+    ]
+}
+
+/// A Minecraft Java Edition server, which listens on port 25565 by default.
+///
+/// Players connect to `subdomain`, or the domain itself if it's `None`.
+pub fn minecraft(host: &str, port: Option<u16>, subdomain: Option<&str>) -> Vec<NewRecord> {
+    let name = match subdomain {
+        Some(subdomain) => format!("_minecraft._tcp.{subdomain}"),
+        None => "_minecraft._tcp".to_string(),
+    };
+    vec![srv(&name, 5, port.unwrap_or(25565), host)]
+}
+
+/// The records a static site host documents for a custom domain, and what's left to do on
+/// the host's side.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SiteHost {
+    /// Addresses at the domain itself, and a CNAME record at `www`.
+    pub records: Vec<NewRecord>,
+    /// Steps which can't be done through DNS.
+    pub steps: Vec<String>,
+}
+
+impl SiteHost {
+    /// Plans making the domain and `www` point at the host, replacing any address, ALIAS, or
+    /// CNAME records they have.
+    pub fn plan(&self, domain: &str, records: &[DnsRecord]) -> ChangeSet {
+        let existing: Vec<DnsRecord> = records
+            .iter()
+            .filter(|x| matches!(subdomain_of(&x.name, domain), None | Some("www")))
+            .filter(|x| {
+                matches!(
+                    x.ty,
+                    RecordType::A | RecordType::Aaaa | RecordType::Alias | RecordType::Cname
+                )
+            })
+            .cloned()
+            .collect();
+        ChangeSet::diff(domain, &existing, &self.records)
+    }
+
+    fn new(apex: &[(RecordType, &str)], www: &str, steps: Vec<String>) -> Self {
+        let mut records: Vec<NewRecord> = apex
+            .iter()
+            .map(|(ty, address)| NewRecord {
+                name: None,
+                ty: *ty,
+                content: address.to_string(),
+                ttl: None,
+                prio: None,
+            })
+            .collect();
+        records.push(NewRecord {
+            name: Some("www".to_string()),
+            ty: RecordType::Cname,
+            content: www.to_string(),
+            ttl: None,
+            prio: None,
+        });
+        Self { records, steps }
+    }
+}
+
+/// A GitHub Pages site of `user`, which may also be an organization.
+pub fn github_pages(domain: &str, user: &str) -> SiteHost {
+    use RecordType::{Aaaa, A};
+    SiteHost::new(
+        &[
+            (A, "185.199.108.153"),
+            (A, "185.199.109.153"),
+            (A, "185.199.110.153"),
+            (A, "185.199.111.153"),
+            (Aaaa, "2606:50c0:8000::153"),
+            (Aaaa, "2606:50c0:8001::153"),
+            (Aaaa, "2606:50c0:8002::153"),
+            (Aaaa, "2606:50c0:8003::153"),
+        ],
+        &format!("{}.github.io", user.to_ascii_lowercase()),
+        vec![
+            format!("Set the custom domain to {domain} in the repository's Settings > Pages."),
+            "Enable \"Enforce HTTPS\" there once the certificate has been issued.".to_string(),
+            format!(
+                "Verify {domain} in your account's Settings > Pages, so nobody else can use it."
+            ),
+        ],
+    )
+}
+
+/// A Netlify site named `site`, as in `site.netlify.app`.
+pub fn netlify(domain: &str, site: &str) -> SiteHost {
+    SiteHost::new(
+        &[(RecordType::A, "75.2.60.5")],
+        &format!("{site}.netlify.app"),
+        vec![
+            format!("Add {domain} under Domain management in the site's settings."),
+            "Netlify issues a certificate once the records have propagated.".to_string(),
+        ],
+    )
+}
+
+/// A Vercel project.
+pub fn vercel(domain: &str) -> SiteHost {
+    SiteHost::new(
+        &[(RecordType::A, "76.76.21.21")],
+        "cname.vercel-dns.com",
+        vec![format!(
+            "Add {domain} and www.{domain} in the project's Settings > Domains."
+        )],
+    )
+}
+
+/// The outcome of pointing a domain at a static site host.
+#[derive(Debug)]
+pub struct SiteSetup {
+    pub report: TransactionReport,
+    /// Whether public resolvers return the first of the host's addresses yet, or `None` if
+    /// the changes weren't all applied, so there was nothing to check.
+    pub propagation: Option<PropagationReport>,
+    /// What's left to do on the host's side.
+    pub steps: Vec<String>,
+}
+
+/// Plans making `domain` have the `desired` records, replacing existing records with the same
+/// names and types while leaving every other record alone.
+pub fn plan(domain: &str, records: &[DnsRecord], desired: &[NewRecord]) -> ChangeSet {
+    let existing: Vec<DnsRecord> = records
+        .iter()
+        .filter(|x| {
+            let name = subdomain_of(&x.name, domain);
+            desired
+                .iter()
+                .any(|y| y.ty == x.ty && y.name.as_deref() == name)
+        })
+        .cloned()
+        .collect();
+    ChangeSet::diff(domain, &existing, desired)
+}
+
+/// Presets
+impl Client {
+    /// Gives `domain` a bundle of records, such as those from [preset](crate::preset),
+    /// replacing any existing records with the same names and types in one transaction.
+    ///
+    /// Records without a ttl get `ttl`.
+    pub fn apply_preset(
+        &self,
+        domain: &str,
+        records: &[NewRecord],
+        ttl: Option<Ttl>,
+    ) -> Result<TransactionReport> {
+        let records: Vec<NewRecord> = records
+            .iter()
+            .cloned()
+            .map(|x| NewRecord {
+                ttl: x.ttl.or(ttl),
+                ..x
+            })
+            .collect();
+        let changes = plan(domain, &self.fetch_records(domain)?, &records);
+        self.apply_transaction(domain, changes.into_changes())
+    }
+
+    /// Points `domain` and `www` at a static site host, then checks whether the change has
+    /// reached public resolvers.
+    pub fn setup_site(&self, domain: &str, host: SiteHost) -> Result<SiteSetup> {
+        let changes = host.plan(domain, &self.fetch_records(domain)?);
+        self.apply_site_plan(host, changes)
+    }
+
+    /// Like [Client::setup_site], but applies `changes`, a plan from [SiteHost::plan] which
+    /// may already have been shown to someone.
+    pub fn apply_site_plan(&self, host: SiteHost, changes: ChangeSet) -> Result<SiteSetup> {
+        let domain = changes.domain.clone();
+        let report = self.apply_transaction(&domain, changes.into_changes())?;
+        let resolvers = [Resolver::cloudflare(), Resolver::google()];
+        let propagation = report
+            .is_success()
+            .then(|| host.records.iter().find(|x| x.ty == RecordType::A))
+            .flatten()
+            .map(|x| PropagationReport::check(&resolvers, &domain, RecordType::A, &x.content));
+        Ok(SiteSetup {
+            report,
+            propagation,
+            steps: host.steps,
+        })
+    }
+
+    /// Points `domain` at the GitHub Pages site of `user`. See [Client::setup_site].
+    pub fn setup_github_pages(&self, domain: &str, user: &str) -> Result<SiteSetup> {
+        self.setup_site(domain, github_pages(domain, user))
+    }
+
+    /// Points `domain` at the Netlify site `site`. See [Client::setup_site].
+    pub fn setup_netlify(&self, domain: &str, site: &str) -> Result<SiteSetup> {
+        self.setup_site(domain, netlify(domain, site))
+    }
+
+    /// Points `domain` at Vercel. See [Client::setup_site].
+    pub fn setup_vercel(&self, domain: &str) -> Result<SiteSetup> {
+        self.setup_site(domain, vercel(domain))
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{DnsRecord, RecordType};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Statistics and likely mistakes in a zone, for periodic hygiene reviews.
+///
+/// Printing a report with `{}` gives a human-readable summary.
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct ZoneReport {
+    pub total: usize,
+    /// The number of records of each type, most common first.
+    pub by_type: Vec<(RecordType, usize)>,
+    /// The number of records with each ttl. Records whose ttl isn't a number are left out.
+    pub ttls: BTreeMap<u32, usize>,
+    /// CNAME records pointing at a name within the zone which has no records.
+    pub orphaned_cnames: Vec<DnsRecord>,
+    /// Groups of records with the same name, type, and content.
+    pub duplicates: Vec<Vec<DnsRecord>>,
+    /// Suggestions about records which are commonly missing or misconfigured.
+    pub hints: Vec<String>,
+}
+
+impl ZoneReport {
+    /// Analyzes every record of a single zone.
+    ///
+    /// The zone's apex is taken to be the shortest record name, since every zone has
+    /// NS records there.
+    pub fn analyze(records: &[DnsRecord]) -> Self {
+        let mut report = Self {
+            total: records.len(),
+            ..Self::default()
+        };
+        let Some(apex) = records
+            .iter()
+            .map(|x| x.name.as_str())
+            .min_by_key(|x| x.len())
+        else {
+            return report;
+        };
+
+        for record in records {
+            match report.by_type.iter_mut().find(|x| x.0 == record.ty) {
+                Some((_, count)) => *count += 1,
+                None => report.by_type.push((record.ty, 1)),
+            }
+            if let Ok(ttl) = record.ttl.parse() {
+                *report.ttls.entry(ttl).or_default() += 1;
+            }
+        }
+        report.by_type.sort_by_key(|x| std::cmp::Reverse(x.1));
+
+        let has_name = |name: &str| records.iter().any(|x| x.name.eq_ignore_ascii_case(name));
+        let in_zone = |name: &str| name == apex || name.ends_with(&format!(".{apex}"));
+        for record in records.iter().filter(|x| x.ty == RecordType::Cname) {
+            let target = record.content.trim_end_matches('.');
+            if in_zone(target) && !has_name(target) {
+                report.orphaned_cnames.push(record.clone());
+            }
+        }
+
+        let mut seen: Vec<&DnsRecord> = Vec::new();
+        for record in records {
+            let same = |x: &&DnsRecord| {
+                x.name == record.name && x.ty == record.ty && x.content == record.content
+            };
+            if seen.iter().any(same) {
+                continue;
+            }
+            seen.push(record);
+            let group: Vec<DnsRecord> = records.iter().filter(same).cloned().collect();
+            if group.len() > 1 {
+                report.duplicates.push(group);
+            }
+        }
+
+        report.hints = hints(records, apex);
+        report
+    }
+}
+
+fn hints(records: &[DnsRecord], apex: &str) -> Vec<String> {
+    let mut hints = Vec::new();
+    let count = |name: &str, ty: RecordType| {
+        records
+            .iter()
+            .filter(|x| x.ty == ty && x.name == name)
+            .count()
+    };
+    let spf_count = |name: &str| {
+        records
+            .iter()
+            .filter(|x| x.ty == RecordType::Txt && x.name == name)
+            .filter(|x| x.content.trim_matches('"').starts_with("v=spf1"))
+            .count()
+    };
+
+    let mut names: Vec<&str> = records.iter().map(|x| x.name.as_str()).collect();
+    names.sort_unstable();
+    names.dedup();
+    for name in names {
+        let has_mx = count(name, RecordType::Mx) > 0;
+        let spf = spf_count(name);
+        if spf > 1 {
+            hints.push(format!(
+                "{name} has {spf} SPF records; receivers treat more than one as an error"
+            ));
+        }
+        if spf > 0 && !has_mx && name == apex {
+            hints.push(format!(
+                "{name} has an SPF record but no MX records, so it can't receive mail"
+            ));
+        }
+        if has_mx && spf == 0 {
+            hints.push(format!("{name} receives mail but has no SPF record"));
+        }
+        let cnames = count(name, RecordType::Cname);
+        if cnames > 0 && records.iter().filter(|x| x.name == name).count() > cnames {
+            hints.push(format!(
+                "{name} has a CNAME record alongside other records, which resolvers may ignore"
+            ));
+        }
+    }
+    // A DMARC policy at the apex also covers every subdomain.
+    if count(apex, RecordType::Mx) > 0 && count(&format!("_dmarc.{apex}"), RecordType::Txt) == 0 {
+        hints.push(format!("{apex} receives mail but has no DMARC record"));
+    }
+    if count(apex, RecordType::Caa) == 0 {
+        hints.push(format!(
+            "{apex} has no CAA records, so any certificate authority may issue certificates for it"
+        ));
+    }
+    hints
+}
+
+impl std::fmt::Display for ZoneReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} records", self.total)?;
+        for (ty, count) in &self.by_type {
+            writeln!(f, "  {:<6} {count}", ty.as_str())?;
+        }
+
+        writeln!(f, "\nTTLs")?;
+        for (ttl, count) in &self.ttls {
+            writeln!(f, "  {ttl:<6} {count}")?;
+        }
+
+        if !self.orphaned_cnames.is_empty() {
+            writeln!(f, "\nCNAME records pointing at names with no records")?;
+            for record in &self.orphaned_cnames {
+                writeln!(f, "  {} -> {}", record.name, record.content)?;
+            }
+        }
+        if !self.duplicates.is_empty() {
+            writeln!(f, "\nDuplicate records")?;
+            for group in &self.duplicates {
+                let mut ids = String::new();
+                for record in group {
+                    write!(ids, " {}", record.id)?;
+                }
+                let record = &group[0];
+                writeln!(
+                    f,
+                    "  {} {} {} (ids{ids})",
+                    record.ty, record.name, record.content
+                )?;
+            }
+        }
+        if !self.hints.is_empty() {
+            writeln!(f, "\nHints")?;
+            for hint in &self.hints {
+                writeln!(f, "  {hint}")?;
+            }
+        }
+        Ok(())
+    }
+}
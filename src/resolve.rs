@@ -0,0 +1,444 @@
+//! Looking up published records, to check that changes have taken effect.
+//!
+//! Lookups go through either the system resolver, over classic UDP/TCP DNS,
+//! or a DNS-over-HTTPS service, which also works on networks that block outbound port 53.
+
+use crate::clock::{Clock, SystemClock};
+use crate::{DnsRecord, Error, RecordType, Result};
+use std::net::{IpAddr, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+/// How often resolvers are asked again while waiting for a change to propagate.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How names are resolved.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Resolver {
+    /// The operating system's resolver. This can only look up A and AAAA records.
+    #[default]
+    System,
+    /// A DNS-over-HTTPS service which speaks the JSON API shared by Cloudflare and Google.
+    Doh(String),
+}
+
+impl Resolver {
+    pub const CLOUDFLARE_URL: &'static str = "https://cloudflare-dns.com/dns-query";
+    pub const GOOGLE_URL: &'static str = "https://dns.google/resolve";
+    pub const QUAD9_URL: &'static str = "https://dns.quad9.net:5053/dns-query";
+
+    pub fn cloudflare() -> Self {
+        Resolver::Doh(Self::CLOUDFLARE_URL.to_string())
+    }
+
+    pub fn google() -> Self {
+        Resolver::Doh(Self::GOOGLE_URL.to_string())
+    }
+
+    pub fn quad9() -> Self {
+        Resolver::Doh(Self::QUAD9_URL.to_string())
+    }
+
+    /// Looks up the A and AAAA records of `name`.
+    pub fn lookup_addresses(&self, name: &str) -> Result<Vec<IpAddr>> {
+        match self {
+            Resolver::System => Ok((name, 0).to_socket_addrs()?.map(|x| x.ip()).collect()),
+            Resolver::Doh(_) => {
+                let mut addresses = Vec::new();
+                for ty in [RecordType::A, RecordType::Aaaa] {
+                    addresses.extend(
+                        self.lookup(name, ty)?
+                            .iter()
+                            .filter_map(|x| x.parse::<IpAddr>().ok()),
+                    );
+                }
+                Ok(addresses)
+            }
+        }
+    }
+
+    /// Looks up the records of `name` with type `ty`, returning their data as DNS presents it.
+    ///
+    /// The system resolver fails with [Error::UnsupportedLookup] for types other than
+    /// A and AAAA.
+    pub fn lookup(&self, name: &str, ty: RecordType) -> Result<Vec<String>> {
+        match self {
+            Resolver::System => {
+                let ipv6 = match ty {
+                    RecordType::A => false,
+                    RecordType::Aaaa => true,
+                    ty => return Err(Error::UnsupportedLookup { ty }),
+                };
+                Ok(self
+                    .lookup_addresses(name)?
+                    .into_iter()
+                    .filter(|x| x.is_ipv6() == ipv6)
+                    .map(|x| x.to_string())
+                    .collect())
+            }
+            Resolver::Doh(url) => doh_lookup(url, name, ty),
+        }
+    }
+}
+
+impl std::fmt::Display for Resolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Resolver::System => f.write_str("system"),
+            Resolver::Doh(url) if url == Self::CLOUDFLARE_URL => f.write_str("cloudflare"),
+            Resolver::Doh(url) if url == Self::GOOGLE_URL => f.write_str("google"),
+            Resolver::Doh(url) if url == Self::QUAD9_URL => f.write_str("quad9"),
+            Resolver::Doh(url) => f.write_str(url),
+        }
+    }
+}
+
+/// Parses `system`, `cloudflare`, `google`, `quad9`, or the url of a DNS-over-HTTPS service.
+impl std::str::FromStr for Resolver {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "system" => Ok(Resolver::System),
+            "cloudflare" => Ok(Resolver::cloudflare()),
+            "google" => Ok(Resolver::google()),
+            "quad9" => Ok(Resolver::quad9()),
+            url if url.starts_with("https://") => Ok(Resolver::Doh(url.to_string())),
+            _ => Err(format!(
+                "unknown resolver \"{s}\"; expected system, cloudflare, google, quad9, or an https:// url"
+            )),
+        }
+    }
+}
+
+/// What each of a set of resolvers returned for a record, such as to show how far a change
+/// has spread during a cutover.
+///
+/// Printing a report with `{}` gives a table with a line per resolver and a summary such as
+/// `8/10 resolvers updated`.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct PropagationReport {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: RecordType,
+    /// The answer being waited for, as DNS presents it, such as `10 mail.example.com` for an
+    /// MX record.
+    pub expected: String,
+    pub resolvers: Vec<ResolverAnswer>,
+}
+
+/// One resolver's part of a [PropagationReport].
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct ResolverAnswer {
+    #[serde(serialize_with = "serialize_display")]
+    pub resolver: Resolver,
+    pub answers: Vec<String>,
+    /// Why the lookup failed, in which case there are no answers.
+    pub error: Option<String>,
+    /// How long the lookup took.
+    #[serde(rename = "rtt_ms", serialize_with = "serialize_millis")]
+    pub rtt: Duration,
+    /// Whether the expected answer was among the answers.
+    pub matched: bool,
+}
+
+impl PropagationReport {
+    /// Asks every resolver at once for the records of `name` with type `ty`.
+    pub fn check(resolvers: &[Resolver], name: &str, ty: RecordType, expected: &str) -> Self {
+        let resolvers = resolvers
+            .iter()
+            .zip(lookup_all(resolvers, name, ty))
+            .map(|(resolver, (answers, rtt))| {
+                let (answers, error) = match answers {
+                    Ok(answers) => (answers, None),
+                    Err(msg) => (Vec::new(), Some(msg.to_string())),
+                };
+                let matched = answers.iter().any(|x| answer_matches(ty, expected, "", x));
+                ResolverAnswer {
+                    resolver: resolver.clone(),
+                    answers,
+                    error,
+                    rtt,
+                    matched,
+                }
+            })
+            .collect();
+        Self {
+            name: name.to_string(),
+            ty,
+            expected: expected.to_string(),
+            resolvers,
+        }
+    }
+
+    /// Checks every 5 seconds until all resolvers return the expected answer or `timeout`
+    /// passes, calling `progress` with each report. Returns the last report.
+    pub fn wait(
+        resolvers: &[Resolver],
+        name: &str,
+        ty: RecordType,
+        expected: &str,
+        timeout: Duration,
+        progress: impl FnMut(&Self),
+    ) -> Self {
+        Self::wait_with_clock(
+            &SystemClock,
+            resolvers,
+            name,
+            ty,
+            expected,
+            timeout,
+            progress,
+        )
+    }
+
+    /// Like [PropagationReport::wait], but measures time and sleeps on `clock`.
+    pub fn wait_with_clock(
+        clock: &dyn Clock,
+        resolvers: &[Resolver],
+        name: &str,
+        ty: RecordType,
+        expected: &str,
+        timeout: Duration,
+        mut progress: impl FnMut(&Self),
+    ) -> Self {
+        let start = clock.now();
+        loop {
+            let report = Self::check(resolvers, name, ty, expected);
+            progress(&report);
+            let elapsed = clock.now().saturating_duration_since(start);
+            if report.is_complete() || elapsed >= timeout {
+                return report;
+            }
+            clock.sleep(POLL_INTERVAL.min(timeout - elapsed));
+        }
+    }
+
+    /// The number of resolvers which returned the expected answer.
+    pub fn matched(&self) -> usize {
+        self.resolvers.iter().filter(|x| x.matched).count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.matched() == self.resolvers.len()
+    }
+}
+
+impl std::fmt::Display for PropagationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let resolvers: Vec<String> = self
+            .resolvers
+            .iter()
+            .map(|x| x.resolver.to_string())
+            .collect();
+        let width = resolvers.iter().map(String::len).max().unwrap_or(0);
+        for (resolver, answer) in resolvers.iter().zip(&self.resolvers) {
+            let status = if answer.matched { "ok" } else { "--" };
+            let answers = match &answer.error {
+                Some(msg) => format!("error: {msg}"),
+                None if answer.answers.is_empty() => "(no records)".to_string(),
+                None => answer.answers.join(", "),
+            };
+            writeln!(
+                f,
+                "{status} {resolver:<width$} {:>5}ms  {answers}",
+                answer.rtt.as_millis()
+            )?;
+        }
+        writeln!(
+            f,
+            "{}/{} resolvers updated",
+            self.matched(),
+            self.resolvers.len()
+        )
+    }
+}
+
+fn serialize_display<S: serde::Serializer>(
+    value: &impl std::fmt::Display,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn serialize_millis<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_u128(duration.as_millis())
+}
+
+/// Waits until none of `resolvers` return `record`, such as after deleting an ACME challenge
+/// or the old address of a cutover.
+///
+/// Fails with [Error::PropagationTimeout] if some still return it after `timeout`. Resolvers
+/// which fail to answer count as still returning it, except that [Error::UnsupportedLookup]
+/// is returned straight away.
+pub fn wait_for_absence(
+    record: &DnsRecord,
+    resolvers: &[Resolver],
+    timeout: Duration,
+) -> Result<()> {
+    wait_for_absence_with_clock(&SystemClock, record, resolvers, timeout)
+}
+
+/// Like [wait_for_absence], but measures time and sleeps on `clock`.
+pub fn wait_for_absence_with_clock(
+    clock: &dyn Clock,
+    record: &DnsRecord,
+    resolvers: &[Resolver],
+    timeout: Duration,
+) -> Result<()> {
+    let start = clock.now();
+    loop {
+        let answers = lookup_all(resolvers, &record.name, record.ty);
+        let mut pending = 0;
+        for (answers, _) in answers {
+            match answers {
+                Ok(answers)
+                    if !answers
+                        .iter()
+                        .any(|x| answer_matches(record.ty, &record.content, &record.prio, x)) => {}
+                Err(Error::UnsupportedLookup { ty }) => {
+                    return Err(Error::UnsupportedLookup { ty })
+                }
+                _ => pending += 1,
+            }
+        }
+        if pending == 0 {
+            return Ok(());
+        }
+        let elapsed = clock.now().saturating_duration_since(start);
+        if elapsed >= timeout {
+            return Err(Error::PropagationTimeout {
+                name: record.name.clone(),
+                ty: record.ty,
+                pending,
+            });
+        }
+        clock.sleep(POLL_INTERVAL.min(timeout - elapsed));
+    }
+}
+
+/// Looks up a record with every resolver at once, timing each lookup.
+fn lookup_all(
+    resolvers: &[Resolver],
+    name: &str,
+    ty: RecordType,
+) -> Vec<(Result<Vec<String>>, Duration)> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = resolvers
+            .iter()
+            .map(|resolver| {
+                scope.spawn(move || {
+                    let start = Instant::now();
+                    (resolver.lookup(name, ty), start.elapsed())
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|x| std::panic::resume_unwind(x))
+            })
+            .collect()
+    })
+}
+
+/// Whether `answer`, as a resolver presents it, is the data of a record with porkbun's
+/// `content` and `prio`.
+///
+/// Case, trailing dots, and TXT quoting are ignored, and the priority porkbun keeps apart is
+/// expected at the front of MX, SRV, HTTPS, and SVCB answers.
+/// ```
+/// use porkbun::{resolve::answer_matches, RecordType};
+///
+/// assert!(answer_matches(RecordType::Mx, "mail.example.com", "10", "10 Mail.example.com."));
+/// assert!(answer_matches(RecordType::Txt, "v=spf1 -all", "0", "\"v=spf1 -all\""));
+/// ```
+pub fn answer_matches(ty: RecordType, content: &str, prio: &str, answer: &str) -> bool {
+    let normalize = |text: &str| {
+        let text = match ty {
+            // Long TXT values are split into several quoted strings.
+            RecordType::Txt => text.trim().trim_matches('"').replace("\" \"", ""),
+            _ => text.trim().to_ascii_lowercase(),
+        };
+        text.split_whitespace()
+            .map(|x| x.trim_end_matches('.'))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let content = match ty {
+        RecordType::Mx | RecordType::Srv | RecordType::Https | RecordType::Svcb => {
+            format!("{} {content}", prio.trim())
+        }
+        _ => content.to_string(),
+    };
+    normalize(&content) == normalize(answer)
+}
+
+fn doh_lookup(url: &str, name: &str, ty: RecordType) -> Result<Vec<String>> {
+    #[derive(Debug, serde::Deserialize)]
+    struct Response {
+        #[serde(rename = "Status")]
+        status: u16,
+        #[serde(rename = "Answer", default)]
+        answer: Vec<Answer>,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Answer {
+        #[serde(rename = "type")]
+        ty: u16,
+        data: String,
+    }
+
+    let response = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?
+        .get(url)
+        .query(&[("name", name), ("type", ty.as_str())])
+        .header(reqwest::header::ACCEPT, "application/dns-json")
+        .send()?
+        .error_for_status()?
+        .text()?;
+    let response: Response = crate::parse_response(response)?;
+    #[cfg(feature = "tracing_debug")]
+    tracing::debug!("parsed response: {response:?}");
+
+    // 3 is NXDOMAIN, meaning the name has no records at all.
+    if response.status != 0 && response.status != 3 {
+        return Err(Error::ResolveFailed {
+            name: name.to_string(),
+            rcode: response.status,
+        });
+    }
+    // The answer also includes any CNAME records which were followed.
+    let code = type_code(ty);
+    Ok(response
+        .answer
+        .into_iter()
+        .filter(|x| Some(x.ty) == code)
+        .map(|x| x.data)
+        .collect())
+}
+
+/// The numeric code DNS uses for a record type.
+fn type_code(ty: RecordType) -> Option<u16> {
+    match ty {
+        RecordType::A => Some(1),
+        RecordType::Ns => Some(2),
+        RecordType::Cname => Some(5),
+        RecordType::Mx => Some(15),
+        RecordType::Txt => Some(16),
+        RecordType::Aaaa => Some(28),
+        RecordType::Srv => Some(33),
+        RecordType::Tlsa => Some(52),
+        RecordType::Svcb => Some(64),
+        RecordType::Https => Some(65),
+        RecordType::Caa => Some(257),
+        // ALIAS records are flattened into addresses, so never appear in answers.
+        RecordType::Alias => None,
+    }
+}
@@ -0,0 +1,116 @@
+//! Exponential backoff, as used by [Client](crate::Client) and available to anything else
+//! which wants to share its policy.
+//!
+//! ```
+//! use porkbun::retry::{retry_with, Backoff, Retry};
+//! use std::time::Duration;
+//!
+//! let backoff = Backoff {
+//!     initial_delay: Duration::from_millis(1),
+//!     ..Backoff::default()
+//! };
+//! let result: Result<u32, &str> = retry_with(
+//!     &backoff,
+//!     |attempt| if attempt < 2 { Err("busy") } else { Ok(attempt) },
+//!     |_| Retry::Backoff,
+//! );
+//! assert_eq!(result, Ok(2));
+//! ```
+
+use crate::clock::{Clock, SystemClock};
+use std::time::Duration;
+
+/// How a [Client](crate::Client) retries requests which were rejected by porkbun's rate limit.
+///
+/// If porkbun says how long to wait, that delay is used instead of the backoff,
+/// but is still capped at `max_delay`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Backoff {
+    /// The total number of attempts made, including the first.
+    /// A value of 1 disables retries.
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// How much the delay grows after each attempt.
+    pub multiplier: f64,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl Backoff {
+    /// Never retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// The delay before retrying after `attempt` (counting from 0) failed.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.max(1.0).powi(attempt.min(64) as i32);
+        Duration::try_from_secs_f64(self.initial_delay.as_secs_f64() * factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+}
+
+/// Whether [retry_with] should try again after an attempt fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Retry {
+    /// Give up, returning the error.
+    Stop,
+    /// Wait as long as the [Backoff] says.
+    Backoff,
+    /// Wait for a specific time, such as one from a `Retry-After` header,
+    /// capped at [Backoff::max_delay].
+    After(Duration),
+}
+
+/// Calls `operation` with the attempt number (counting from 0) until it succeeds,
+/// `retry` says to stop, or `backoff.max_attempts` is reached.
+///
+/// The thread sleeps between attempts.
+pub fn retry_with<T, E>(
+    backoff: &Backoff,
+    operation: impl FnMut(u32) -> Result<T, E>,
+    retry: impl FnMut(&E) -> Retry,
+) -> Result<T, E> {
+    retry_with_clock(&SystemClock, backoff, operation, retry)
+}
+
+/// Like [retry_with], but sleeps on `clock`.
+pub fn retry_with_clock<T, E>(
+    clock: &dyn Clock,
+    backoff: &Backoff,
+    mut operation: impl FnMut(u32) -> Result<T, E>,
+    mut retry: impl FnMut(&E) -> Retry,
+) -> Result<T, E> {
+    let mut attempt = 0;
+    loop {
+        let error = match operation(attempt) {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+        if attempt + 1 >= backoff.max_attempts {
+            return Err(error);
+        }
+        let delay = match retry(&error) {
+            Retry::Stop => return Err(error),
+            Retry::Backoff => backoff.delay(attempt),
+            Retry::After(delay) => delay.min(backoff.max_delay),
+        };
+        #[cfg(feature = "tracing")]
+        tracing::warn!("attempt {} failed, retrying in {delay:?}", attempt + 1);
+        clock.sleep(delay);
+        attempt += 1;
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{Client, Error, Result};
+use std::io::Write;
+use std::path::Path;
+
+/// Key rotation
+impl Client {
+    /// Replaces the keys in the key file at `path` with a new pair, returning a client with
+    /// this one's settings which uses them.
+    ///
+    /// The new keys are checked with [Client::ping] before the file is touched, so a mistyped
+    /// key never replaces working ones. The file is then replaced in a single step, read back,
+    /// and checked again, restoring the previous file if that fails. Only revoke the old keys
+    /// once this succeeds.
+    ///
+    /// Only plaintext key files are supported. Fails with [Error::InvalidKey], without touching
+    /// the file, if it is encrypted with age, since it would otherwise be replaced with
+    /// plaintext keys.
+    /// ```no_run
+    /// let client = porkbun::Client::open_keys("keys.json")?;
+    /// let client = client.rotate_keys("keys.json", "pk1_...", "sk1_...")?;
+    /// # Ok::<(), porkbun::Error>(())
+    /// ```
+    pub fn rotate_keys(
+        &self,
+        path: impl AsRef<Path>,
+        api_key: &str,
+        secret_api_key: &str,
+    ) -> Result<Client> {
+        let path = path.as_ref();
+        let previous = std::fs::read(path)?;
+        if is_encrypted(&previous) {
+            return Err(Error::InvalidKey {
+                reason: "only plaintext key files can be rotated, but this one is encrypted"
+                    .to_string(),
+            });
+        }
+        let key_file = serde_json::json!({
+            "apikey": api_key,
+            "secretapikey": secret_api_key,
+        })
+        .to_string();
+        let mut rotated = self.clone();
+        rotated.api_key = api_key.into();
+        rotated.secret_api_key = secret_api_key.into();
+        rotated.key_file = key_file.as_str().into();
+        rotated.ping()?;
+
+        replace_file(path, key_file.as_bytes())?;
+        let verified = match std::fs::read_to_string(path) {
+            Ok(written) if written == key_file => rotated.ping().map(|_| ()),
+            Ok(_) => Err(Error::InvalidKey {
+                reason: "the key file changed while it was being replaced".to_string(),
+            }),
+            Err(error) => Err(error.into()),
+        };
+        if let Err(error) = verified {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("new keys failed after being saved, restoring the old ones: {error}");
+            replace_file(path, &previous)?;
+            return Err(error);
+        }
+        Ok(rotated)
+    }
+}
+
+/// Whether a key file is encrypted with age, in either its binary or armored form.
+fn is_encrypted(file: &[u8]) -> bool {
+    let file = file.trim_ascii_start();
+    file.starts_with(b"age-encryption.org/")
+        || file.starts_with(b"-----BEGIN AGE ENCRYPTED FILE-----")
+}
+
+/// Writes `text` beside `path` and renames it over `path`, so that readers see either the old
+/// or the new file. On Unix, only the owner can access the new file.
+fn replace_file(path: &Path, text: &[u8]) -> std::io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&temporary)?;
+    file.write_all(text)?;
+    file.sync_all()?;
+    std::fs::rename(&temporary, path)
+}
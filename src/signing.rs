@@ -0,0 +1,56 @@
+//! Detached ed25519 signatures for zone snapshots, so that a backup which was tampered with
+//! is caught before it's restored.
+//!
+//! Keys are PKCS#8 PEM files, such as those made by
+//! `openssl genpkey -algorithm ed25519 -out snapshot.pem` and
+//! `openssl pkey -in snapshot.pem -pubout -out snapshot.pub.pem`.
+//! Signatures are hex encoded.
+
+use crate::{Error, Result};
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Signature, Signer, Verifier};
+use std::fmt::Write;
+
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+/// Reads a private key from a PEM file's contents.
+pub fn signing_key(pem: &str) -> Result<SigningKey> {
+    SigningKey::from_pkcs8_pem(pem).map_err(|msg| Error::InvalidKey {
+        reason: msg.to_string(),
+    })
+}
+
+/// Reads a public key from a PEM file's contents.
+pub fn verifying_key(pem: &str) -> Result<VerifyingKey> {
+    VerifyingKey::from_public_key_pem(pem).map_err(|msg| Error::InvalidKey {
+        reason: msg.to_string(),
+    })
+}
+
+/// Signs the exact bytes of a snapshot.
+pub fn sign(key: &SigningKey, snapshot: &[u8]) -> String {
+    let mut hex = String::with_capacity(Signature::BYTE_SIZE * 2);
+    for byte in key.sign(snapshot).to_bytes() {
+        write!(hex, "{byte:02x}").ok();
+    }
+    hex
+}
+
+/// Checks a signature made by [sign], failing with [Error::BadSignature] if the snapshot was
+/// changed or signed by another key.
+pub fn verify(key: &VerifyingKey, snapshot: &[u8], signature: &str) -> Result<()> {
+    let signature = signature.trim();
+    let bytes: Option<Vec<u8>> = (signature.len() == Signature::BYTE_SIZE * 2)
+        .then(|| {
+            (0..signature.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(signature.get(i..i + 2)?, 16).ok())
+                .collect()
+        })
+        .flatten();
+    let signature = bytes
+        .and_then(|x| Signature::from_slice(&x).ok())
+        .ok_or(Error::BadSignature)?;
+    key.verify(snapshot, &signature)
+        .map_err(|_| Error::BadSignature)
+}
@@ -0,0 +1,161 @@
+use crate::{canonicalize, DnsRecord, NewRecord, ZoneSpec};
+use std::fmt::Write;
+
+/// The records of a zone at one point in time, such as a spec written by a backup.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ZoneSnapshot {
+    pub domain: String,
+    pub records: Vec<NewRecord>,
+}
+
+/// How a zone changed between two [snapshots](ZoneSnapshot).
+///
+/// Printing a summary with `{}` gives a line per change, like a [PlanRenderer](crate::PlanRenderer)
+/// plan; [ChangeSummary::json] gives the same for other tools.
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct ChangeSummary {
+    pub domain: String,
+    pub added: Vec<NewRecord>,
+    pub removed: Vec<NewRecord>,
+    /// Records with the same name and type, but different content, ttl, or priority, as
+    /// `(before, after)`.
+    pub changed: Vec<(NewRecord, NewRecord)>,
+}
+
+impl ZoneSnapshot {
+    pub fn from_records(domain: &str, records: &[DnsRecord]) -> Self {
+        Self::from_spec(&ZoneSpec::from_records(domain, records))
+    }
+
+    pub fn from_spec(spec: &ZoneSpec) -> Self {
+        Self {
+            domain: spec.domain.clone(),
+            records: spec.to_records(),
+        }
+    }
+
+    /// Compares this snapshot with an `older` one of the same zone.
+    ///
+    /// Records with the same name, type, and [canonical](canonicalize) content are the same
+    /// record. Of the rest,
+    /// records with the same name and type are paired up as changed, and any left over were
+    /// added or removed.
+    /// ```
+    /// use porkbun::{NewRecord, RecordType, ZoneSnapshot};
+    ///
+    /// let record = |content: &str| NewRecord {
+    ///     name: Some("www".into()),
+    ///     ty: RecordType::A,
+    ///     content: content.into(),
+    ///     ttl: None,
+    ///     prio: None,
+    /// };
+    /// let older = ZoneSnapshot {
+    ///     domain: "example.com".into(),
+    ///     records: vec![record("203.0.113.7")],
+    /// };
+    /// let newer = ZoneSnapshot {
+    ///     records: vec![record("203.0.113.8")],
+    ///     ..older.clone()
+    /// };
+    /// let summary = newer.diff(&older);
+    /// assert_eq!(summary.changed.len(), 1);
+    /// assert!(older.diff(&older).is_empty());
+    /// ```
+    pub fn diff(&self, older: &ZoneSnapshot) -> ChangeSummary {
+        let mut removed: Vec<&NewRecord> = older.records.iter().collect();
+        let mut summary = ChangeSummary {
+            domain: self.domain.clone(),
+            ..ChangeSummary::default()
+        };
+        let same_name_type = |a: &NewRecord, b: &NewRecord| a.name == b.name && a.ty == b.ty;
+        let canonical = |x: &NewRecord| NewRecord {
+            content: canonicalize(x.ty, &x.content),
+            ..x.clone()
+        };
+
+        let mut leftover = Vec::new();
+        for record in &self.records {
+            let found = removed
+                .iter()
+                .position(|x| canonical(x) == canonical(record));
+            match found {
+                Some(i) => {
+                    let before = removed.remove(i);
+                    if canonical(before) != canonical(record) {
+                        summary.changed.push((before.clone(), record.clone()));
+                    }
+                }
+                None => leftover.push(record),
+            }
+        }
+        for record in leftover {
+            match removed.iter().position(|x| same_name_type(x, record)) {
+                Some(i) => summary
+                    .changed
+                    .push((removed.remove(i).clone(), record.clone())),
+                None => summary.added.push(record.clone()),
+            }
+        }
+        summary.removed = removed.into_iter().cloned().collect();
+        summary
+    }
+}
+
+impl ChangeSummary {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    pub fn json(&self) -> String {
+        // Records are plain strings and numbers, so this can't fail.
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+impl std::fmt::Display for ChangeSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let domain = &self.domain;
+        let name = |record: &NewRecord| match &record.name {
+            Some(name) => format!("{name}.{domain}"),
+            None => domain.clone(),
+        };
+        let details = |record: &NewRecord| {
+            let mut details = record.content.clone();
+            if let Some(prio) = record.prio.as_deref().filter(|x| *x != "0") {
+                write!(details, " (priority {prio})").ok();
+            }
+            if let Some(ttl) = record.ttl {
+                write!(details, " (ttl {ttl})").ok();
+            }
+            details
+        };
+
+        if self.is_empty() {
+            return writeln!(f, "No changes to {domain} since the last snapshot.");
+        }
+        for record in &self.added {
+            writeln!(f, "+ {} {} {}", record.ty, name(record), details(record))?;
+        }
+        for (before, after) in &self.changed {
+            writeln!(
+                f,
+                "~ {} {} {} -> {}",
+                after.ty,
+                name(after),
+                details(before),
+                details(after)
+            )?;
+        }
+        for record in &self.removed {
+            writeln!(f, "- {} {} {}", record.ty, name(record), details(record))?;
+        }
+        writeln!(
+            f,
+            "\n{} added, {} changed, {} removed since the last snapshot.",
+            self.added.len(),
+            self.changed.len(),
+            self.removed.len()
+        )
+    }
+}
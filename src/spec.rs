@@ -0,0 +1,553 @@
+use crate::{
+    check_apex_cname, check_svcb, replace_whole, subdomain_of, Client, DnsRecord, Error, NewRecord,
+    RecordType, Result, Ttl,
+};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// A declarative description of every record a domain should have.
+///
+/// Specs are usually kept in TOML files, which [ZoneSpec::to_toml] writes:
+/// ```toml
+/// domain = "example.com"
+///
+/// [[records]]
+/// type = "A"
+/// content = "203.0.113.7"
+/// ttl = 600
+///
+/// [[records]]
+/// # Mail is handled by the office server.
+/// type = "MX"
+/// content = "mail.example.com"
+/// prio = 10
+/// ```
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ZoneSpec {
+    pub domain: String,
+    /// Files of records shared between specs, which this zone also has.
+    ///
+    /// Specs don't read files themselves; see [ZoneSpec::resolve_includes].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Names of [SpecSet] blocks whose records this zone also has.
+    #[serde(default, rename = "use", skip_serializing_if = "Vec::is_empty")]
+    pub uses: Vec<String>,
+    /// A wildcard record, answering for every subdomain which isn't in the spec.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wildcard: Option<WildcardSpec>,
+    #[serde(default)]
+    pub records: Vec<RecordSpec>,
+}
+
+/// The `*.<domain>` record of a [ZoneSpec], such as to point every unlisted subdomain at a
+/// default host:
+/// ```toml
+/// [wildcard]
+/// type = "A"
+/// content = "203.0.113.7"
+/// ```
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WildcardSpec {
+    #[serde(rename = "type")]
+    pub ty: RecordType,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<Ttl>,
+}
+
+impl WildcardSpec {
+    /// A copy of the wildcard record at the subdomain `name`.
+    pub fn to_record(&self, name: &str) -> NewRecord {
+        NewRecord {
+            name: Some(name.to_string()),
+            ty: self.ty,
+            content: self.content.clone(),
+            ttl: self.ttl,
+            prio: None,
+        }
+    }
+}
+
+/// Several zones described in one file, which can share blocks of records.
+/// ```toml
+/// [[blocks.mail]]
+/// type = "MX"
+/// content = "mx.example.net"
+/// prio = 10
+///
+/// [[zones]]
+/// domain = "example.com"
+/// use = ["mail"]
+///
+/// [[zones]]
+/// domain = "example.org"
+/// use = ["mail"]
+///
+/// [[zones.records]]
+/// type = "A"
+/// content = "203.0.113.7"
+/// ```
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpecSet {
+    /// Named groups of records. Their names are relative to whichever zone uses them.
+    #[serde(default)]
+    pub blocks: BTreeMap<String, Vec<RecordSpec>>,
+    #[serde(default)]
+    pub zones: Vec<ZoneSpec>,
+}
+
+impl SpecSet {
+    /// The JSON schema of files describing several zones, for editors to check them with.
+    #[cfg(feature = "schema")]
+    pub fn json_schema() -> serde_json::Value {
+        schemars::schema_for!(SpecSet).to_value()
+    }
+
+    /// Adds the records of the blocks each zone uses to the zone, ahead of its own records.
+    ///
+    /// Fails if a zone uses a block which doesn't exist.
+    pub fn resolve(self) -> Result<Vec<ZoneSpec>> {
+        let blocks = self.blocks;
+        self.zones
+            .into_iter()
+            .map(|mut zone| {
+                let mut records = Vec::new();
+                for name in zone.uses.drain(..) {
+                    let block = blocks
+                        .get(&name)
+                        .ok_or_else(|| Error::UnknownBlock { name })?;
+                    records.extend(block.iter().cloned());
+                }
+                records.append(&mut zone.records);
+                zone.records = records;
+                Ok(zone)
+            })
+            .collect()
+    }
+}
+
+/// A single record of a [ZoneSpec].
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecordSpec {
+    /// The subdomain of the record, or `None` for the domain itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub ty: RecordType,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<Ttl>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prio: Option<u16>,
+    /// The record's notes, which [ZoneSpec::to_toml] writes as a comment.
+    /// Comments aren't read back, so this is always `None` for parsed specs.
+    #[serde(skip)]
+    pub notes: Option<String>,
+}
+
+impl RecordSpec {
+    pub fn to_record(&self) -> NewRecord {
+        NewRecord {
+            name: self.name.clone(),
+            ty: self.ty,
+            content: self.content.clone(),
+            ttl: self.ttl,
+            prio: self.prio.map(|x| x.to_string()),
+        }
+    }
+}
+
+impl From<&NewRecord> for RecordSpec {
+    fn from(record: &NewRecord) -> Self {
+        Self {
+            name: record.name.clone(),
+            ty: record.ty,
+            content: record.content.clone(),
+            ttl: record.ttl,
+            prio: record.prio.as_deref().and_then(|x| prio(record.ty, x)),
+            notes: None,
+        }
+    }
+}
+
+/// A mistake in one record of a [ZoneSpec], found by [ZoneSpec::validate].
+#[derive(Debug)]
+pub struct SpecProblem {
+    /// The index of the record in [ZoneSpec::records].
+    pub record: usize,
+    pub error: Error,
+}
+
+impl std::fmt::Display for SpecProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "record {}: {}", self.record + 1, self.error)
+    }
+}
+
+/// Porkbun reports a priority of 0 for types which don't have one, so it's only kept for
+/// types which do.
+fn prio(ty: RecordType, prio: &str) -> Option<u16> {
+    match ty {
+        RecordType::Mx | RecordType::Srv | RecordType::Https | RecordType::Svcb => {
+            prio.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+impl ZoneSpec {
+    /// The JSON schema of spec files, for editors to check them with.
+    #[cfg(feature = "schema")]
+    pub fn json_schema() -> serde_json::Value {
+        schemars::schema_for!(ZoneSpec).to_value()
+    }
+
+    /// Describes the `records` of `domain` as they are.
+    pub fn from_records(domain: &str, records: &[DnsRecord]) -> Self {
+        let records = records
+            .iter()
+            .map(|record| RecordSpec {
+                name: subdomain_of(&record.name, domain).map(str::to_string),
+                ty: record.ty,
+                content: record.content.clone(),
+                ttl: record.ttl.parse().ok(),
+                prio: prio(record.ty, &record.prio),
+                notes: record.notes.clone().filter(|x| !x.is_empty()),
+            })
+            .collect();
+        Self {
+            domain: domain.to_string(),
+            include: Vec::new(),
+            uses: Vec::new(),
+            wildcard: None,
+            records,
+        }
+    }
+
+    /// Snapshots the current records of `domain`, such as to start managing an existing zone
+    /// with a spec file.
+    pub fn from_live(client: &Client, domain: &str) -> Result<Self> {
+        Ok(Self::from_records(domain, &client.fetch_records(domain)?))
+    }
+
+    /// Adds the records of each included file to the zone, ahead of its own records.
+    ///
+    /// `load` is called with each entry of [ZoneSpec::include], such as to read and parse a
+    /// file relative to the spec's own.
+    pub fn resolve_includes<E>(
+        &mut self,
+        mut load: impl FnMut(&str) -> std::result::Result<Vec<RecordSpec>, E>,
+    ) -> std::result::Result<(), E> {
+        let mut records = Vec::new();
+        for include in self.include.drain(..) {
+            records.extend(load(&include)?);
+        }
+        records.append(&mut self.records);
+        self.records = records;
+        Ok(())
+    }
+
+    /// Finds records porkbun would reject or resolvers would ignore, without contacting
+    /// porkbun: malformed addresses and HTTPS/SVCB records, ttls below the minimum, CNAME
+    /// records at the apex or beside other records, and duplicates.
+    /// ```
+    /// let mut spec = porkbun::ZoneSpec {
+    ///     domain: "example.com".into(),
+    ///     ..Default::default()
+    /// };
+    /// spec.records.push(porkbun::RecordSpec {
+    ///     name: None,
+    ///     ty: porkbun::RecordType::A,
+    ///     content: "203.0.113.300".into(),
+    ///     ttl: None,
+    ///     prio: None,
+    ///     notes: None,
+    /// });
+    /// assert_eq!(spec.validate().len(), 1);
+    /// ```
+    pub fn validate(&self) -> Vec<SpecProblem> {
+        let mut problems = Vec::new();
+        for (index, record) in self.records.iter().enumerate() {
+            let mut problem = |error| {
+                problems.push(SpecProblem {
+                    record: index,
+                    error,
+                })
+            };
+            let name = record.name.as_deref().filter(|x| !x.is_empty());
+            let full_name = match name {
+                Some(name) => format!("{name}.{}", self.domain),
+                None => self.domain.clone(),
+            };
+            let invalid = |reason| Error::InvalidContent {
+                ty: record.ty,
+                content: record.content.clone(),
+                reason,
+            };
+            match record.ty {
+                RecordType::A if record.content.parse::<Ipv4Addr>().is_err() => {
+                    problem(invalid("not an ipv4 address"))
+                }
+                RecordType::Aaaa if record.content.parse::<Ipv6Addr>().is_err() => {
+                    problem(invalid("not an ipv6 address"))
+                }
+                _ => {}
+            }
+            let prio = record.prio.map(|x| x.to_string());
+            if let Err(error) = check_svcb(record.ty, &record.content, prio.as_deref()) {
+                problem(error);
+            }
+            if let Err(error) = check_apex_cname(&self.domain, record.ty, name) {
+                problem(error);
+            }
+            if let Some(ttl) = record.ttl.filter(|x| *x < Ttl::MIN) {
+                problem(Error::TtlTooLow {
+                    ttl,
+                    minimum: Ttl::MIN,
+                });
+            }
+
+            // Each pair is reported once, at the later record.
+            let earlier = &self.records[..index];
+            let same_name = |x: &&RecordSpec| x.name.as_deref().filter(|x| !x.is_empty()) == name;
+            let conflict = |reason| Error::SpecConflict {
+                name: full_name.clone(),
+                ty: record.ty,
+                reason,
+            };
+            if self.wildcard.is_some() && name == Some("*") {
+                problem(conflict("is also set by the spec's wildcard"));
+            }
+            if earlier
+                .iter()
+                .filter(same_name)
+                .any(|x| x.ty == record.ty && x.content == record.content)
+            {
+                problem(conflict("is a duplicate"));
+            } else if earlier
+                .iter()
+                .filter(same_name)
+                .any(|x| x.ty == RecordType::Cname || record.ty == RecordType::Cname)
+            {
+                problem(conflict(
+                    "shares its name with a CNAME record, which must be the only record there",
+                ));
+            }
+        }
+        problems
+    }
+
+    /// The records the zone should have.
+    ///
+    /// With a [wildcard](ZoneSpec::wildcard), this includes the `*` record and a copy of it at
+    /// each of the [shadowed names](ZoneSpec::shadowed_names), so that they keep answering
+    /// like the rest of the zone. A CNAME can't be copied beside other records, so those
+    /// names are left as they are.
+    pub fn to_records(&self) -> Vec<NewRecord> {
+        let mut records: Vec<NewRecord> = self.records.iter().map(RecordSpec::to_record).collect();
+        if let Some(wildcard) = &self.wildcard {
+            records.push(wildcard.to_record("*"));
+            if wildcard.ty != RecordType::Cname {
+                let shadowed = self.shadowed_names();
+                records.extend(shadowed.into_iter().map(|x| wildcard.to_record(x)));
+            }
+        }
+        records
+    }
+
+    /// The subdomains in the spec which hide its wildcard.
+    ///
+    /// A name with any records of its own doesn't match the wildcard, so lookups of the
+    /// wildcard's type find nothing there unless the name has a record of that type or a
+    /// CNAME. Names starting with `_`, such as `_dmarc`, aren't looked up as hosts and are
+    /// left out.
+    /// ```
+    /// use porkbun::{RecordSpec, RecordType, WildcardSpec, ZoneSpec};
+    ///
+    /// let record = |name: &str, ty, content: &str| RecordSpec {
+    ///     name: Some(name.into()),
+    ///     ty,
+    ///     content: content.into(),
+    ///     ttl: None,
+    ///     prio: None,
+    ///     notes: None,
+    /// };
+    /// let spec = ZoneSpec {
+    ///     domain: "example.com".into(),
+    ///     wildcard: Some(WildcardSpec {
+    ///         ty: RecordType::A,
+    ///         content: "203.0.113.7".into(),
+    ///         ttl: None,
+    ///     }),
+    ///     records: vec![
+    ///         record("www", RecordType::A, "203.0.113.8"),
+    ///         record("mail", RecordType::Mx, "mx.example.net"),
+    ///     ],
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(spec.shadowed_names(), ["mail"]);
+    /// // The wildcard, and a copy of it so that mail still has an address.
+    /// assert_eq!(spec.to_records().len(), 4);
+    /// ```
+    pub fn shadowed_names(&self) -> Vec<&str> {
+        let Some(wildcard) = &self.wildcard else {
+            return Vec::new();
+        };
+        let mut names: Vec<&str> = self
+            .records
+            .iter()
+            .filter_map(|x| x.name.as_deref())
+            .filter(|x| !x.is_empty() && !x.starts_with(['*', '_']))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names.retain(|name| {
+            !self.records.iter().any(|x| {
+                x.name.as_deref() == Some(name)
+                    && (x.ty == wildcard.ty || x.ty == RecordType::Cname)
+            })
+        });
+        names
+    }
+
+    /// Replaces `${name}` placeholders in the domain, record names, and contents with
+    /// `lookup(name)`, so that one spec can describe several domains. `$${` is a literal `${`.
+    /// ```
+    /// let mut spec = porkbun::ZoneSpec {
+    ///     domain: "${env}.example.com".into(),
+    ///     ..Default::default()
+    /// };
+    /// spec.interpolate(|name| (name == "env").then(|| "staging".to_string()))?;
+    /// assert_eq!(spec.domain, "staging.example.com");
+    /// # Ok::<(), porkbun::Error>(())
+    /// ```
+    pub fn interpolate(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<()> {
+        self.domain = expand(&self.domain, &lookup)?;
+        for record in &mut self.records {
+            if let Some(name) = &record.name {
+                record.name = Some(expand(name, &lookup)?);
+            }
+            record.content = expand(&record.content, &lookup)?;
+        }
+        if let Some(wildcard) = &mut self.wildcard {
+            wildcard.content = expand(&wildcard.content, &lookup)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces `from` with `to` in record names and contents, such as to point a copy of a
+    /// zone's records at the copy's own domain.
+    ///
+    /// Only whole labels are replaced, so rewriting `example.com` leaves `myexample.com` and
+    /// `example.com.au` alone.
+    /// ```
+    /// let mut spec = porkbun::ZoneSpec::default();
+    /// spec.records.push(porkbun::RecordSpec {
+    ///     name: Some("www".into()),
+    ///     ty: porkbun::RecordType::Cname,
+    ///     content: "example.com".into(),
+    ///     ttl: None,
+    ///     prio: None,
+    ///     notes: None,
+    /// });
+    /// spec.rewrite("example.com", "staging.example.net");
+    /// assert_eq!(spec.records[0].content, "staging.example.net");
+    /// ```
+    pub fn rewrite(&mut self, from: &str, to: &str) {
+        if from.is_empty() {
+            return;
+        }
+        for record in &mut self.records {
+            if let Some(name) = &mut record.name {
+                *name = replace_whole(name, from, to);
+            }
+            record.content = replace_whole(&record.content, from, to);
+        }
+        if let Some(wildcard) = &mut self.wildcard {
+            wildcard.content = replace_whole(&wildcard.content, from, to);
+        }
+    }
+
+    /// Formats the spec as TOML, with each record's notes as a comment above it.
+    pub fn to_toml(&self) -> String {
+        let mut toml = format!("domain = {}\n", string(&self.domain));
+        let list = |items: &[String]| items.iter().map(|x| string(x)).collect::<Vec<_>>();
+        if !self.include.is_empty() {
+            writeln!(toml, "include = [{}]", list(&self.include).join(", ")).ok();
+        }
+        if !self.uses.is_empty() {
+            writeln!(toml, "use = [{}]", list(&self.uses).join(", ")).ok();
+        }
+        if let Some(wildcard) = &self.wildcard {
+            toml += "\n[wildcard]\n";
+            writeln!(toml, "type = {}", string(wildcard.ty.as_str())).ok();
+            writeln!(toml, "content = {}", string(&wildcard.content)).ok();
+            if let Some(ttl) = wildcard.ttl {
+                writeln!(toml, "ttl = {ttl}").ok();
+            }
+        }
+        for record in &self.records {
+            toml += "\n[[records]]\n";
+            for line in record.notes.iter().flat_map(|x| x.lines()) {
+                writeln!(toml, "# {line}").ok();
+            }
+            if let Some(name) = &record.name {
+                writeln!(toml, "name = {}", string(name)).ok();
+            }
+            writeln!(toml, "type = {}", string(record.ty.as_str())).ok();
+            writeln!(toml, "content = {}", string(&record.content)).ok();
+            if let Some(ttl) = record.ttl {
+                writeln!(toml, "ttl = {ttl}").ok();
+            }
+            if let Some(prio) = record.prio {
+                writeln!(toml, "prio = {prio}").ok();
+            }
+        }
+        toml
+    }
+}
+
+fn expand(text: &str, lookup: &impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        expanded += &rest[..start];
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("$${") {
+            expanded += "${";
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let Some(end) = after.find('}') else {
+                return Err(Error::InvalidTemplate {
+                    text: text.to_string(),
+                    reason: "unterminated ${",
+                });
+            };
+            let name = after[..end].trim();
+            expanded += &lookup(name).ok_or_else(|| Error::UndefinedVariable {
+                name: name.to_string(),
+            })?;
+            rest = &after[end + 1..];
+        } else {
+            expanded.push('$');
+            rest = &rest[1..];
+        }
+    }
+    expanded += rest;
+    Ok(expanded)
+}
+
+/// Quotes a TOML basic string, whose escapes are a superset of those JSON uses.
+fn string(text: &str) -> String {
+    serde_json::Value::from(text).to_string()
+}
@@ -0,0 +1,300 @@
+//! Checking SPF policies against the limit of 10 DNS lookups receivers enforce, and
+//! flattening them into addresses to stay under it.
+//!
+//! A flattened policy doesn't follow changes to the providers it included, so it should be
+//! rebuilt regularly.
+
+use crate::resolve::Resolver;
+use crate::{subdomain_of, ChangeSet, DnsRecord, NewRecord, RecordType, Result, TxtRecord};
+
+/// The most DNS lookups receivers make while evaluating a policy (RFC 7208 section 4.6.4).
+pub const LOOKUP_LIMIT: usize = 10;
+
+/// An SPF policy, such as `v=spf1 include:_spf.example.net -all`, split into its terms.
+/// ```
+/// let spf = porkbun::spf::Spf::parse("v=spf1 mx include:_spf.example.net -all").unwrap();
+/// assert_eq!(spf.terms, ["mx", "include:_spf.example.net", "-all"]);
+/// assert_eq!(spf.to_string(), "v=spf1 mx include:_spf.example.net -all");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct Spf {
+    pub terms: Vec<String>,
+}
+
+impl Spf {
+    /// Parses the content of a TXT record, or returns `None` if it isn't an SPF policy.
+    ///
+    /// Quotes are removed, so content can be given either as porkbun stores it or as
+    /// a resolver presents it.
+    pub fn parse(content: &str) -> Option<Self> {
+        let content = unquote(content);
+        let mut terms = content.split_whitespace();
+        if !terms.next()?.eq_ignore_ascii_case("v=spf1") {
+            return None;
+        }
+        Some(Self {
+            terms: terms.map(str::to_string).collect(),
+        })
+    }
+
+    /// Plans replacing the SPF record of `domain` itself with this policy, or creating one
+    /// if there is none.
+    ///
+    /// Policies longer than 255 bytes are split into several strings, as by [TxtRecord::encode].
+    pub fn plan(&self, domain: &str, records: &[DnsRecord]) -> ChangeSet {
+        let existing: Vec<DnsRecord> = records
+            .iter()
+            .filter(|x| x.ty == RecordType::Txt && subdomain_of(&x.name, domain).is_none())
+            .filter(|x| Spf::parse(&x.content).is_some())
+            .cloned()
+            .collect();
+        let desired = NewRecord {
+            name: None,
+            ty: RecordType::Txt,
+            content: TxtRecord::encode(&self.to_string()),
+            ttl: None,
+            prio: None,
+        };
+        ChangeSet::diff(domain, &existing, &[desired])
+    }
+}
+
+impl std::fmt::Display for Spf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("v=spf1")?;
+        for term in &self.terms {
+            write!(f, " {term}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Joins the quoted strings a long TXT record is split into.
+fn unquote(content: &str) -> String {
+    let content = content.trim();
+    if !content.starts_with('"') {
+        return content.to_string();
+    }
+    let mut text = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => text.extend(chars.next()),
+            c if quoted => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// An SPF policy with every include resolved.
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct SpfAnalysis {
+    pub domain: String,
+    /// The policy published at the domain.
+    pub policy: Option<Spf>,
+    /// How many DNS lookups receivers make to evaluate the policy, which fails beyond
+    /// [LOOKUP_LIMIT].
+    pub lookups: usize,
+    /// Every address the policy allows to send mail, as `ip4:` and `ip6:` mechanisms.
+    pub addresses: Vec<String>,
+    /// Terms which can't be flattened into addresses, such as `exists:`, `ptr`, or macros.
+    pub unflattenable: Vec<String>,
+    /// The policy's final `all` term, such as `-all` or `~all`.
+    pub all: Option<String>,
+    /// Problems found along the way, such as includes without an SPF policy.
+    pub problems: Vec<String>,
+}
+
+impl SpfAnalysis {
+    /// Looks up the policy of `domain` and everything it includes.
+    ///
+    /// `resolver` must be able to look up TXT and MX records, so it can't be
+    /// [Resolver::System].
+    pub fn analyze(resolver: &Resolver, domain: &str) -> Result<Self> {
+        let mut analysis = Self {
+            domain: domain.to_string(),
+            ..Self::default()
+        };
+        analysis.policy = lookup_policy(resolver, domain)?;
+        match analysis.policy.clone() {
+            Some(policy) => {
+                analysis.all = analysis.walk(resolver, domain, &policy, 0, true)?;
+            }
+            None => analysis
+                .problems
+                .push(format!("{domain} has no SPF policy")),
+        }
+        let mut seen = std::collections::HashSet::new();
+        analysis.addresses.retain(|x| seen.insert(x.clone()));
+        if analysis.lookups > LOOKUP_LIMIT {
+            analysis.problems.push(format!(
+                "the policy needs {} DNS lookups, but receivers give up after {LOOKUP_LIMIT}",
+                analysis.lookups
+            ));
+        }
+        Ok(analysis)
+    }
+
+    pub fn exceeds_limit(&self) -> bool {
+        self.lookups > LOOKUP_LIMIT
+    }
+
+    /// A policy allowing the same addresses with no lookups at all, or `None` if some terms
+    /// can't be flattened.
+    pub fn flatten(&self) -> Option<Spf> {
+        if !self.unflattenable.is_empty() || self.policy.is_none() {
+            return None;
+        }
+        let mut terms = self.addresses.clone();
+        terms.extend(self.all.clone());
+        Some(Spf { terms })
+    }
+
+    /// Adds the addresses `policy` allows, returning its `all` term.
+    ///
+    /// Only the top level policy's `all` counts; included policies' are ignored, as they are
+    /// by receivers.
+    fn walk(
+        &mut self,
+        resolver: &Resolver,
+        domain: &str,
+        policy: &Spf,
+        depth: usize,
+        top: bool,
+    ) -> Result<Option<String>> {
+        let mut all = None;
+        let mut redirect = None;
+        for term in &policy.terms {
+            let (qualifier, mechanism) = match term.chars().next() {
+                Some(c @ ('+' | '-' | '~' | '?')) => (c, &term[1..]),
+                _ => ('+', term.as_str()),
+            };
+            let lower = mechanism.to_ascii_lowercase();
+            let (name, arg) = match lower.find([':', '=', '/']) {
+                Some(i) => (&lower[..i], &mechanism[i..]),
+                None => (lower.as_str(), ""),
+            };
+            if name == "all" {
+                all = Some(term.clone());
+                continue;
+            }
+            if matches!(name, "include" | "a" | "mx" | "ptr" | "exists" | "redirect") {
+                self.lookups += 1;
+            }
+            // Macros are expanded per message, and other qualifiers exclude addresses.
+            if arg.contains('%') || (qualifier != '+' && name != "redirect") {
+                self.unflattenable.push(term.clone());
+                continue;
+            }
+            let target = arg.strip_prefix([':', '=']).unwrap_or(arg);
+            match name {
+                "ip4" | "ip6" => self.addresses.push(format!("{name}:{target}")),
+                "include" | "redirect" if depth >= LOOKUP_LIMIT => {
+                    // Receivers would have given up long ago, so this is likely a loop.
+                    self.problems.push(format!(
+                        "stopped following {term}, which is nested too deeply"
+                    ));
+                }
+                "include" => match lookup_policy(resolver, target)? {
+                    Some(included) => {
+                        self.walk(resolver, target, &included, depth + 1, false)?;
+                    }
+                    None => self
+                        .problems
+                        .push(format!("{target}, included by {domain}, has no SPF policy")),
+                },
+                "redirect" => redirect = Some(target.to_string()),
+                "a" | "mx" => {
+                    let (host, cidr4, cidr6) = split_cidr(target, domain);
+                    let hosts = match name {
+                        "mx" => resolver
+                            .lookup(&host, RecordType::Mx)?
+                            .iter()
+                            .filter_map(|x| x.split_whitespace().nth(1))
+                            .map(|x| x.trim_end_matches('.').to_string())
+                            .collect(),
+                        _ => vec![host],
+                    };
+                    for host in hosts {
+                        for address in resolver.lookup_addresses(&host)? {
+                            self.addresses.push(match (address, cidr4, cidr6) {
+                                (std::net::IpAddr::V4(x), Some(cidr), _) => {
+                                    format!("ip4:{x}/{cidr}")
+                                }
+                                (std::net::IpAddr::V4(x), None, _) => format!("ip4:{x}"),
+                                (std::net::IpAddr::V6(x), _, Some(cidr)) => {
+                                    format!("ip6:{x}/{cidr}")
+                                }
+                                (std::net::IpAddr::V6(x), _, None) => format!("ip6:{x}"),
+                            });
+                        }
+                    }
+                }
+                // Other modifiers, such as `exp=`, don't affect who may send.
+                _ if arg.starts_with('=') => {}
+                _ => self.unflattenable.push(term.clone()),
+            }
+        }
+        // A redirect only applies to policies without an `all` term.
+        if let (None, Some(target)) = (&all, redirect) {
+            if depth < LOOKUP_LIMIT {
+                if let Some(redirected) = lookup_policy(resolver, &target)? {
+                    return self.walk(resolver, &target, &redirected, depth + 1, top);
+                }
+            }
+            self.problems.push(format!(
+                "{target}, redirected to by {domain}, has no SPF policy"
+            ));
+        }
+        Ok(all.filter(|_| top))
+    }
+}
+
+impl std::fmt::Display for SpfAnalysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.policy {
+            Some(policy) => writeln!(f, "{}: {policy}", self.domain)?,
+            None => writeln!(f, "{}: no SPF policy", self.domain)?,
+        }
+        writeln!(f, "{} DNS lookups (limit {LOOKUP_LIMIT})", self.lookups)?;
+        writeln!(f, "{} addresses allowed", self.addresses.len())?;
+        if !self.unflattenable.is_empty() {
+            writeln!(f, "\nTerms which can't be flattened")?;
+            for term in &self.unflattenable {
+                writeln!(f, "  {term}")?;
+            }
+        }
+        if !self.problems.is_empty() {
+            writeln!(f, "\nProblems")?;
+            for problem in &self.problems {
+                writeln!(f, "  {problem}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn lookup_policy(resolver: &Resolver, domain: &str) -> Result<Option<Spf>> {
+    Ok(resolver
+        .lookup(domain, RecordType::Txt)?
+        .iter()
+        .find_map(|x| Spf::parse(x)))
+}
+
+/// Splits the target of an `a` or `mx` mechanism, such as `example.com/24//64`, into its host
+/// and prefix lengths. The host defaults to `domain`.
+fn split_cidr<'a>(target: &'a str, domain: &'a str) -> (String, Option<&'a str>, Option<&'a str>) {
+    let (rest, cidr6) = match target.split_once("//") {
+        Some((rest, cidr6)) => (rest, Some(cidr6)),
+        None => (target, None),
+    };
+    let (host, cidr4) = match rest.split_once('/') {
+        Some((host, cidr4)) => (host, Some(cidr4)),
+        None => (rest, None),
+    };
+    let host = if host.is_empty() { domain } else { host };
+    (host.to_string(), cidr4, cidr6)
+}
@@ -0,0 +1,193 @@
+use crate::{DnsRecord, Error, Result};
+use std::num::NonZeroU16;
+
+/// The content of an HTTPS or SVCB record (RFC 9460).
+///
+/// These records either alias a name to another, or describe how to connect to a service.
+/// Browsers silently ignore records which mix the two, so the modes are kept apart:
+/// ```
+/// use porkbun::{SvcParam, SvcbRecord};
+/// use std::num::NonZeroU16;
+///
+/// let alias = SvcbRecord::alias("cdn.example.net");
+/// assert_eq!(alias.content(), "cdn.example.net");
+///
+/// let priority = NonZeroU16::new(1).unwrap();
+/// let service = SvcbRecord::service(priority, ".")
+///     .with_param(SvcParam::new("alpn", "h3,h2"));
+/// assert_eq!(service.content(), ". alpn=h3,h2");
+///
+/// // Alias mode can't have parameters.
+/// assert!(SvcbRecord::parse("0", "cdn.example.net alpn=h2").is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SvcbRecord {
+    /// Priority 0: clients should look up `target` instead, like a CNAME which may be
+    /// placed at the apex.
+    Alias { target: String },
+    /// A way of connecting to the service; lower priorities are preferred.
+    ///
+    /// A `target` of `.` means the owner name itself.
+    Service {
+        priority: NonZeroU16,
+        target: String,
+        params: Vec<SvcParam>,
+    },
+}
+
+/// A service parameter, such as `alpn=h2` or `no-default-alpn`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SvcParam {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+impl SvcParam {
+    pub fn new(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: Some(value.into()),
+        }
+    }
+
+    /// A parameter without a value, such as `no-default-alpn`.
+    pub fn flag(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: None,
+        }
+    }
+}
+
+impl std::fmt::Display for SvcParam {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.value {
+            None => f.write_str(&self.key),
+            Some(value) if value.contains(|x: char| x.is_whitespace() || x == '"') => {
+                write!(f, "{}=\"{}\"", self.key, value.replace('"', "\\\""))
+            }
+            Some(value) => write!(f, "{}={value}", self.key),
+        }
+    }
+}
+
+impl SvcbRecord {
+    pub fn alias(target: impl Into<String>) -> Self {
+        SvcbRecord::Alias {
+            target: target.into(),
+        }
+    }
+
+    pub fn service(priority: NonZeroU16, target: impl Into<String>) -> Self {
+        SvcbRecord::Service {
+            priority,
+            target: target.into(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Adds a parameter to a service mode record. Alias mode records are returned unchanged.
+    pub fn with_param(mut self, param: SvcParam) -> Self {
+        if let SvcbRecord::Service { params, .. } = &mut self {
+            params.push(param);
+        }
+        self
+    }
+
+    /// The priority porkbun keeps in the record's `prio` field; 0 for alias mode.
+    pub fn priority(&self) -> u16 {
+        match self {
+            SvcbRecord::Alias { .. } => 0,
+            SvcbRecord::Service { priority, .. } => priority.get(),
+        }
+    }
+
+    pub fn target(&self) -> &str {
+        match self {
+            SvcbRecord::Alias { target } | SvcbRecord::Service { target, .. } => target,
+        }
+    }
+
+    /// The record's content, without the priority.
+    pub fn content(&self) -> String {
+        match self {
+            SvcbRecord::Alias { target } => target.clone(),
+            SvcbRecord::Service { target, params, .. } => {
+                let mut content = target.clone();
+                for param in params {
+                    content += &format!(" {param}");
+                }
+                content
+            }
+        }
+    }
+
+    /// Parses a record's priority and content.
+    ///
+    /// Fails if an alias mode record (priority 0) has parameters, since clients ignore them.
+    pub fn parse(prio: &str, content: &str) -> Result<Self> {
+        let invalid = |reason| Error::InvalidSvcb {
+            content: content.to_string(),
+            reason,
+        };
+        let prio = prio.trim();
+        let priority: u16 = match prio {
+            "" => 0,
+            prio => prio.parse().map_err(|_| invalid("invalid priority"))?,
+        };
+        let mut fields = split_fields(content).ok_or_else(|| invalid("unterminated quote"))?;
+        if fields.is_empty() {
+            return Err(invalid("missing target"));
+        }
+        let target = fields.remove(0);
+
+        let Some(priority) = NonZeroU16::new(priority) else {
+            if !fields.is_empty() {
+                return Err(invalid("alias mode (priority 0) can't have parameters"));
+            }
+            return Ok(SvcbRecord::Alias { target });
+        };
+        let params = fields
+            .into_iter()
+            .map(|field| match field.split_once('=') {
+                Some((key, value)) => SvcParam::new(key, value),
+                None => SvcParam::flag(field),
+            })
+            .collect();
+        Ok(SvcbRecord::Service {
+            priority,
+            target,
+            params,
+        })
+    }
+
+    pub fn from_record(record: &DnsRecord) -> Result<Self> {
+        Self::parse(&record.prio, &record.content)
+    }
+}
+
+/// Splits content at whitespace, keeping quoted values together without their quotes.
+///
+/// Returns `None` if a quote is left open.
+fn split_fields(content: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut chars = content.trim().chars().peekable();
+    while chars.peek().is_some() {
+        let mut field = String::new();
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => quoted = !quoted,
+                '\\' if quoted => field.extend(chars.next()),
+                c if c.is_whitespace() && !quoted => break,
+                c => field.push(c),
+            }
+        }
+        if quoted {
+            return None;
+        }
+        while chars.next_if(|x| x.is_whitespace()).is_some() {}
+        fields.push(field);
+    }
+    Some(fields)
+}
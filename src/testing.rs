@@ -0,0 +1,140 @@
+//! Helpers for testing code which uses this crate without reaching porkbun.
+//!
+//! [MockServer] serves canned responses (such as those in [fixtures]) to a [Client] which
+//! is pointed at it:
+//! ```
+//! use porkbun::testing::{fixtures, MockServer};
+//!
+//! let server = MockServer::start();
+//! server.respond("dns/retrieve", fixtures::RETRIEVE);
+//!
+//! let records = server.client().fetch_records("example.com").unwrap();
+//! assert_eq!(records.len(), 4);
+//! assert_eq!(server.requests()[0].path, "/api/json/v3/dns/retrieve/example.com");
+//! ```
+
+use crate::{Client, ClientBuilder};
+
+/// Porkbun responses in the shape each endpoint returns them,
+/// using documentation addresses and ids in place of real ones.
+pub mod fixtures {
+    /// `ping`, from an ipv4 address.
+    pub const PING: &str = r#"{"status":"SUCCESS","yourIp":"203.0.113.7"}"#;
+    /// `ping`, from an ipv6 address.
+    pub const PING_IPV6: &str = r#"{"status":"SUCCESS","yourIp":"2001:db8::7"}"#;
+    /// `dns/retrieve`, for `example.com`.
+    pub const RETRIEVE: &str = r#"{"status":"SUCCESS","records":[{"id":"106926652","name":"example.com","type":"A","content":"203.0.113.7","ttl":"600","prio":"0","notes":""},{"id":"106926653","name":"example.com","type":"AAAA","content":"2001:db8::7","ttl":"600","prio":"0","notes":""},{"id":"106926654","name":"example.com","type":"MX","content":"mail.example.com","ttl":"600","prio":"10","notes":""},{"id":"106926655","name":"_dmarc.example.com","type":"TXT","content":"v=DMARC1; p=none","ttl":"600","prio":"0","notes":null}]}"#;
+    /// `dns/retrieve`, for a single record by id.
+    pub const RETRIEVE_BY_ID: &str = r#"{"status":"SUCCESS","records":[{"id":"106926652","name":"example.com","type":"A","content":"203.0.113.7","ttl":"600","prio":"0","notes":""}]}"#;
+    /// `dns/retrieveByNameType`, for the A records of `www.example.com`.
+    pub const RETRIEVE_BY_NAME_TYPE: &str = r#"{"status":"SUCCESS","records":[{"id":"106926656","name":"www.example.com","type":"A","content":"203.0.113.7","ttl":"600","prio":"0","notes":""}]}"#;
+    /// `dns/retrieveByNameType`, when no records match.
+    pub const RETRIEVE_EMPTY: &str = r#"{"status":"SUCCESS","records":[]}"#;
+    /// `dns/create`.
+    pub const CREATE: &str = r#"{"status":"SUCCESS","id":106926659}"#;
+    /// `dns/edit`, `dns/editByNameType`, `dns/delete`, and `dns/deleteByNameType`.
+    pub const SUCCESS: &str = r#"{"status":"SUCCESS"}"#;
+    /// Any endpoint, when the api keys are wrong.
+    pub const INVALID_API_KEY: &str = r#"{"status":"ERROR","message":"Invalid API key. (002)"}"#;
+    /// Any endpoint, when api access hasn't been enabled for the domain.
+    pub const DOMAIN_NOT_OPTED_IN: &str =
+        r#"{"status":"ERROR","message":"Domain is not opted in to API access."}"#;
+    /// Any endpoint, when too many requests have been made.
+    pub const RATE_LIMITED: &str =
+        r#"{"status":"ERROR","message":"Rate limit exceeded. Please try again later."}"#;
+}
+
+/// A request received by a [MockServer].
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    pub path: String,
+    pub body: String,
+}
+
+/// A local HTTP server which imitates the porkbun API.
+///
+/// Requests to an endpoint with no response configured receive a 404 error.
+pub struct MockServer {
+    runtime: tokio::runtime::Runtime,
+    server: wiremock::MockServer,
+}
+
+impl MockServer {
+    /// Starts a server on a random local port.
+    ///
+    /// # Panics
+    ///
+    /// If the server can't be started.
+    pub fn start() -> Self {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("failed to start mock server runtime");
+        let server = runtime.block_on(wiremock::MockServer::start());
+        Self { runtime, server }
+    }
+
+    /// The url to pass to [ClientBuilder::base_url].
+    pub fn base_url(&self) -> String {
+        format!("{}/api/json/v3", self.server.uri())
+    }
+
+    /// Creates a client which sends every request to this server.
+    pub fn client(&self) -> Client {
+        self.builder()
+            .build("pk1_test", "sk1_test")
+            .expect("failed to build mock client")
+    }
+
+    /// A client builder which sends every request to this server, for further configuration.
+    pub fn builder(&self) -> ClientBuilder {
+        ClientBuilder::new().base_url(self.base_url())
+    }
+
+    /// Responds to every request to `endpoint`, such as `dns/retrieve`, with `body`.
+    ///
+    /// If an endpoint is given several responses, the first one is used.
+    pub fn respond(&self, endpoint: &str, body: &str) {
+        self.respond_with_status(endpoint, 200, body);
+    }
+
+    pub fn respond_with_status(&self, endpoint: &str, status: u16, body: &str) {
+        self.respond_with_headers(endpoint, status, &[], body);
+    }
+
+    /// Like [MockServer::respond_with_status], but also sends `headers`, such as rate limits.
+    pub fn respond_with_headers(
+        &self,
+        endpoint: &str,
+        status: u16,
+        headers: &[(&str, &str)],
+        body: &str,
+    ) {
+        let path = format!("^/api/json/v3/{}(/.*)?$", endpoint.trim_matches('/'));
+        let mut response = wiremock::ResponseTemplate::new(status).set_body_string(body);
+        for (name, value) in headers {
+            response = response.insert_header(*name, *value);
+        }
+        let mock = wiremock::Mock::given(wiremock::matchers::method("POST"))
+            .and(wiremock::matchers::path_regex(path))
+            .respond_with(response);
+        self.runtime.block_on(self.server.register(mock));
+    }
+
+    /// Removes every configured response and forgets all received requests.
+    pub fn reset(&self) {
+        self.runtime.block_on(self.server.reset());
+    }
+
+    /// Every request received so far, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.runtime
+            .block_on(self.server.received_requests())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|request| RecordedRequest {
+                path: request.url.path().to_string(),
+                body: String::from_utf8_lossy(&request.body).into_owned(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{canonicalize, Client, DnsRecord, Error, NewRecord, RecordId, RecordType, Result};
+
+/// A single modification to a domain's records.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase", tag = "operation")]
+pub enum Change {
+    Create(NewRecord),
+    Edit { id: RecordId, record: NewRecord },
+    Delete { id: RecordId },
+}
+
+/// What happened to a [Change] during [Client::apply_transaction].
+#[derive(Debug)]
+pub enum ChangeOutcome {
+    /// The change was applied and remains in place.
+    Applied,
+    /// The change was applied, then reverted after a later change failed.
+    Reverted,
+    /// The change was applied, but reverting it failed, so it remains in place.
+    RevertFailed(Error),
+    /// The change could not be applied; this is what triggered the rollback.
+    Failed(Error),
+    /// The change was not attempted because an earlier change failed.
+    Skipped,
+}
+
+#[derive(Debug)]
+pub struct TransactionReport {
+    /// Each change alongside what happened to it, in the order they were given.
+    pub changes: Vec<(Change, ChangeOutcome)>,
+    /// The records affected by edits and deletions, as they were before the transaction.
+    pub snapshot: Vec<DnsRecord>,
+}
+
+impl TransactionReport {
+    /// Returns true if every change was applied.
+    pub fn is_success(&self) -> bool {
+        self.changes
+            .iter()
+            .all(|(_, outcome)| matches!(outcome, ChangeOutcome::Applied))
+    }
+
+    /// Returns true if the domain was left as it was before the transaction.
+    ///
+    /// This is also true when every change succeeded, since nothing needed reverting.
+    pub fn is_consistent(&self) -> bool {
+        self.is_success()
+            || self
+                .changes
+                .iter()
+                .all(|(_, outcome)| !matches!(outcome, ChangeOutcome::RevertFailed(_)))
+    }
+}
+
+/// Transactions
+impl Client {
+    /// Applies a list of changes in order, reverting those already applied if any fails.
+    ///
+    /// Records affected by edits and deletions are fetched first; if this fails, nothing is
+    /// changed and an error is returned.
+    /// Otherwise, the report describes which changes were applied or reverted.
+    ///
+    /// Porkbun has no real transactions, so other clients may observe the partial state,
+    /// and a deleted record that is restored will have a new id.
+    pub fn apply_transaction(
+        &self,
+        domain: &str,
+        changes: Vec<Change>,
+    ) -> Result<TransactionReport> {
+        let mut snapshot = Vec::new();
+        for change in &changes {
+            if let Change::Edit { id, .. } | Change::Delete { id } = change {
+                snapshot.extend(self.fetch_record(domain, *id)?);
+            }
+        }
+
+        let mut outcomes = Vec::with_capacity(changes.len());
+        // The id of each created record, so it can be deleted again.
+        let mut created = Vec::with_capacity(changes.len());
+        let mut failed = false;
+        for change in &changes {
+            if failed {
+                outcomes.push(ChangeOutcome::Skipped);
+                created.push(None);
+                continue;
+            }
+            match self.apply_change(domain, change) {
+                Ok(id) => {
+                    outcomes.push(ChangeOutcome::Applied);
+                    created.push(id);
+                }
+                Err(msg) => {
+                    outcomes.push(ChangeOutcome::Failed(msg));
+                    created.push(None);
+                    failed = true;
+                }
+            }
+        }
+
+        if failed {
+            // Reverting matters more than a deadline which may have caused the failure.
+            let client = Client {
+                deadline: None,
+                ..self.clone()
+            };
+            for ((change, outcome), id) in changes.iter().zip(&mut outcomes).zip(&created).rev() {
+                if matches!(outcome, ChangeOutcome::Applied) {
+                    *outcome = match client.revert_change(domain, change, *id, &snapshot) {
+                        Ok(()) => ChangeOutcome::Reverted,
+                        Err(msg) => ChangeOutcome::RevertFailed(msg),
+                    };
+                }
+            }
+        }
+
+        Ok(TransactionReport {
+            changes: changes.into_iter().zip(outcomes).collect(),
+            snapshot,
+        })
+    }
+
+    /// Returns the id of the new record when creating one.
+    fn apply_change(&self, domain: &str, change: &Change) -> Result<Option<RecordId>> {
+        match change {
+            Change::Create(record) => {
+                self.create_record_with_notes(domain, record, self.owner.as_deref())
+            }
+            Change::Edit { id, record } => self.edit_record(domain, *id, record).map(|()| None),
+            Change::Delete { id } => self.delete_record(domain, *id).map(|()| None),
+        }
+    }
+
+    fn revert_change(
+        &self,
+        domain: &str,
+        change: &Change,
+        created: Option<RecordId>,
+        snapshot: &[DnsRecord],
+    ) -> Result<()> {
+        // The record as it was, with its notes; records without notes get empty ones, rather
+        // than keeping this client's owner marker.
+        let original = |id: RecordId| {
+            snapshot
+                .iter()
+                .find(|x| x.id == id)
+                .map(|x| {
+                    let notes = x.notes.as_deref().unwrap_or_default();
+                    (NewRecord::from_existing(domain, x), Some(notes))
+                })
+                .ok_or_else(|| Error::Irreversible {
+                    reason: format!("record {id} was missing from the snapshot"),
+                })
+        };
+        match change {
+            Change::Create(_) => {
+                let id = created.ok_or_else(|| Error::Irreversible {
+                    reason: "create response did not include a record id".to_string(),
+                })?;
+                self.delete_record(domain, id)
+            }
+            Change::Edit { id, .. } => {
+                let (record, notes) = original(*id)?;
+                self.edit_record_with_notes(domain, *id, &record, notes)
+            }
+            Change::Delete { id } => {
+                let (record, notes) = original(*id)?;
+                self.create_record_with_notes(domain, &record, notes)
+                    .map(|_| ())
+            }
+        }
+    }
+}
+
+/// The changes which make `existing`, the records with one type and subdomain,
+/// hold exactly `values`.
+///
+/// Creations come before deletions so that the name never goes unanswered.
+pub(crate) fn converge<S: AsRef<str>>(
+    subdomain: Option<&str>,
+    ty: RecordType,
+    existing: &[DnsRecord],
+    values: &[S],
+) -> Vec<Change> {
+    // Contents are compared in canonical form, so that only real differences cause changes.
+    let wanted: Vec<String> = values
+        .iter()
+        .map(|x| canonicalize(ty, x.as_ref()))
+        .collect();
+    let mut kept: Vec<&str> = Vec::new();
+    let mut deletions = Vec::new();
+    for record in existing {
+        let content = canonicalize(ty, &record.content);
+        match wanted.iter().find(|x| **x == content) {
+            Some(value) if !kept.contains(&value.as_str()) => kept.push(value),
+            _ => deletions.push(Change::Delete { id: record.id }),
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (value, canonical) in values.iter().zip(&wanted) {
+        if !kept.contains(&canonical.as_str()) {
+            kept.push(canonical);
+            changes.push(Change::Create(NewRecord {
+                name: subdomain.map(str::to_string),
+                ty,
+                content: value.as_ref().to_string(),
+                ttl: None,
+                prio: None,
+            }));
+        }
+    }
+    changes.extend(deletions);
+    changes
+}
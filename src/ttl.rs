@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+/// A DNS record's time to live, in seconds.
+///
+/// Porkbun rejects TTLs below [Ttl::MIN]; what happens to smaller values passed to a
+/// [Client](crate::Client) is decided by its [TtlPolicy].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Ttl(u32);
+
+impl Ttl {
+    /// The smallest TTL porkbun accepts, and its default.
+    pub const MIN: Ttl = Ttl(600);
+    pub const TEN_MIN: Ttl = Ttl(600);
+    pub const HOUR: Ttl = Ttl(3600);
+    pub const DAY: Ttl = Ttl(86400);
+
+    pub const fn from_secs(secs: u32) -> Self {
+        Self(secs)
+    }
+
+    pub const fn as_secs(self) -> u32 {
+        self.0
+    }
+}
+
+impl From<Ttl> for Duration {
+    fn from(ttl: Ttl) -> Self {
+        Duration::from_secs(ttl.0.into())
+    }
+}
+
+impl std::fmt::Display for Ttl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::str::FromStr for Ttl {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+/// Serialized as a string, like porkbun does.
+impl serde::Serialize for Ttl {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Ttl {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = Ttl;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a ttl as an integer or string")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, ttl: u64) -> Result<Ttl, E> {
+                u32::try_from(ttl).map(Ttl).map_err(E::custom)
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, ttl: i64) -> Result<Ttl, E> {
+                u32::try_from(ttl).map(Ttl).map_err(E::custom)
+            }
+
+            fn visit_str<E: serde::de::Error>(self, ttl: &str) -> Result<Ttl, E> {
+                ttl.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// What a [Client](crate::Client) does with a TTL below [Ttl::MIN].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TtlPolicy {
+    /// Fail with [Error::TtlTooLow](crate::Error::TtlTooLow) before making any request.
+    #[default]
+    Reject,
+    /// Raise the TTL to [Ttl::MIN], logging a warning if the `tracing` feature is enabled.
+    Clamp,
+}
+
+impl TtlPolicy {
+    pub(crate) fn apply(self, ttl: Option<Ttl>) -> crate::Result<Option<Ttl>> {
+        match ttl {
+            Some(ttl) if ttl < Ttl::MIN => match self {
+                TtlPolicy::Reject => Err(crate::Error::TtlTooLow {
+                    ttl,
+                    minimum: Ttl::MIN,
+                }),
+                TtlPolicy::Clamp => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        "raising ttl of {ttl}s to porkbun's minimum of {}s",
+                        Ttl::MIN
+                    );
+                    Ok(Some(Ttl::MIN))
+                }
+            },
+            ttl => Ok(ttl),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::TxtRecord;
+
+/// The longest string a single TXT character-string can hold, in bytes.
+const SEGMENT_LEN: usize = 255;
+
+impl TxtRecord {
+    /// Formats `text` as TXT record content.
+    ///
+    /// Text longer than 255 bytes (such as a 2048-bit DKIM key) is split into several quoted
+    /// strings, since that is the most a single DNS character-string can hold.
+    /// Shorter text is returned unchanged.
+    /// ```
+    /// let key = "k".repeat(300);
+    /// let content = porkbun::TxtRecord::encode(&key);
+    /// assert_eq!(content, format!("\"{}\" \"{}\"", "k".repeat(255), "k".repeat(45)));
+    /// ```
+    pub fn encode(text: &str) -> String {
+        if text.len() <= SEGMENT_LEN {
+            return text.to_string();
+        }
+        let mut content = String::with_capacity(text.len() + text.len() / SEGMENT_LEN * 4 + 2);
+        let mut rest = text;
+        while !rest.is_empty() {
+            let mut end = rest.len().min(SEGMENT_LEN);
+            // Splitting inside of a multi-byte character would produce invalid utf-8.
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            let (segment, remaining) = rest.split_at(end);
+            if !content.is_empty() {
+                content.push(' ');
+            }
+            content.push('"');
+            for c in segment.chars() {
+                if c == '"' || c == '\\' {
+                    content.push('\\');
+                }
+                content.push(c);
+            }
+            content.push('"');
+            rest = remaining;
+        }
+        content
+    }
+
+    /// Splits the record's content into its character-strings.
+    ///
+    /// Content which isn't made of quoted strings is treated as a single string.
+    pub fn segments(&self) -> Vec<String> {
+        let content = self.text.trim();
+        if !content.starts_with('"') {
+            return vec![self.text.clone()];
+        }
+
+        let mut segments = Vec::new();
+        let mut chars = content.chars();
+        while let Some(c) = chars.next() {
+            if c != '"' {
+                continue;
+            }
+            let mut segment = String::new();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => segment.extend(chars.next()),
+                    c => segment.push(c),
+                }
+            }
+            segments.push(segment);
+        }
+        segments
+    }
+
+    /// The record's text with every character-string concatenated, as DNS clients see it.
+    pub fn joined(&self) -> String {
+        self.segments().concat()
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{DnsRecord, RecordType};
+
+/// A collection of DNS records fetched from a single domain.
+///
+/// ```no_run
+/// # fn main() -> porkbun::Result<()> {
+/// # let client = porkbun::Client::open_keys("keys.json")?;
+/// use porkbun::{RecordType, Zone};
+///
+/// let zone = Zone::from(client.fetch_records("example.com")?);
+/// for record in zone.filter().ty(RecordType::Txt).name_contains("_dmarc").ttl_lt(600) {
+///     println!("{}: {}", record.name, record.content);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Zone {
+    records: Vec<DnsRecord>,
+}
+
+impl Zone {
+    pub fn records(&self) -> &[DnsRecord] {
+        &self.records
+    }
+
+    pub fn into_records(self) -> Vec<DnsRecord> {
+        self.records
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, DnsRecord> {
+        self.records.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Begins a query over the records in this zone.
+    ///
+    /// With no conditions added, the filter yields every record.
+    pub fn filter(&self) -> Filter<'_> {
+        Filter {
+            records: self.records.iter(),
+            predicates: Vec::new(),
+        }
+    }
+}
+
+impl From<Vec<DnsRecord>> for Zone {
+    fn from(records: Vec<DnsRecord>) -> Self {
+        Self { records }
+    }
+}
+
+impl FromIterator<DnsRecord> for Zone {
+    fn from_iter<T: IntoIterator<Item = DnsRecord>>(iter: T) -> Self {
+        Self {
+            records: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for Zone {
+    type Item = DnsRecord;
+    type IntoIter = std::vec::IntoIter<DnsRecord>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Zone {
+    type Item = &'a DnsRecord;
+    type IntoIter = std::slice::Iter<'a, DnsRecord>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.iter()
+    }
+}
+
+type Predicate<'a> = Box<dyn Fn(&DnsRecord) -> bool + 'a>;
+
+/// An iterator over the records of a [Zone] which match every added condition.
+///
+/// Records whose TTL cannot be parsed as a number never match TTL conditions.
+pub struct Filter<'a> {
+    records: std::slice::Iter<'a, DnsRecord>,
+    predicates: Vec<Predicate<'a>>,
+}
+
+impl<'a> Filter<'a> {
+    /// Adds an arbitrary condition.
+    pub fn matching(mut self, predicate: impl Fn(&DnsRecord) -> bool + 'a) -> Self {
+        self.predicates.push(Box::new(predicate));
+        self
+    }
+
+    pub fn ty(self, ty: RecordType) -> Self {
+        self.matching(move |record| record.ty == ty)
+    }
+
+    /// Matches records whose fully-qualified name is exactly `name`.
+    pub fn name(self, name: &'a str) -> Self {
+        self.matching(move |record| record.name == name)
+    }
+
+    pub fn name_contains(self, pattern: &'a str) -> Self {
+        self.matching(move |record| record.name.contains(pattern))
+    }
+
+    pub fn content(self, content: &'a str) -> Self {
+        self.matching(move |record| record.content == content)
+    }
+
+    pub fn content_contains(self, pattern: &'a str) -> Self {
+        self.matching(move |record| record.content.contains(pattern))
+    }
+
+    pub fn ttl_lt(self, ttl: u32) -> Self {
+        self.matching(move |record| record.ttl.parse::<u32>().is_ok_and(|x| x < ttl))
+    }
+
+    pub fn ttl_gt(self, ttl: u32) -> Self {
+        self.matching(move |record| record.ttl.parse::<u32>().is_ok_and(|x| x > ttl))
+    }
+}
+
+impl<'a> Iterator for Filter<'a> {
+    type Item = &'a DnsRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let predicates = &self.predicates;
+        self.records
+            .by_ref()
+            .find(|record| predicates.iter().all(|predicate| predicate(record)))
+    }
+}
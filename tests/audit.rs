@@ -0,0 +1,113 @@
+use porkbun::testing::{fixtures, MockServer};
+use porkbun::{AuditEntry, AuditSink, Error, JsonlSink, MemorySink, RecordId, RecordType};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("porkbun-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[derive(Debug)]
+struct FailingSink;
+
+impl AuditSink for FailingSink {
+    fn record(&self, _: &AuditEntry) -> std::io::Result<()> {
+        Err(std::io::Error::other("disk full"))
+    }
+}
+
+#[test]
+fn entries_record_the_actor() {
+    let server = MockServer::start();
+    server.respond("dns/create", fixtures::CREATE);
+    server.respond("dns/retrieve", fixtures::RETRIEVE_BY_ID);
+    let sink = Arc::new(MemorySink::new());
+    let client = server.client().with_audit(sink.clone()).with_actor("ci");
+
+    client
+        .create_record(
+            "example.com",
+            None,
+            RecordType::A,
+            "203.0.113.7",
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(sink.entries()[0].actor.as_deref(), Some("ci"));
+}
+
+#[test]
+fn failed_audit_keeps_the_created_id() {
+    let server = MockServer::start();
+    server.respond("dns/create", fixtures::CREATE);
+    server.respond("dns/retrieve", fixtures::RETRIEVE_BY_ID);
+    let client = server.client().with_audit(FailingSink);
+
+    let error = client
+        .create_record(
+            "example.com",
+            None,
+            RecordType::A,
+            "203.0.113.7",
+            None,
+            None,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        Error::Audit {
+            created: Some(RecordId(106926659)),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn jsonl_entries_are_chained() {
+    let path = temp_path("chain.jsonl");
+    let server = MockServer::start();
+    server.respond("dns/create", fixtures::CREATE);
+    server.respond("dns/retrieve", fixtures::RETRIEVE_BY_ID);
+    let client = server.client().with_audit(JsonlSink::open(&path).unwrap());
+    for _ in 0..3 {
+        client
+            .create_record(
+                "example.com",
+                None,
+                RecordType::A,
+                "203.0.113.7",
+                None,
+                None,
+            )
+            .unwrap();
+    }
+
+    let entries = JsonlSink::read(&path).unwrap();
+    assert_eq!(entries.len(), 3);
+    assert!(entries[0].prev_hash.is_none());
+    assert_eq!(JsonlSink::verify(&path).unwrap(), None);
+
+    // Reopening the log continues the chain.
+    let client = server.client().with_audit(JsonlSink::open(&path).unwrap());
+    client
+        .create_record(
+            "example.com",
+            None,
+            RecordType::A,
+            "203.0.113.7",
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(JsonlSink::verify(&path).unwrap(), None);
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    // Editing an entry breaks the link from the one after it.
+    let tampered = text.replacen("\"create\"", "\"delete\"", 1);
+    std::fs::write(&path, tampered).unwrap();
+    assert_eq!(JsonlSink::verify(&path).unwrap(), Some(1));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,43 @@
+use porkbun::{ChangeSet, DnsRecord, RecordId, RecordType};
+
+fn record(id: u64, ty: RecordType, content: &str) -> DnsRecord {
+    DnsRecord {
+        id: RecordId(id),
+        name: "example.com".to_string(),
+        ty,
+        content: content.to_string(),
+        ttl: "600".to_string(),
+        prio: "0".to_string(),
+        notes: None,
+    }
+}
+
+#[test]
+fn replace_skips_addresses_sharing_a_prefix() {
+    let records = [
+        record(1, RecordType::A, "203.0.113.10"),
+        record(2, RecordType::A, "203.0.113.100"),
+        record(
+            3,
+            RecordType::Txt,
+            "v=spf1 ip4:203.0.113.10 ip4:203.0.113.101 -all",
+        ),
+    ];
+    let changes = ChangeSet::replace("example.com", &records, "203.0.113.10", "198.51.100.7");
+
+    let edited: Vec<(RecordId, &str)> = changes
+        .edit
+        .iter()
+        .map(|x| (x.before.id, x.after.content.as_str()))
+        .collect();
+    assert_eq!(
+        edited,
+        [
+            (RecordId(1), "198.51.100.7"),
+            (
+                RecordId(3),
+                "v=spf1 ip4:198.51.100.7 ip4:203.0.113.101 -all"
+            ),
+        ]
+    );
+}
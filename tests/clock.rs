@@ -0,0 +1,79 @@
+use porkbun::clock::MockClock;
+use porkbun::resolve::{wait_for_absence_with_clock, PropagationReport, Resolver};
+use porkbun::retry::Backoff;
+use porkbun::testing::{fixtures, MockServer};
+use porkbun::{Deadline, DnsRecord, Error, RecordId, RecordType};
+use std::time::Duration;
+
+#[test]
+fn retries_wait_on_the_client_clock() {
+    let server = MockServer::start();
+    server.respond_with_status("ping", 429, fixtures::RATE_LIMITED);
+    let clock = MockClock::new();
+    let client = server
+        .client()
+        .with_backoff(Backoff::default())
+        .with_clock(clock.clone());
+
+    assert!(client.ping().is_err());
+    assert!(server.requests().len() > 1);
+    assert!(clock.elapsed() > Duration::ZERO);
+}
+
+#[test]
+fn deadlines_expire_on_the_client_clock() {
+    let server = MockServer::start();
+    server.respond("ping", fixtures::PING);
+    let clock = MockClock::new();
+    clock.advance(Duration::from_secs(3600));
+    let deadline = Deadline::after_with_clock(&clock, Duration::from_secs(60));
+    let client = server
+        .client()
+        .with_clock(clock.clone())
+        .with_deadline(deadline);
+
+    assert!(client.ping().is_ok());
+    clock.advance(Duration::from_secs(60));
+    assert!(matches!(client.ping(), Err(Error::DeadlineExceeded)));
+}
+
+#[test]
+fn propagation_waits_on_the_given_clock() {
+    let clock = MockClock::new();
+    let report = PropagationReport::wait_with_clock(
+        &clock,
+        &[Resolver::System],
+        "localhost",
+        RecordType::A,
+        "192.0.2.1",
+        Duration::from_secs(60),
+        |_| {},
+    );
+    assert!(!report.is_complete());
+    assert_eq!(clock.elapsed(), Duration::from_secs(60));
+}
+
+#[test]
+fn absence_waits_on_the_given_clock() {
+    let record = DnsRecord {
+        id: RecordId(1),
+        name: "localhost".to_string(),
+        ty: RecordType::A,
+        content: "127.0.0.1".to_string(),
+        ttl: "600".to_string(),
+        prio: "0".to_string(),
+        notes: None,
+    };
+    let clock = MockClock::new();
+    let result = wait_for_absence_with_clock(
+        &clock,
+        &record,
+        &[Resolver::System],
+        Duration::from_secs(30),
+    );
+    assert!(matches!(
+        result,
+        Err(Error::PropagationTimeout { pending: 1, .. })
+    ));
+    assert_eq!(clock.elapsed(), Duration::from_secs(30));
+}
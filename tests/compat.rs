@@ -0,0 +1,116 @@
+use porkbun::compat;
+use porkbun::endpoints::Endpoint;
+use porkbun::testing::{fixtures, MockServer};
+use serde_json::Value;
+
+fn listing(domains: &[String]) -> String {
+    let domains: Vec<Value> = domains
+        .iter()
+        .map(|domain| serde_json::json!({ "domain": domain, "status": "ACTIVE", "tld": "com" }))
+        .collect();
+    serde_json::json!({ "status": "SUCCESS", "domains": domains }).to_string()
+}
+
+fn captured_records(response: &str) -> Vec<Value> {
+    let server = MockServer::start();
+    server.respond("dns/retrieve", response);
+    let golden = server
+        .client()
+        .capture(Endpoint::DnsRetrieve {
+            domain: "shop.test",
+        })
+        .unwrap();
+    assert_eq!(golden.operation, "dns/retrieve");
+    let response: Value = serde_json::from_str(&golden.response).unwrap();
+    response["records"].as_array().unwrap().clone()
+}
+
+#[test]
+fn domains_are_replaced_in_order() {
+    let server = MockServer::start();
+    let domains = ["zeta.net", "alpha.org", "Beta.io"].map(String::from);
+    server.respond("domain/listAll", &listing(&domains));
+
+    let golden = server.client().capture(Endpoint::ListDomains).unwrap();
+    let response: Value = serde_json::from_str(&golden.response).unwrap();
+    let names: Vec<&str> = response["domains"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| x["domain"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["example.com", "example2.com", "example3.com"]);
+}
+
+#[test]
+fn long_listings_are_truncated() {
+    let server = MockServer::start();
+    let domains: Vec<String> = (0..150).map(|i| format!("domain{i}.net")).collect();
+    server.respond("domain/listAll", &listing(&domains));
+
+    let golden = server.client().capture(Endpoint::ListDomains).unwrap();
+    let response: Value = serde_json::from_str(&golden.response).unwrap();
+    let captured = response["domains"].as_array().unwrap();
+    assert_eq!(captured.len(), 100);
+    assert_eq!(captured[99]["domain"], "example100.com");
+    assert!(!golden.response.contains(".net"));
+}
+
+#[test]
+fn records_are_redacted() {
+    let response = fixtures::RETRIEVE
+        .replace("example.com", "shop.test")
+        .replacen(r#""notes":"""#, r#""notes":"office router""#, 1);
+    let records = captured_records(&response);
+
+    assert_eq!(records[0]["name"], "example.com");
+    assert_eq!(records[0]["notes"], "redacted");
+    assert_eq!(records[1]["notes"], "");
+    assert_eq!(records[2]["content"], "mail.example.com");
+    assert_eq!(records[3]["name"], "_dmarc.example.com");
+    assert_eq!(records[3]["content"], "redacted");
+    assert!(!records.iter().any(|x| x.to_string().contains("shop.test")));
+}
+
+#[test]
+fn equal_addresses_stay_equal() {
+    let response = r#"{"status":"SUCCESS","records":[
+        {"id":"1","name":"shop.test","type":"A","content":"198.51.100.20","ttl":"600","prio":"0","notes":""},
+        {"id":"2","name":"www.shop.test","type":"A","content":"192.0.2.99","ttl":"600","prio":"0","notes":""},
+        {"id":"3","name":"api.shop.test","type":"A","content":"198.51.100.20","ttl":"600","prio":"0","notes":""},
+        {"id":"4","name":"shop.test","type":"AAAA","content":"2001:db8:1234::1","ttl":"600","prio":"0","notes":""}]}"#;
+    let records = captured_records(response);
+
+    let contents: Vec<&str> = records
+        .iter()
+        .map(|x| x["content"].as_str().unwrap())
+        .collect();
+    assert_eq!(contents[0], contents[2]);
+    assert_ne!(contents[0], contents[1]);
+    assert!(contents[0].starts_with("203.0.113."));
+    assert!(contents[1].starts_with("203.0.113."));
+    assert!(contents[3].starts_with("2001:db8::"));
+    assert!(!contents.contains(&"198.51.100.20"));
+}
+
+#[test]
+fn golden_responses_are_compatible() {
+    let reports = compat::check_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden")).unwrap();
+
+    assert_eq!(reports.len(), 9);
+    for (path, report) in reports {
+        assert!(
+            report.is_compatible(),
+            "{}: {:?}",
+            path.display(),
+            report.error
+        );
+        assert!(
+            report.error.is_none(),
+            "{}: {:?}",
+            path.display(),
+            report.error
+        );
+        assert!(report.unknown_fields.is_empty(), "{}", path.display());
+    }
+}
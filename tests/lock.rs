@@ -0,0 +1,89 @@
+use porkbun::testing::{fixtures, MockServer};
+use porkbun::Error;
+use std::time::Duration;
+
+/// A lock record held by `content`.
+fn lock_record(content: &str) -> String {
+    format!(
+        r#"{{"status":"SUCCESS","records":[{{"id":"106926670","name":"_porkbun-lock.example.com","type":"TXT","content":"{content}","ttl":"600","prio":"0","notes":""}}]}}"#
+    )
+}
+
+fn paths(server: &MockServer) -> Vec<String> {
+    server.requests().into_iter().map(|x| x.path).collect()
+}
+
+#[test]
+fn free_locks_are_taken_and_released_when_dropped() {
+    let server = MockServer::start();
+    server.respond("dns/retrieveByNameType", fixtures::RETRIEVE_EMPTY);
+    server.respond("dns/create", fixtures::CREATE);
+    server.respond("dns/delete", fixtures::SUCCESS);
+
+    let lock = server
+        .client()
+        .lock_zone("example.com", "deploy job 41", Duration::from_secs(600))
+        .unwrap();
+    let create = server
+        .requests()
+        .into_iter()
+        .find(|x| x.path.contains("/dns/create/"))
+        .unwrap();
+    assert!(create.body.contains(r#""name":"_porkbun-lock""#));
+    assert!(create.body.contains("holder=deploy job 41 expires="));
+
+    drop(lock);
+    assert_eq!(
+        paths(&server).last().map(String::as_str),
+        Some("/api/json/v3/dns/delete/example.com/106926659")
+    );
+}
+
+#[test]
+fn held_locks_name_their_holder() {
+    let server = MockServer::start();
+    server.respond(
+        "dns/retrieveByNameType",
+        &lock_record("holder=nightly sync expires=4102444800"),
+    );
+
+    let result =
+        server
+            .client()
+            .lock_zone("example.com", "deploy job 41", Duration::from_secs(600));
+    match result {
+        Err(Error::ZoneLocked { domain, holder }) => {
+            assert_eq!(domain, "example.com");
+            assert_eq!(holder, "nightly sync");
+        }
+        other => panic!("expected the zone to be locked, got {other:?}"),
+    }
+    assert!(!paths(&server).iter().any(|x| x.contains("/dns/create/")));
+}
+
+#[test]
+fn expired_locks_are_deleted_before_taking_the_lock() {
+    let server = MockServer::start();
+    server.respond(
+        "dns/retrieveByNameType",
+        &lock_record("holder=crashed job expires=1"),
+    );
+    server.respond("dns/create", fixtures::CREATE);
+    server.respond("dns/delete", fixtures::SUCCESS);
+
+    let lock = server
+        .client()
+        .lock_zone("example.com", "deploy job 41", Duration::from_secs(600))
+        .unwrap();
+    let paths = paths(&server);
+    let stale = paths
+        .iter()
+        .position(|x| x == "/api/json/v3/dns/delete/example.com/106926670")
+        .unwrap();
+    let create = paths
+        .iter()
+        .position(|x| x.contains("/dns/create/"))
+        .unwrap();
+    assert!(stale < create);
+    lock.release().unwrap();
+}
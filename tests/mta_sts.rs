@@ -0,0 +1,58 @@
+use porkbun::testing::{fixtures, MockServer};
+use porkbun::{StsMode, TlsRpt};
+
+#[test]
+fn setup_creates_every_record() {
+    let server = MockServer::start();
+    server.respond("dns/retrieveByNameType", fixtures::RETRIEVE_EMPTY);
+    server.respond("dns/create", fixtures::CREATE);
+    let rpt = TlsRpt::new(&["tls@example.com"]).unwrap();
+
+    let (policy, report) = server
+        .client()
+        .setup_mta_sts(
+            "example.com",
+            StsMode::Enforce,
+            &["mx.example.com"],
+            "sts.example.net",
+            &rpt,
+        )
+        .unwrap();
+    assert!(report.is_success());
+    assert_eq!(report.changes.len(), 3);
+
+    let created: Vec<String> = server
+        .requests()
+        .into_iter()
+        .filter(|x| x.path.contains("/dns/create/"))
+        .map(|x| x.body)
+        .collect();
+    assert_eq!(created.len(), 3);
+    let body = |name: &str| created.iter().find(|x| x.contains(name)).unwrap();
+    assert!(body(r#""name":"_mta-sts""#).contains(&policy.record_content()));
+    assert!(body(r#""name":"mta-sts""#).contains(r#""type":"CNAME""#));
+    assert!(body(r#""name":"_smtp._tls""#).contains("v=TLSRPTv1; rua=mailto:tls@example.com"));
+}
+
+#[test]
+fn policy_hosts_may_be_addresses() {
+    let server = MockServer::start();
+    server.respond("dns/retrieveByNameType", fixtures::RETRIEVE_EMPTY);
+    server.respond("dns/create", fixtures::CREATE);
+    let rpt = TlsRpt::new(&["tls@example.com"]).unwrap();
+
+    server
+        .client()
+        .setup_mta_sts(
+            "example.com",
+            StsMode::Testing,
+            &["mx.example.com"],
+            "2001:db8::7",
+            &rpt,
+        )
+        .unwrap();
+    assert!(server
+        .requests()
+        .iter()
+        .any(|x| x.body.contains(r#""name":"mta-sts""#) && x.body.contains(r#""type":"AAAA""#)));
+}
@@ -0,0 +1,38 @@
+use porkbun::testing::MockServer;
+use porkbun::{Error, ParseMode};
+
+const UNKNOWN_TYPE: &str = r#"{"status":"SUCCESS","records":[{"id":"1","name":"example.com","type":"A","content":"203.0.113.7","ttl":"600","prio":"0","notes":""},{"id":"2","name":"example.com","type":"LOC","content":"52 22 23.000 N","ttl":"600","prio":"0","notes":""}]}"#;
+
+#[test]
+fn strict_fetches_fail_on_unknown_types() {
+    let server = MockServer::start();
+    server.respond("dns/retrieve", UNKNOWN_TYPE);
+
+    let error = server.client().fetch_records("example.com").unwrap_err();
+    assert!(
+        matches!(error, Error::MalformedApiSerde { pointer, .. } if pointer == "/records/1/type")
+    );
+}
+
+#[test]
+fn lenient_fetches_skip_unknown_types() {
+    let server = MockServer::start();
+    server.respond("dns/retrieve", UNKNOWN_TYPE);
+    let client = server.client().with_parse_mode(ParseMode::Lenient);
+
+    assert_eq!(client.fetch_records("example.com").unwrap().len(), 1);
+}
+
+#[test]
+fn skipped_records_belong_to_their_fetch() {
+    let server = MockServer::start();
+    server.respond("dns/retrieve", UNKNOWN_TYPE);
+    let client = server.client();
+
+    for _ in 0..2 {
+        let (records, skipped) = client.fetch_records_lenient("example.com").unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].0["type"], "LOC");
+    }
+}
@@ -0,0 +1,55 @@
+use porkbun::testing::{fixtures, MockServer};
+use porkbun::{ChangeSet, NewRecord, RecordType, Ttl};
+
+fn apex_a(content: &str) -> NewRecord {
+    NewRecord {
+        name: None,
+        ty: RecordType::A,
+        content: content.to_string(),
+        ttl: None,
+        prio: None,
+    }
+}
+
+#[test]
+fn edits_keep_the_existing_ttl() {
+    let server = MockServer::start();
+    server.respond("dns/retrieve", fixtures::RETRIEVE_BY_ID);
+    server.respond("dns/edit", fixtures::SUCCESS);
+    let client = server.client();
+    let existing = client.fetch_records("example.com").unwrap();
+
+    let changes = ChangeSet::diff("example.com", &existing, &[apex_a("203.0.113.8")]);
+    assert_eq!(changes.edit.len(), 1);
+    assert_eq!(changes.edit[0].after.ttl, Some(Ttl::from_secs(600)));
+    assert_eq!(changes.edit[0].after.prio.as_deref(), Some("0"));
+
+    server.reset();
+    server.respond("dns/retrieve", fixtures::RETRIEVE_BY_ID);
+    server.respond("dns/edit", fixtures::SUCCESS);
+    let report = client
+        .apply_transaction("example.com", changes.into_changes())
+        .unwrap();
+    assert!(report.is_success());
+    let edit = server
+        .requests()
+        .into_iter()
+        .find(|x| x.path.contains("/dns/edit/"))
+        .unwrap();
+    assert!(edit.body.contains(r#""ttl":"600""#));
+}
+
+#[test]
+fn planned_ttls_replace_existing_ones() {
+    let server = MockServer::start();
+    server.respond("dns/retrieve", fixtures::RETRIEVE_BY_ID);
+    let existing = server.client().fetch_records("example.com").unwrap();
+
+    let desired = NewRecord {
+        ttl: Some(Ttl::HOUR),
+        ..apex_a("203.0.113.7")
+    };
+    let changes = ChangeSet::diff("example.com", &existing, &[desired]);
+    assert_eq!(changes.edit.len(), 1);
+    assert_eq!(changes.edit[0].after.ttl, Some(Ttl::HOUR));
+}
@@ -0,0 +1,17 @@
+use porkbun::preset;
+use porkbun::testing::{fixtures, MockServer};
+
+#[test]
+fn failed_site_setups_skip_the_propagation_check() {
+    let server = MockServer::start();
+    server.respond("dns/retrieve", fixtures::RETRIEVE);
+    server.respond("dns/create", fixtures::INVALID_API_KEY);
+
+    let setup = server
+        .client()
+        .setup_site("example.com", preset::vercel("example.com"))
+        .unwrap();
+    assert!(!setup.report.is_success());
+    assert!(setup.propagation.is_none());
+    assert!(!setup.steps.is_empty());
+}
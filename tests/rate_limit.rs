@@ -0,0 +1,50 @@
+use porkbun::testing::{fixtures, MockServer};
+use porkbun::RateLimit;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn rate_limit_after(reset: &str) -> RateLimit {
+    let server = MockServer::start();
+    server.respond_with_headers(
+        "ping",
+        200,
+        &[
+            ("x-ratelimit-limit", "60"),
+            ("x-ratelimit-remaining", "59"),
+            ("x-ratelimit-reset", reset),
+        ],
+        fixtures::PING,
+    );
+    let seen = Arc::new(Mutex::new(None));
+    let hook = seen.clone();
+    let client = server
+        .client()
+        .with_call_hook(move |info| *hook.lock().unwrap() = info.rate_limit);
+    client.ping().unwrap();
+    let rate_limit = seen.lock().unwrap().take();
+    rate_limit.unwrap()
+}
+
+#[test]
+fn reset_seconds_are_read_as_a_delay() {
+    let rate_limit = rate_limit_after("30");
+    assert_eq!(rate_limit.limit, Some(60));
+    assert_eq!(rate_limit.remaining, Some(59));
+    assert_eq!(rate_limit.reset, Some(Duration::from_secs(30)));
+}
+
+#[test]
+fn reset_timestamps_are_read_relative_to_now() {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let reset = rate_limit_after(&(now.as_secs() + 30).to_string())
+        .reset
+        .unwrap();
+    assert!(reset <= Duration::from_secs(30));
+    assert!(reset >= Duration::from_secs(25));
+}
+
+#[test]
+fn past_reset_timestamps_are_read_as_now() {
+    let reset = rate_limit_after("1700000000").reset.unwrap();
+    assert_eq!(reset, Duration::ZERO);
+}
@@ -0,0 +1,64 @@
+use porkbun::testing::{fixtures, MockServer};
+use porkbun::{Error, RecordId};
+
+const RECORD: &str = r#"{"status":"SUCCESS","records":[{"id":"106926656","name":"www.example.com","type":"MX","content":"mail.example.com","ttl":"3600","prio":"20","notes":"managed by the mail team"}]}"#;
+
+#[test]
+fn renamed_records_keep_their_ttl_priority_and_notes() {
+    let server = MockServer::start();
+    server.respond("dns/retrieve", RECORD);
+    server.respond("dns/create", fixtures::CREATE);
+    server.respond("dns/delete", fixtures::SUCCESS);
+
+    let id = server
+        .client()
+        .rename_record("example.com", RecordId(106926656), Some("mx"))
+        .unwrap();
+    assert_eq!(id, Some(RecordId(106926659)));
+
+    let requests = server.requests();
+    let create = requests
+        .iter()
+        .find(|x| x.path.contains("/dns/create/"))
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_str(&create.body).unwrap();
+    assert_eq!(body["name"], "mx");
+    assert_eq!(body["ttl"], "3600");
+    assert_eq!(body["prio"], "20");
+    assert_eq!(body["notes"], "managed by the mail team");
+    assert_eq!(
+        requests.last().unwrap().path,
+        "/api/json/v3/dns/delete/example.com/106926656"
+    );
+}
+
+#[test]
+fn copies_are_deleted_when_the_original_cant_be() {
+    let server = MockServer::start();
+    server.respond("dns/retrieve", RECORD);
+    server.respond("dns/create", fixtures::CREATE);
+    server.respond(
+        "dns/delete/example.com/106926656",
+        fixtures::DOMAIN_NOT_OPTED_IN,
+    );
+    server.respond("dns/delete", fixtures::SUCCESS);
+
+    let result = server
+        .client()
+        .rename_record("example.com", RecordId(106926656), Some("mx"));
+    assert!(matches!(result, Err(Error::ApiAccessDisabled { .. })));
+
+    let deletes: Vec<String> = server
+        .requests()
+        .into_iter()
+        .map(|x| x.path)
+        .filter(|x| x.contains("/dns/delete/"))
+        .collect();
+    assert_eq!(
+        deletes,
+        [
+            "/api/json/v3/dns/delete/example.com/106926656",
+            "/api/json/v3/dns/delete/example.com/106926659",
+        ]
+    );
+}
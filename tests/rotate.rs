@@ -0,0 +1,32 @@
+use porkbun::testing::{fixtures, MockServer};
+use porkbun::Error;
+
+#[test]
+fn encrypted_key_files_are_left_alone() {
+    let server = MockServer::start();
+    server.respond("ping", fixtures::PING);
+    let path = std::env::temp_dir().join(format!("porkbun-rotate-{}.age", std::process::id()));
+    let armored = "-----BEGIN AGE ENCRYPTED FILE-----\nYWdlLWVuY3J5cHRpb24ub3JnL3YxCg==\n-----END AGE ENCRYPTED FILE-----\n";
+    std::fs::write(&path, armored).unwrap();
+
+    let result = server.client().rotate_keys(&path, "pk1_new", "sk1_new");
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(result, Err(Error::InvalidKey { .. })));
+    assert_eq!(contents, armored);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn plaintext_key_files_are_replaced() {
+    let server = MockServer::start();
+    server.respond("ping", fixtures::PING);
+    let path = std::env::temp_dir().join(format!("porkbun-rotate-{}.json", std::process::id()));
+    std::fs::write(&path, r#"{"apikey":"pk1_old","secretapikey":"sk1_old"}"#).unwrap();
+
+    let result = server.client().rotate_keys(&path, "pk1_new", "sk1_new");
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(result.is_ok());
+    assert!(contents.contains("pk1_new") && contents.contains("sk1_new"));
+}
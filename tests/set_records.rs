@@ -0,0 +1,111 @@
+use porkbun::testing::{fixtures, MockServer};
+use porkbun::{Change, NewRecord, RecordId, RecordType};
+
+/// The A records of `www.example.com`, with `.7` listed twice.
+const RECORDS: &str = r#"{"status":"SUCCESS","records":[{"id":"1","name":"www.example.com","type":"A","content":"203.0.113.7","ttl":"600","prio":"0","notes":""},{"id":"2","name":"www.example.com","type":"A","content":"203.0.113.7","ttl":"600","prio":"0","notes":""},{"id":"3","name":"www.example.com","type":"A","content":"203.0.113.9","ttl":"600","prio":"0","notes":""}]}"#;
+
+fn server() -> MockServer {
+    let server = MockServer::start();
+    server.respond("dns/retrieveByNameType", RECORDS);
+    // Deleted records are fetched first, in case they need restoring.
+    server.respond("dns/retrieve", RECORDS);
+    server.respond("dns/create", fixtures::CREATE);
+    server.respond("dns/delete", fixtures::SUCCESS);
+    server
+}
+
+#[test]
+fn missing_values_are_created_before_extras_are_deleted() {
+    let server = server();
+    let report = server
+        .client()
+        .set_records(
+            "example.com",
+            Some("www"),
+            RecordType::A,
+            &["203.0.113.7", "203.0.113.8"],
+        )
+        .unwrap();
+    assert!(report.is_success());
+
+    let changes: Vec<String> = report
+        .changes
+        .iter()
+        .map(|(change, _)| match change {
+            Change::Create(record) => format!("create {}", record.content),
+            Change::Delete { id } => format!("delete {id}"),
+            Change::Edit { id, .. } => format!("edit {id}"),
+        })
+        .collect();
+    assert_eq!(changes, ["create 203.0.113.8", "delete 2", "delete 3"]);
+    let paths: Vec<String> = server.requests().into_iter().map(|x| x.path).collect();
+    assert_eq!(
+        paths.last().map(String::as_str),
+        Some("/api/json/v3/dns/delete/example.com/3")
+    );
+}
+
+#[test]
+fn matching_records_are_left_alone() {
+    let server = MockServer::start();
+    server.respond("dns/retrieveByNameType", fixtures::RETRIEVE_BY_NAME_TYPE);
+
+    let report = server
+        .client()
+        .set_records("example.com", Some("www"), RecordType::A, &["203.0.113.7"])
+        .unwrap();
+    assert!(report.changes.is_empty());
+    assert!(server
+        .requests()
+        .iter()
+        .all(|x| x.path.contains("/dns/retrieveByNameType/")));
+}
+
+#[test]
+fn no_values_deletes_every_record() {
+    let server = server();
+    let report = server
+        .client()
+        .set_records("example.com", Some("www"), RecordType::A, &[])
+        .unwrap();
+
+    let deleted: Vec<RecordId> = report
+        .changes
+        .iter()
+        .filter_map(|(change, _)| match change {
+            Change::Delete { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(deleted, [RecordId(1), RecordId(2), RecordId(3)]);
+}
+
+#[test]
+fn differences_in_how_addresses_are_written_are_ignored() {
+    let server = MockServer::start();
+    server.respond(
+        "dns/retrieveByNameType",
+        r#"{"status":"SUCCESS","records":[{"id":"106926660","name":"www.example.com","type":"AAAA","content":"2001:DB8:0::1","ttl":"600","prio":"0","notes":""}]}"#,
+    );
+
+    let report = server
+        .client()
+        .set_records(
+            "example.com",
+            Some("www"),
+            RecordType::Aaaa,
+            &["2001:db8::1", "2001:db8::2"],
+        )
+        .unwrap();
+    let changes: Vec<&Change> = report.changes.iter().map(|(x, _)| x).collect();
+    assert_eq!(
+        changes,
+        [&Change::Create(NewRecord {
+            name: Some("www".to_string()),
+            ty: RecordType::Aaaa,
+            content: "2001:db8::2".to_string(),
+            ttl: None,
+            prio: None,
+        })]
+    );
+}
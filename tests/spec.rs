@@ -0,0 +1,40 @@
+use porkbun::{RecordSpec, RecordType, ZoneSpec};
+
+fn record(name: &str, ty: RecordType, content: &str) -> RecordSpec {
+    RecordSpec {
+        name: Some(name.into()),
+        ty,
+        content: content.into(),
+        ttl: None,
+        prio: None,
+        notes: None,
+    }
+}
+
+#[test]
+fn rewrite_only_replaces_whole_labels() {
+    let mut spec = ZoneSpec {
+        domain: "example.com".into(),
+        records: vec![
+            record("www", RecordType::Cname, "Example.com."),
+            record("mail", RecordType::Mx, "mx.example.com"),
+            record("", RecordType::Txt, "v=spf1 include:_spf.example.com -all"),
+            record("shop", RecordType::Cname, "myexample.com"),
+            record("au", RecordType::Cname, "example.com.au"),
+        ],
+        ..Default::default()
+    };
+    spec.rewrite("example.com", "example.net");
+
+    let contents: Vec<&str> = spec.records.iter().map(|x| x.content.as_str()).collect();
+    assert_eq!(
+        contents,
+        [
+            "example.net.",
+            "mx.example.net",
+            "v=spf1 include:_spf.example.net -all",
+            "myexample.com",
+            "example.com.au",
+        ]
+    );
+}
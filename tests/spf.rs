@@ -0,0 +1,44 @@
+use porkbun::spf::Spf;
+use porkbun::{DnsRecord, RecordId, RecordType, TxtRecord};
+
+/// A flattened policy with 20 addresses, well past the 255 bytes of one TXT string.
+fn flattened() -> Spf {
+    let mut terms: Vec<String> = (1..=20).map(|x| format!("ip4:198.51.100.{x}")).collect();
+    terms.push("-all".to_string());
+    Spf { terms }
+}
+
+fn txt(content: &str) -> DnsRecord {
+    DnsRecord {
+        id: RecordId(1),
+        name: "example.com".to_string(),
+        ty: RecordType::Txt,
+        content: content.to_string(),
+        ttl: "600".to_string(),
+        prio: "0".to_string(),
+        notes: None,
+    }
+}
+
+#[test]
+fn long_policies_are_split_into_strings() {
+    let spf = flattened();
+    assert!(spf.to_string().len() > 255);
+
+    let changes = spf.plan(
+        "example.com",
+        &[txt("v=spf1 include:_spf.example.net -all")],
+    );
+    assert_eq!(changes.edit.len(), 1);
+    let content = &changes.edit[0].after.content;
+    assert_eq!(content, &TxtRecord::encode(&spf.to_string()));
+    assert!(content.starts_with('"'));
+    assert_eq!(Spf::parse(content), Some(spf));
+}
+
+#[test]
+fn replanning_a_published_long_policy_changes_nothing() {
+    let spf = flattened();
+    let published = txt(&TxtRecord::encode(&spf.to_string()));
+    assert!(spf.plan("example.com", &[published]).is_empty());
+}
@@ -0,0 +1,105 @@
+use porkbun::{
+    ChangeSet, ConflictStrategy, DnsRecord, Error, NewRecord, RecordId, RecordType, SyncOptions,
+};
+
+fn live(id: u64, name: &str, ty: RecordType, content: &str) -> DnsRecord {
+    DnsRecord {
+        id: RecordId(id),
+        name: name.to_string(),
+        ty,
+        content: content.to_string(),
+        ttl: "600".to_string(),
+        prio: "0".to_string(),
+        notes: None,
+    }
+}
+
+fn wanted(name: Option<&str>, ty: RecordType, content: &str) -> NewRecord {
+    NewRecord {
+        name: name.map(str::to_string),
+        ty,
+        content: content.to_string(),
+        ttl: None,
+        prio: None,
+    }
+}
+
+/// A zone whose A and TXT records differ from the spec, with an MX record the spec lacks.
+fn diff(options: &SyncOptions) -> porkbun::Result<ChangeSet> {
+    let existing = [
+        live(1, "www.example.com", RecordType::A, "203.0.113.7"),
+        live(2, "example.com", RecordType::Txt, "v=spf1 -all"),
+        live(3, "example.com", RecordType::Mx, "mail.example.com"),
+    ];
+    let desired = [
+        wanted(Some("www"), RecordType::A, "203.0.113.8"),
+        wanted(None, RecordType::Txt, "site-verification=abc"),
+    ];
+    ChangeSet::diff_with("example.com", &existing, &desired, options)
+}
+
+fn edited(changes: &ChangeSet) -> Vec<RecordId> {
+    changes.edit.iter().map(|x| x.before.id).collect()
+}
+
+#[test]
+fn overwrite_edits_and_prunes() {
+    let changes = diff(&SyncOptions::new().prune(true)).unwrap();
+    assert_eq!(edited(&changes), [RecordId(1), RecordId(2)]);
+    assert!(changes.create.is_empty());
+    let deleted: Vec<RecordId> = changes.delete.iter().map(|x| x.id).collect();
+    assert_eq!(deleted, [RecordId(3)]);
+
+    let changes = diff(&SyncOptions::new()).unwrap();
+    assert!(changes.delete.is_empty());
+}
+
+#[test]
+fn ignored_types_are_left_alone() {
+    let options = SyncOptions::new()
+        .prune(true)
+        .strategy_for(RecordType::Txt, ConflictStrategy::Ignore)
+        .strategy_for(RecordType::Mx, ConflictStrategy::Ignore);
+    let changes = diff(&options).unwrap();
+    assert_eq!(edited(&changes), [RecordId(1)]);
+    assert!(changes.create.is_empty());
+    assert!(changes.delete.is_empty());
+}
+
+#[test]
+fn merged_types_gain_records_alongside_live_ones() {
+    let options = SyncOptions::new().strategy_for(RecordType::Txt, ConflictStrategy::Merge);
+    let changes = diff(&options).unwrap();
+    assert_eq!(edited(&changes), [RecordId(1)]);
+    let created: Vec<(Option<&str>, RecordType, &str)> = changes
+        .create
+        .iter()
+        .map(|x| (x.name.as_deref(), x.ty, x.content.as_str()))
+        .collect();
+    assert_eq!(created, [(None, RecordType::Txt, "site-verification=abc")]);
+}
+
+#[test]
+fn failing_types_refuse_edits_and_deletions() {
+    let options = SyncOptions::new().strategy_for(RecordType::A, ConflictStrategy::Fail);
+    match diff(&options) {
+        Err(Error::SyncConflict { name, ty, reason }) => {
+            assert_eq!(name, "www.example.com");
+            assert_eq!(ty, RecordType::A);
+            assert_eq!(reason, "would be edited");
+        }
+        other => panic!("expected a conflict, got {other:?}"),
+    }
+
+    let options = SyncOptions::new()
+        .prune(true)
+        .strategy_for(RecordType::Mx, ConflictStrategy::Fail);
+    assert!(matches!(
+        diff(&options),
+        Err(Error::SyncConflict {
+            ty: RecordType::Mx,
+            reason: "would be deleted",
+            ..
+        })
+    ));
+}
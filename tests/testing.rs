@@ -0,0 +1,73 @@
+use porkbun::testing::{fixtures, MockServer};
+use porkbun::{Error, RecordId, RecordType};
+
+#[test]
+fn fixtures_parse_for_their_endpoints() {
+    let server = MockServer::start();
+    server.respond("ping", fixtures::PING);
+    server.respond("dns/retrieve", fixtures::RETRIEVE);
+    server.respond("dns/create", fixtures::CREATE);
+    server.respond("dns/delete", fixtures::SUCCESS);
+    let client = server.client();
+
+    assert_eq!(client.ping().unwrap(), Some("203.0.113.7".parse().unwrap()));
+    assert_eq!(client.fetch_records("example.com").unwrap().len(), 4);
+    let id = client
+        .create_record(
+            "example.com",
+            Some("www"),
+            RecordType::A,
+            "203.0.113.7",
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(id, Some(RecordId(106926659)));
+    client
+        .delete_record("example.com", RecordId(106926659))
+        .unwrap();
+}
+
+#[test]
+fn requests_are_recorded_with_their_keys() {
+    let server = MockServer::start();
+    server.respond("dns/retrieve", fixtures::RETRIEVE_BY_ID);
+
+    server
+        .client()
+        .fetch_record("example.com", RecordId(106926652))
+        .unwrap();
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].path,
+        "/api/json/v3/dns/retrieve/example.com/106926652"
+    );
+    assert!(requests[0].body.contains("pk1_test"));
+}
+
+#[test]
+fn error_fixtures_become_errors() {
+    let server = MockServer::start();
+    server.respond("ping", fixtures::INVALID_API_KEY);
+    server.respond("dns/retrieve", fixtures::DOMAIN_NOT_OPTED_IN);
+    let client = server.client();
+
+    assert!(matches!(client.ping(), Err(Error::Api { .. })));
+    assert!(matches!(
+        client.fetch_records("example.com"),
+        Err(Error::ApiAccessDisabled { .. })
+    ));
+}
+
+#[test]
+fn reset_forgets_responses_and_requests() {
+    let server = MockServer::start();
+    server.respond("ping", fixtures::PING);
+    server.client().ping().unwrap();
+
+    server.reset();
+    assert!(server.requests().is_empty());
+    server.respond_with_status("ping", 500, "");
+    assert!(server.client().ping().is_err());
+}
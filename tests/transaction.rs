@@ -0,0 +1,71 @@
+use porkbun::testing::{fixtures, MockServer};
+use porkbun::{Change, ChangeOutcome, NewRecord, RecordId, RecordType};
+
+const RECORD: &str = r#"{"status":"SUCCESS","records":[{"id":"106926656","name":"www.example.com","type":"A","content":"203.0.113.7","ttl":"600","prio":"0","notes":"managed by the web team"}]}"#;
+
+fn www(content: &str) -> NewRecord {
+    NewRecord {
+        name: Some("www".to_string()),
+        ty: RecordType::A,
+        content: content.to_string(),
+        ttl: None,
+        prio: None,
+    }
+}
+
+/// The notes sent with the last request to an endpoint.
+fn last_notes(server: &MockServer, endpoint: &str) -> serde_json::Value {
+    let request = server
+        .requests()
+        .into_iter()
+        .rfind(|x| x.path.contains(endpoint))
+        .unwrap();
+    serde_json::from_str::<serde_json::Value>(&request.body).unwrap()["notes"].clone()
+}
+
+#[test]
+fn reverted_edits_restore_the_original_notes() {
+    let server = MockServer::start();
+    server.respond("dns/retrieve", RECORD);
+    server.respond("dns/edit", fixtures::SUCCESS);
+    server.respond("dns/create", fixtures::DOMAIN_NOT_OPTED_IN);
+    let client = server.client().with_owner("porkbun-sync");
+
+    let changes = vec![
+        Change::Edit {
+            id: RecordId(106926656),
+            record: www("203.0.113.8"),
+        },
+        Change::Create(www("203.0.113.9")),
+    ];
+    let report = client.apply_transaction("example.com", changes).unwrap();
+    assert!(matches!(report.changes[0].1, ChangeOutcome::Reverted));
+    assert!(report.is_consistent());
+    assert_eq!(last_notes(&server, "/dns/edit/"), "managed by the web team");
+}
+
+#[test]
+fn reverted_deletes_recreate_the_original_notes() {
+    let server = MockServer::start();
+    server.respond("dns/retrieve", RECORD);
+    server.respond("dns/delete", fixtures::SUCCESS);
+    server.respond("dns/edit", fixtures::DOMAIN_NOT_OPTED_IN);
+    server.respond("dns/create", fixtures::CREATE);
+    let client = server.client().with_owner("porkbun-sync");
+
+    let changes = vec![
+        Change::Delete {
+            id: RecordId(106926656),
+        },
+        Change::Edit {
+            id: RecordId(106926657),
+            record: www("203.0.113.8"),
+        },
+    ];
+    let report = client.apply_transaction("example.com", changes).unwrap();
+    assert!(matches!(report.changes[0].1, ChangeOutcome::Reverted));
+    assert_eq!(
+        last_notes(&server, "/dns/create/"),
+        "managed by the web team"
+    );
+}
@@ -0,0 +1,71 @@
+use porkbun::testing::{fixtures, MockServer};
+use porkbun::{RecordId, TxtRecord};
+
+fn txt(content: &str) -> TxtRecord {
+    TxtRecord {
+        id: RecordId(1),
+        name: "selector._domainkey.example.com".to_string(),
+        text: content.to_string(),
+        ttl: "600".to_string(),
+        prio: "0".to_string(),
+        notes: None,
+    }
+}
+
+#[test]
+fn long_text_round_trips_through_segments() {
+    // A 2048-bit DKIM key, with a quote and backslash to check escaping.
+    let key = format!(
+        "v=DKIM1; k=rsa; p={}\"\\",
+        "MIIBIjANBgkqhkiG9w0B".repeat(20)
+    );
+    let record = txt(&TxtRecord::encode(&key));
+
+    let segments = record.segments();
+    assert_eq!(segments.len(), 2);
+    assert!(segments.iter().all(|x| x.len() <= 255));
+    assert_eq!(record.joined(), key);
+}
+
+#[test]
+fn segments_never_split_characters() {
+    let text = "é".repeat(200);
+    let record = txt(&TxtRecord::encode(&text));
+
+    let lengths: Vec<usize> = record.segments().iter().map(String::len).collect();
+    assert_eq!(lengths, [254, 146]);
+    assert_eq!(record.joined(), text);
+}
+
+#[test]
+fn short_and_unquoted_text_is_one_segment() {
+    assert_eq!(TxtRecord::encode("v=spf1 -all"), "v=spf1 -all");
+    assert_eq!(txt("v=spf1 -all").segments(), ["v=spf1 -all"]);
+    assert_eq!(txt(r#""v=spf1 " "-all""#).joined(), "v=spf1 -all");
+}
+
+#[test]
+fn create_txt_sends_split_content() {
+    let server = MockServer::start();
+    server.respond("dns/create", fixtures::CREATE);
+
+    server
+        .client()
+        .create_txt(
+            "example.com",
+            Some("selector._domainkey"),
+            &"k".repeat(300),
+            None,
+        )
+        .unwrap();
+    let create = server
+        .requests()
+        .into_iter()
+        .find(|x| x.path.contains("/dns/create/"))
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_str(&create.body).unwrap();
+    assert_eq!(
+        body["content"],
+        format!("\"{}\" \"{}\"", "k".repeat(255), "k".repeat(45))
+    );
+}
@@ -0,0 +1,57 @@
+use porkbun::testing::{fixtures, MockServer};
+use porkbun::{wildcard, RecordType};
+
+#[test]
+fn wildcard_names_are_kept_in_urls() {
+    let server = MockServer::start();
+    server.respond("dns/retrieveByNameType", fixtures::RETRIEVE_EMPTY);
+    server.respond("dns/deleteByNameType", fixtures::SUCCESS);
+    let client = server.client();
+
+    client
+        .fetch_records_by_name_type("example.com", RecordType::A, Some(&wildcard(None)))
+        .unwrap();
+    client
+        .delete_records_by_name_type("example.com", RecordType::A, Some(&wildcard(Some("dev"))))
+        .unwrap();
+
+    let paths: Vec<String> = server.requests().into_iter().map(|x| x.path).collect();
+    assert_eq!(
+        paths,
+        [
+            "/api/json/v3/dns/retrieveByNameType/example.com/A/*",
+            "/api/json/v3/dns/deleteByNameType/example.com/A/*.dev",
+        ]
+    );
+}
+
+#[test]
+fn special_characters_are_escaped() {
+    let server = MockServer::start();
+    server.respond("dns/retrieveByNameType", fixtures::RETRIEVE_EMPTY);
+
+    server
+        .client()
+        .fetch_records_by_name_type("example.com", RecordType::Txt, Some("a b/c?d"))
+        .unwrap();
+    assert_eq!(
+        server.requests()[0].path,
+        "/api/json/v3/dns/retrieveByNameType/example.com/TXT/a%20b%2Fc%3Fd"
+    );
+}
+
+#[test]
+fn apex_records_have_an_empty_subdomain() {
+    let server = MockServer::start();
+    server.respond("dns/retrieveByNameType", fixtures::RETRIEVE_BY_NAME_TYPE);
+
+    let records = server
+        .client()
+        .fetch_records_by_name_type("example.com", RecordType::A, None)
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(
+        server.requests()[0].path,
+        "/api/json/v3/dns/retrieveByNameType/example.com/A/"
+    );
+}